@@ -2,29 +2,46 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 
 /// Parse the advent of code arguments (not using clap to learn how this can be done)
-pub fn parse_arguments(name: &str) -> Arguments {
+/// # Arguments
+/// * `name` - The name of the program, for the help
+/// * `known` - The names of the options used by the solutions, any other option is rejected
+pub fn parse_arguments(name: &str, known: &[&[&str]]) -> Arguments {
     let mut args = std::env::args().skip(1);
     let mut day: Option<Day> = None;
     let mut input: Option<PathBuf> = None;
+    let mut options = Options::default();
     loop {
         match next_opt(&mut args) {
             Ok(Some(Opt::Day(d))) => day = Some(d),
             Ok(Some(Opt::Input(i))) => input = Some(i),
-            Ok(Some(Opt::Help)) => print_help_and_exit(name),
+            Ok(Some(Opt::Other(option, value))) => {
+                if !known.iter().any(|names| names.contains(&option.as_str())) {
+                    println!("unknown option: --{option}\n");
+                    print_help_and_exit(name, known);
+                }
+                options.0.push((option, value));
+            }
+            Ok(Some(Opt::Help)) => print_help_and_exit(name, known),
             Ok(None) => match (day, input) {
-                (Some(day), Some(input)) => return Arguments { day, input },
+                (Some(day), Some(input)) => {
+                    return Arguments {
+                        day,
+                        input,
+                        options,
+                    };
+                }
                 (None, _) => {
                     println!("'day' is required\n");
-                    print_help_and_exit(name)
+                    print_help_and_exit(name, known)
                 }
                 (_, None) => {
                     println!("'input' is required\n");
-                    print_help_and_exit(name)
+                    print_help_and_exit(name, known)
                 }
             },
             Err(reason) => {
                 println!("{reason}\n");
-                print_help_and_exit(name);
+                print_help_and_exit(name, known);
             }
         }
     }
@@ -37,6 +54,33 @@ pub struct Arguments {
     pub day: Day,
     /// The input for that day problem
     pub input: PathBuf,
+    /// The other options, given to the solutions of the days using them
+    pub options: Options,
+}
+
+/// The options given to the solutions, as their name and value (`--name` or `--name=value`)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options(Vec<(String, Option<String>)>);
+
+impl Options {
+    /// The options among some arguments (`--name` or `--name=value`), the others are ignored
+    pub fn parse<'a>(args: impl IntoIterator<Item = &'a str>) -> Self {
+        Self(args.into_iter().filter_map(option).collect())
+    }
+
+    /// True if an option was given (`--name` or `--name=value`)
+    pub fn has(&self, name: &str) -> bool {
+        self.0.iter().any(|(option, _)| option == name)
+    }
+
+    /// The values given to an option that can be repeated (`--name=value`)
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.0
+            .iter()
+            .filter(|(option, _)| option == name)
+            .filter_map(|(_, value)| value.clone())
+            .collect()
+    }
 }
 
 /// The Day of the Advent of Code problem to solve (between 01 and 25 or all)
@@ -74,17 +118,24 @@ pub enum Day {
 enum Opt {
     Day(Day),
     Input(PathBuf),
+    Other(String, Option<String>),
     Help,
 }
 
-fn print_help_and_exit(name: &str) -> ! {
+fn print_help_and_exit(name: &str, known: &[&[&str]]) -> ! {
+    let options = known.iter().flat_map(|names| names.iter()).copied();
+    let mut options: Vec<&str> = options.collect();
+    options.sort_unstable();
+    options.dedup();
     println!(
         "Solutions for the advent of code problems
   Usage: {name}.exe --day <DAY> --input <FILE>
   Options:
   -d, --day <DAY>     The specific day of the problem or 'all'
   -i, --input <FILE>  The problem's input. If day is 'all', a directory from 01..txt to 25.txt
-  -h, --help          Print help"
+  --<NAME>[=<VALUE>]  An option for the solution of some days: {}
+  -h, --help          Print help",
+        options.join(", ")
     );
     std::process::exit(1)
 }
@@ -130,11 +181,22 @@ fn next_opt<Args: Iterator<Item = String>>(args: &mut Args) -> Result<Option<Opt
         Ok(Some(Opt::Input(PathBuf::from(input.into_owned()))))
     } else if arg == "-h" || arg == "--help" {
         Ok(Some(Opt::Help))
+    } else if let Some((name, value)) = option(&arg) {
+        Ok(Some(Opt::Other(name, value)))
     } else {
         Err(format!("unknown argument: {arg}"))
     }
 }
 
+/// The name and value of an option argument (`--name` or `--name=value`)
+fn option(arg: &str) -> Option<(String, Option<String>)> {
+    let option = arg.strip_prefix("--").filter(|o| !o.is_empty())?;
+    Some(match option.split_once('=') {
+        Some((name, value)) => (name.to_owned(), Some(value.to_owned())),
+        None => (option.to_owned(), None),
+    })
+}
+
 fn opt_value<'a, Args: Iterator<Item = String>>(
     arg: &'a String,
     short: &str,
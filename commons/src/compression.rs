@@ -0,0 +1,323 @@
+//! Compress a path into a main routine calling a few movement functions, each of them (and the
+//! main routine) fitting in a limited number of ASCII characters.
+//!
+//! The functions are named `A`, `B`, `C`... in the order of their first call in the main routine,
+//! so that two solutions only differing by the names of their functions are not both found.
+//! The moves can be anything written as text, like the letters of a string:
+//! ```
+//! use commons::compression::Compressor;
+//!
+//! let path: Vec<char> = "abcabcdd".chars().collect();
+//! let compressed = Compressor::new(2, 5).unwrap().first(&path).unwrap();
+//! assert_eq!(compressed.main_routine(","), "A,A,B");
+//! assert_eq!(compressed.function(0, ","), "a,b,c");
+//! assert_eq!(compressed.function(1, ","), "d,d");
+//! assert_eq!(compressed.expand(), path);
+//! ```
+
+use std::fmt::Display;
+use std::ops::Range;
+
+use crate::error::Result;
+
+/// The maximum number of movement functions, named from `A` to `Z`
+pub const MAX_FUNCTIONS: usize = 26;
+
+/// Searches the ways to compress a path
+#[derive(Debug, Clone)]
+pub struct Compressor {
+    /// The maximum number of movement functions
+    functions: usize,
+    /// The maximum number of characters of the main routine and of each function
+    max_length: usize,
+    /// The characters between two moves, or two calls in the main routine
+    separator: String,
+}
+
+/// A path compressed as a main routine calling some movement functions
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Compressed<T> {
+    /// The index of the function called by each step of the main routine
+    main: Vec<usize>,
+    /// The moves of each function
+    functions: Vec<Vec<T>>,
+}
+
+/// The state of the search of the compressions of a path
+struct Search<'a, T> {
+    compressor: &'a Compressor,
+    path: &'a [T],
+    /// The number of characters of each move of the path
+    lengths: Vec<usize>,
+    /// The maximum number of calls in the main routine
+    max_calls: usize,
+    /// The current functions, as ranges of the path
+    functions: Vec<Range<usize>>,
+    /// The current main routine
+    main: Vec<usize>,
+}
+
+impl Compressor {
+    /// Create a compressor for up to `functions` functions (at most `MAX_FUNCTIONS`) of up to
+    /// `max_length` characters, separated by commas
+    pub fn new(functions: usize, max_length: usize) -> Result<Self> {
+        ensure!(
+            functions <= MAX_FUNCTIONS,
+            "At most {MAX_FUNCTIONS} functions can be named, not {functions}"
+        );
+        Ok(Self {
+            functions,
+            max_length,
+            separator: ",".to_owned(),
+        })
+    }
+
+    /// Use other characters than a comma between the moves and the calls
+    pub fn with_separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_owned();
+        self
+    }
+
+    /// Find the first compression of a path, trying to reuse the functions and to make them as
+    /// long as possible first
+    pub fn first<T: PartialEq + Display + Clone>(&self, path: &[T]) -> Option<Compressed<T>> {
+        let mut first = None;
+        Search::new(self, path).explore(0, &mut |compressed| {
+            first = Some(compressed);
+            false
+        });
+        first
+    }
+
+    /// Find all the compressions of a path
+    pub fn all<T: PartialEq + Display + Clone>(&self, path: &[T]) -> Vec<Compressed<T>> {
+        let mut all = Vec::new();
+        Search::new(self, path).explore(0, &mut |compressed| {
+            all.push(compressed);
+            true
+        });
+        all
+    }
+
+    /// Find the compression of a path with the smallest number of characters in total, the
+    /// first one found for equal lengths
+    pub fn optimal<T: PartialEq + Display + Clone>(&self, path: &[T]) -> Option<Compressed<T>> {
+        let mut optimal: Option<(usize, Compressed<T>)> = None;
+        Search::new(self, path).explore(0, &mut |compressed| {
+            let length = compressed.length(&self.separator);
+            let shorter = match &optimal {
+                Some((best, _)) => length < *best,
+                None => true,
+            };
+            if shorter {
+                optimal = Some((length, compressed));
+            }
+            true
+        });
+        optimal.map(|(_, compressed)| compressed)
+    }
+}
+
+impl<'a, T: PartialEq + Display + Clone> Search<'a, T> {
+    fn new(compressor: &'a Compressor, path: &'a [T]) -> Self {
+        let separator = compressor.separator.len();
+        Self {
+            compressor,
+            path,
+            lengths: path.iter().map(|step| step.to_string().len()).collect(),
+            max_calls: (compressor.max_length + separator) / (1 + separator),
+            functions: Vec::new(),
+            main: Vec::new(),
+        }
+    }
+
+    /// Explore the compressions of the path from a position, with the current functions
+    /// # Arguments
+    /// * `start` - The position in the path of the next move to compress
+    /// * `visit` - Called on each compression found, returns false to stop the search
+    ///
+    /// # Returns
+    /// False if the search was stopped
+    fn explore(&mut self, start: usize, visit: &mut impl FnMut(Compressed<T>) -> bool) -> bool {
+        if start == self.path.len() {
+            return visit(self.compressed());
+        }
+        if self.main.len() == self.max_calls {
+            return true;
+        }
+
+        for function in 0..self.functions.len() {
+            let range = self.functions[function].clone();
+            if self.path[start..].starts_with(&self.path[range.clone()]) {
+                self.main.push(function);
+                let running = self.explore(start + range.len(), visit);
+                self.main.pop();
+                if !running {
+                    return false;
+                }
+            }
+        }
+
+        if self.functions.len() < self.compressor.functions {
+            for end in (start + 1..=self.path.len()).rev() {
+                let moves = &self.path[start..end];
+                let known = self
+                    .functions
+                    .iter()
+                    .any(|function| &self.path[function.clone()] == moves);
+                if known || self.length(start..end) > self.compressor.max_length {
+                    continue;
+                }
+                self.main.push(self.functions.len());
+                self.functions.push(start..end);
+                let running = self.explore(end, visit);
+                self.functions.pop();
+                self.main.pop();
+                if !running {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// The number of characters of some moves of the path
+    fn length(&self, range: Range<usize>) -> usize {
+        let separators = (range.len() - 1) * self.compressor.separator.len();
+        self.lengths[range].iter().sum::<usize>() + separators
+    }
+
+    fn compressed(&self) -> Compressed<T> {
+        Compressed {
+            main: self.main.clone(),
+            functions: self
+                .functions
+                .iter()
+                .map(|range| self.path[range.clone()].to_vec())
+                .collect(),
+        }
+    }
+}
+
+impl<T: Display> Compressed<T> {
+    /// The name of a function in the main routine, None past the `MAX_FUNCTIONS` names
+    pub fn name(function: usize) -> Option<char> {
+        (function < MAX_FUNCTIONS).then(|| (b'A' + function as u8) as char)
+    }
+
+    /// The main routine, as the names of the functions called
+    pub fn main_routine(&self, separator: &str) -> String {
+        // The compressor never uses more functions than there are names
+        let names = self
+            .main
+            .iter()
+            .filter_map(|function| Self::name(*function));
+        join(names, separator)
+    }
+
+    /// The moves of a function, empty if there is no such function
+    pub fn function(&self, function: usize, separator: &str) -> String {
+        self.functions
+            .get(function)
+            .map(|moves| join(moves, separator))
+            .unwrap_or_default()
+    }
+
+    /// The total number of characters of the main routine and of the functions
+    pub fn length(&self, separator: &str) -> usize {
+        self.main_routine(separator).len()
+            + (0..self.functions.len())
+                .map(|function| self.function(function, separator).len())
+                .sum::<usize>()
+    }
+}
+
+impl<T: Clone> Compressed<T> {
+    /// The full path, by calling the functions of the main routine
+    pub fn expand(&self) -> Vec<T> {
+        self.main
+            .iter()
+            .flat_map(|function| self.functions[*function].iter().cloned())
+            .collect()
+    }
+}
+
+/// Write some elements separated by a separator
+fn join<T: Display>(elements: impl IntoIterator<Item = T>, separator: &str) -> String {
+    let mut joined = String::new();
+    for (index, element) in elements.into_iter().enumerate() {
+        if index > 0 {
+            joined.push_str(separator);
+        }
+        joined.push_str(&element.to_string());
+    }
+    joined
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn synthetic_paths() {
+        let a = ["R", "4", "L", "12"];
+        let b = ["L", "10"];
+        let path = [&a[..], &b, &a, &b, &a, &a].concat();
+        let compressed = Compressor::new(2, 12).unwrap().first(&path).unwrap();
+        assert_eq!(compressed.main_routine(","), "A,B,A,B,A,A");
+        assert_eq!(compressed.function(0, ","), "R,4,L,12");
+        assert_eq!(compressed.function(1, ","), "L,10");
+        assert_eq!(compressed.function(2, ","), "");
+        assert_eq!(compressed.expand(), path);
+        // "R,4,L,12,L,10" is 13 characters, too long for a function
+        assert_eq!(Compressor::new(1, 12).unwrap().first(&path), None);
+
+        let path = ["R", "L", "R", "L"];
+        let all = Compressor::new(2, 20).unwrap().all(&path);
+        let found: Vec<(String, String, String)> = all
+            .iter()
+            .map(|compressed| {
+                (
+                    compressed.main_routine(","),
+                    compressed.function(0, ","),
+                    compressed.function(1, ","),
+                )
+            })
+            .collect();
+        let expected = [
+            ("A", "R,L,R,L", ""),
+            ("A,B", "R,L,R", "L"),
+            ("A,A", "R,L", ""),
+            ("A,B", "R", "L,R,L"),
+            ("A,B,A,B", "R", "L"),
+        ];
+        assert_eq!(found.len(), expected.len());
+        for (main, first, second) in expected {
+            assert!(
+                found.contains(&(main.to_owned(), first.to_owned(), second.to_owned())),
+                "{main} with A = {first} and B = {second} in {found:?}"
+            );
+        }
+        for compressed in &all {
+            assert_eq!(compressed.expand(), path);
+        }
+
+        let optimal = Compressor::new(2, 20).unwrap().optimal(&path).unwrap();
+        assert_eq!(optimal.main_routine(","), "A,A");
+        assert_eq!(optimal.length(","), 6);
+
+        let compressor = Compressor::new(1, 3).unwrap();
+        assert!(compressor.first(&["R", "R"]).is_some());
+        let compressor = compressor.with_separator(", ");
+        assert_eq!(compressor.first(&["R", "R"]), None);
+    }
+
+    #[test]
+    fn function_names() {
+        assert_eq!(Compressed::<char>::name(0), Some('A'));
+        assert_eq!(Compressed::<char>::name(MAX_FUNCTIONS - 1), Some('Z'));
+        assert_eq!(Compressed::<char>::name(MAX_FUNCTIONS), None);
+        assert!(Compressor::new(MAX_FUNCTIONS, 20).is_ok());
+        assert!(Compressor::new(MAX_FUNCTIONS + 1, 20).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Describe a graph in the DOT language of Graphviz, to render the structure of a puzzle with
+//! `dot -Tsvg graph.dot > graph.svg` for example.
+//!
+//! The nodes are declared with their attributes, the edges can refer to nodes that were not
+//! declared (Graphviz creates them). All the identifiers and values are quoted.
+//! ```
+//! use commons::dot::Dot;
+//!
+//! let mut dot = Dot::directed("orbits").with_attribute("rankdir", "LR");
+//! dot.node("COM", &[("shape", "doublecircle")]);
+//! dot.edge("COM", "B", &[]);
+//! dot.edge("B", "C", &[("label", "1")]);
+//! assert_eq!(
+//!     dot.to_string(),
+//!     r#"digraph "orbits" {
+//!   rankdir="LR";
+//!   "COM" [shape="doublecircle"];
+//!   "COM" -> "B";
+//!   "B" -> "C" [label="1"];
+//! }
+//! "#
+//! );
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::error::{Result, WrapErr};
+
+/// The attributes of a node, an edge or a graph, as their names and values
+type Attributes = Vec<(String, String)>;
+
+/// A graph described in the DOT language
+#[derive(Debug, Clone)]
+pub struct Dot {
+    name: String,
+    /// True for a `digraph` with arrows, false for a `graph`
+    directed: bool,
+    attributes: Attributes,
+    nodes: Vec<(String, Attributes)>,
+    edges: Vec<(String, String, Attributes)>,
+}
+
+impl Dot {
+    /// Create a graph with directed edges
+    pub fn directed(name: &str) -> Self {
+        Self::new(name, true)
+    }
+
+    /// Create a graph with undirected edges
+    pub fn undirected(name: &str) -> Self {
+        Self::new(name, false)
+    }
+
+    fn new(name: &str, directed: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            directed,
+            attributes: Vec::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Add an attribute to the whole graph, like `rankdir` or `layout`
+    pub fn with_attribute(mut self, name: &str, value: &str) -> Self {
+        self.attributes.push((name.to_owned(), value.to_owned()));
+        self
+    }
+
+    /// Declare a node with some attributes, like `shape`, `label` or `color`
+    pub fn node(&mut self, id: &str, attributes: &[(&str, &str)]) {
+        self.nodes.push((id.to_owned(), owned(attributes)));
+    }
+
+    /// Add an edge between two nodes with some attributes, like `label` or `weight`
+    pub fn edge(&mut self, from: &str, to: &str, attributes: &[(&str, &str)]) {
+        let edge = (from.to_owned(), to.to_owned(), owned(attributes));
+        self.edges.push(edge);
+    }
+
+    /// The number of declared nodes
+    pub fn nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The number of edges
+    pub fn edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Save the graph in a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).wrap_err_with(|| format!("Could not create {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        write!(writer, "{self}")
+            .and_then(|_| writer.flush())
+            .wrap_err_with(|| format!("Could not write the graph in {path:?}"))
+    }
+}
+
+impl Display for Dot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+        writeln!(f, "{kind} {} {{", Quoted(&self.name))?;
+        for (name, value) in &self.attributes {
+            writeln!(f, "  {name}={};", Quoted(value))?;
+        }
+        for (id, attributes) in &self.nodes {
+            writeln!(f, "  {}{};", Quoted(id), List(attributes))?;
+        }
+        for (from, to, attributes) in &self.edges {
+            let (from, to) = (Quoted(from), Quoted(to));
+            writeln!(f, "  {from} {arrow} {to}{};", List(attributes))?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn owned(attributes: &[(&str, &str)]) -> Attributes {
+    attributes
+        .iter()
+        .map(|(name, value)| ((*name).to_owned(), (*value).to_owned()))
+        .collect()
+}
+
+/// Displays a string between double quotes, escaping the quotes and the backslashes in it
+struct Quoted<'a>(&'a str);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                c => write!(f, "{c}")?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+/// Displays a list of attributes between brackets, with a leading space (nothing if empty)
+struct List<'a>(&'a Attributes);
+
+impl Display for List<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (index, (name, value)) in self.0.iter().enumerate() {
+            let separator = if index == 0 { " [" } else { ", " };
+            write!(f, "{separator}{name}={}", Quoted(value))?;
+        }
+        if !self.0.is_empty() {
+            f.write_str("]")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undirected_graph() {
+        let mut dot = Dot::undirected("caves");
+        dot.node("start", &[("shape", "box"), ("color", "green")]);
+        dot.edge("start", "A", &[]);
+        dot.edge("A", "end", &[("penwidth", "2")]);
+        assert_eq!((dot.nodes(), dot.edges()), (1, 2));
+        assert_eq!(
+            dot.to_string(),
+            [
+                "graph \"caves\" {",
+                "  \"start\" [shape=\"box\", color=\"green\"];",
+                "  \"start\" -- \"A\";",
+                "  \"A\" -- \"end\" [penwidth=\"2\"];",
+                "}\n",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn escaped_identifiers() {
+        let mut dot = Dot::directed("a \"quoted\" name");
+        dot.node("back\\slash", &[("label", "two\nlines")]);
+        assert_eq!(
+            dot.to_string(),
+            [
+                r#"digraph "a \"quoted\" name" {"#,
+                r#"  "back\\slash" [label="two\nlines"];"#,
+                "}\n",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,194 @@
+//! Compare two grids cell by cell, to show where a simulation went wrong in a failed test.
+//!
+//! The grids are drawn side by side, with a third grid marking the cells that changed (or with
+//! colors for a terminal). The grids can have different sizes, the cells missing in one of them
+//! count as differences.
+//! ```
+//! use commons::grid::Grid;
+//!
+//! let expected = Grid::from_text("#..\n#.o");
+//! let actual = Grid::from_text("#o.\n#.o\noo");
+//! let diff = expected.diff(&actual, |c| *c);
+//! assert_eq!(diff.count(), 4);
+//! assert_eq!(
+//!     diff.to_string(),
+//!     "4 differences (left 3x2, right 3x3)
+//! left  right diff
+//! #..   #o.   .*.
+//! #.o   #.o   ...
+//!       oo    ***
+//! "
+//! );
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::Grid;
+
+/// The marker of a cell that changed
+const CHANGED: char = '*';
+
+/// The marker of a cell that did not change
+const SAME: char = '.';
+
+/// The differences between two grids, see `Grid::diff`
+#[derive(Debug, Clone)]
+pub struct Diff {
+    /// The characters of the cells of both grids, on a grid big enough for both
+    cells: Grid<(Option<char>, Option<char>)>,
+    /// The sizes of the grids
+    sizes: [(usize, usize); 2],
+    /// The number of cells that changed
+    count: usize,
+    /// True to color the changed cells for a terminal instead of marking them
+    colors: bool,
+}
+
+impl<T: PartialEq> Grid<T> {
+    /// Compare this grid (on the left) with another one (on the right)
+    /// # Arguments
+    /// * `other` - The grid to compare with
+    /// * `char` - The character drawn for each element of the grids
+    pub fn diff(&self, other: &Grid<T>, char: impl Fn(&T) -> char) -> Diff {
+        let sizes = [size(self), size(other)];
+        let width = sizes[0].0.max(sizes[1].0);
+        let height = sizes[0].1.max(sizes[1].1);
+        let mut count = 0;
+        let cells = Grid::tabulate(width, height, |(x, y)| {
+            let position = (x as isize, y as isize);
+            let (left, right) = (self.get(position), other.get(position));
+            if left != right {
+                count += 1;
+            }
+            (left.map(&char), right.map(&char))
+        });
+        Diff {
+            cells,
+            sizes,
+            count,
+            colors: false,
+        }
+    }
+}
+
+impl Grid<char> {
+    /// A grid of the characters of some lines (like in the puzzle statements), the short lines
+    /// being completed with spaces
+    pub fn from_text(text: &str) -> Self {
+        let width = text.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut grid = Grid::new(width, text.lines().count());
+        for line in text.lines() {
+            let mut line: Vec<char> = line.chars().collect();
+            line.resize(width, ' ');
+            grid.push_line(line);
+        }
+        grid
+    }
+}
+
+/// The size of a grid, even if it is empty
+fn size<T>(grid: &Grid<T>) -> (usize, usize) {
+    if grid.width() == 0 {
+        (0, 0)
+    } else {
+        grid.size()
+    }
+}
+
+impl Diff {
+    /// The number of cells that changed
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// True if the grids are the same
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Color the changed cells (red on the left, green on the right) with ANSI escape codes
+    /// instead of drawing the third grid of markers
+    pub fn with_colors(mut self) -> Self {
+        self.colors = true;
+        self
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let [(left_width, left_height), (right_width, right_height)] = self.sizes;
+        writeln!(
+            f,
+            "{} differences (left {left_width}x{left_height}, right {right_width}x{right_height})",
+            self.count
+        )?;
+        // Each grid is at least as wide as its title, and separated from the next one
+        let width = self.cells.width().max(5) + 1;
+        if self.colors {
+            writeln!(f, "{:width$}right", "left")?;
+        } else {
+            writeln!(f, "{:width$}{:width$}diff", "left", "right")?;
+        }
+
+        for line in self.cells.lines() {
+            let mut text = String::new();
+            for (index, color) in [(0, "31"), (1, "32")] {
+                for &(left, right) in line {
+                    let cell = if index == 0 { left } else { right };
+                    let cell = cell.unwrap_or(' ');
+                    if self.colors && left != right {
+                        text.push_str(&format!("\x1b[1;{color}m{cell}\x1b[0m"));
+                    } else {
+                        text.push(cell);
+                    }
+                }
+                text.push_str(&" ".repeat(width - line.len()));
+            }
+            if self.colors {
+                text.truncate(text.trim_end().len());
+            } else {
+                text.extend(
+                    line.iter()
+                        .map(|(l, r)| if l != r { CHANGED } else { SAME }),
+                );
+            }
+            writeln!(f, "{text}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_grids() {
+        let grid = Grid::from_text("ab\ncd");
+        let diff = grid.diff(&grid.clone(), |c| *c);
+        assert!(diff.is_empty());
+        assert_eq!(
+            diff.to_string(),
+            "0 differences (left 2x2, right 2x2)\nleft  right diff\nab    ab    ..\ncd    cd    ..\n"
+        );
+    }
+
+    #[test]
+    fn colored_differences() {
+        let left: Grid<u8> = Grid::from_vec(2, vec![1, 2, 3, 4]);
+        let right = Grid::from_vec(3, vec![1, 0, 3]);
+        let diff = left.diff(&right, |n| (b'0' + n) as char).with_colors();
+        assert_eq!(diff.count(), 4);
+        assert_eq!(
+            diff.to_string(),
+            [
+                "4 differences (left 2x2, right 3x1)",
+                "left  right",
+                "1\x1b[1;31m2\x1b[0m\x1b[1;31m \x1b[0m   1\x1b[1;32m0\x1b[0m\x1b[1;32m3\x1b[0m",
+                "\x1b[1;31m3\x1b[0m\x1b[1;31m4\x1b[0m    \x1b[1;32m \x1b[0m\x1b[1;32m \x1b[0m",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
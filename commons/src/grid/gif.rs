@@ -0,0 +1,529 @@
+//! Record the successive states of a simulation as an animated GIF, written without any external
+//! library (with its own LZW encoder).
+//!
+//! Each captured grid becomes a frame, its elements are drawn with the colors of a palette (up to
+//! 256 colors). The grids can be placed at some origin, for the worlds growing in all directions:
+//! the animation then covers all the frames, or only the cropped area if there is one.
+//! ```
+//! use commons::grid::Grid;
+//! use commons::grid::gif::Recorder;
+//!
+//! let mut recorder = Recorder::new(&[[0, 0, 0], [255, 255, 255]]).with_skip(2);
+//! let mut grid = Grid::fill(4, 3, false);
+//! for x in 0..4 {
+//!     grid[(x, 1)] = true;
+//!     recorder.capture(&grid, |lit| u8::from(*lit));
+//! }
+//! assert_eq!(recorder.frames(), 2);
+//!
+//! let mut gif = Vec::new();
+//! recorder.write(&mut gif).unwrap();
+//! assert!(gif.starts_with(b"GIF89a"));
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use crate::arguments::Options;
+use crate::error::{Result, WrapErr};
+use crate::grid::image::Rgb;
+use crate::grid::Grid;
+
+/// The largest code of the LZW compression of the GIF format
+const MAX_CODE: u16 = 4095;
+
+/// Records the frames of an animation
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    /// The colors of the animation
+    palette: Vec<Rgb>,
+    /// The size of the square of pixels drawn for each element
+    scale: usize,
+    /// Only one step out of this number is recorded
+    skip: usize,
+    /// The only area to record: x, y, width and height
+    crop: Option<(i64, i64, usize, usize)>,
+    /// The time between two frames, in hundredths of a second
+    delay: u16,
+    /// The number of captures asked, recorded or skipped
+    steps: usize,
+    frames: Vec<Frame>,
+}
+
+/// A recorded frame, as palette indices
+#[derive(Debug, Clone)]
+struct Frame {
+    x: i64,
+    y: i64,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Recorder {
+    /// The names of the options configuring a recorder
+    pub const OPTIONS: &'static [&'static str] = &["gif", "scale", "skip", "crop", "delay"];
+
+    /// Create a recorder with some colors, only the first 256 are used
+    pub fn new(palette: &[Rgb]) -> Self {
+        Self {
+            palette: palette.iter().copied().take(256).collect(),
+            scale: 1,
+            skip: 1,
+            crop: None,
+            delay: 10,
+            steps: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Create a recorder configured from some options, if the `gif` option is given:
+    /// - `--gif=<FILE>` - The file to save the animation in
+    /// - `--scale=<PIXELS>` - The size of the square drawn for each element
+    /// - `--skip=<STEPS>` - Record one step out of this number
+    /// - `--crop=<X>,<Y>,<WIDTH>,<HEIGHT>` - Only record an area
+    /// - `--delay=<HUNDREDTHS>` - The time between two frames
+    ///
+    /// # Returns
+    /// The recorder and the path where the animation should be saved
+    pub fn from_options(options: &Options, palette: &[Rgb]) -> Result<Option<(Self, String)>> {
+        let Some(path) = options.values("gif").into_iter().next() else {
+            return Ok(None);
+        };
+        let number = |name: &str| -> Result<Option<usize>> {
+            options
+                .values(name)
+                .first()
+                .map(|value| {
+                    value
+                        .parse()
+                        .wrap_err_with(|| format!("Invalid --{name} '{value}'"))
+                })
+                .transpose()
+        };
+
+        let mut recorder = Self::new(palette);
+        if let Some(scale) = number("scale")? {
+            recorder = recorder.with_scale(scale);
+        }
+        if let Some(skip) = number("skip")? {
+            recorder = recorder.with_skip(skip);
+        }
+        if let Some(delay) = number("delay")? {
+            recorder = recorder.with_delay(delay.min(u16::MAX as usize) as u16);
+        }
+        if let Some(crop) = options.values("crop").first() {
+            let values = crop
+                .split(',')
+                .map(|value| value.trim().parse::<i64>())
+                .collect::<std::result::Result<Vec<i64>, _>>()
+                .wrap_err_with(|| format!("Invalid --crop '{crop}'"))?;
+            match values[..] {
+                [x, y, width, height] if width > 0 && height > 0 => {
+                    recorder = recorder.with_crop((x, y), (width as usize, height as usize));
+                }
+                _ => bail!("Expected --crop=<X>,<Y>,<WIDTH>,<HEIGHT>, got '{crop}'"),
+            }
+        }
+        Ok(Some((recorder, path)))
+    }
+
+    /// Draw each element as a square of this size (at least 1)
+    pub fn with_scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Only record one step out of this number (the first one, then every `skip` steps)
+    pub fn with_skip(mut self, skip: usize) -> Self {
+        self.skip = skip.max(1);
+        self
+    }
+
+    /// Only record an area of the grids
+    /// # Arguments
+    /// * `corner` - The top left corner of the area (x, y)
+    /// * `size` - The width and height of the area
+    pub fn with_crop(mut self, corner: (i64, i64), size: (usize, usize)) -> Self {
+        self.crop = Some((corner.0, corner.1, size.0, size.1));
+        self
+    }
+
+    /// Wait this time between two frames, in hundredths of a second
+    pub fn with_delay(mut self, delay: u16) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// The number of frames recorded
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Capture a step of the simulation, with the top left corner of the grid at (0, 0)
+    /// # Arguments
+    /// * `grid` - The state of the simulation
+    /// * `color` - The index in the palette of the color of an element
+    pub fn capture<T>(&mut self, grid: &Grid<T>, color: impl Fn(&T) -> u8) {
+        self.capture_at(grid, (0, 0), color);
+    }
+
+    /// Capture a step of the simulation, with the top left corner of the grid at some position
+    pub fn capture_at<T>(&mut self, grid: &Grid<T>, origin: (i64, i64), color: impl Fn(&T) -> u8) {
+        let step = self.steps;
+        self.steps += 1;
+        if step % self.skip != 0 || grid.width() == 0 {
+            return;
+        }
+
+        let (width, height) = grid.size();
+        let (x, y, width, height) = match self.crop {
+            // Only keep the part of the grid in the cropped area
+            Some((x, y, crop_width, crop_height)) => {
+                let left = x.max(origin.0);
+                let top = y.max(origin.1);
+                let right = (x + crop_width as i64).min(origin.0 + width as i64);
+                let bottom = (y + crop_height as i64).min(origin.1 + height as i64);
+                let size = |from: i64, to: i64| (to - from).max(0) as usize;
+                (left, top, size(left, right), size(top, bottom))
+            }
+            None => (origin.0, origin.1, width, height),
+        };
+        let mut pixels = Vec::with_capacity(width * height);
+        for dy in 0..height as i64 {
+            for dx in 0..width as i64 {
+                let position = ((x + dx - origin.0) as isize, (y + dy - origin.1) as isize);
+                pixels.push(grid.get(position).map_or(0, &color));
+            }
+        }
+        self.frames.push(Frame {
+            x,
+            y,
+            width,
+            height,
+            pixels,
+        });
+    }
+
+    /// The area covered by the animation: x, y, width and height
+    fn canvas(&self) -> (i64, i64, usize, usize) {
+        if let Some(crop) = self.crop {
+            return crop;
+        }
+        let frames = self
+            .frames
+            .iter()
+            .filter(|frame| frame.width * frame.height > 0);
+        let (left, top, right, bottom) = frames.fold(
+            (i64::MAX, i64::MAX, i64::MIN, i64::MIN),
+            |(left, top, right, bottom), frame| {
+                (
+                    left.min(frame.x),
+                    top.min(frame.y),
+                    right.max(frame.x + frame.width as i64),
+                    bottom.max(frame.y + frame.height as i64),
+                )
+            },
+        );
+        if left > right {
+            (0, 0, 0, 0)
+        } else {
+            (left, top, (right - left) as usize, (bottom - top) as usize)
+        }
+    }
+
+    /// Write the animation in the GIF format, looping forever
+    pub fn write(&self, writer: &mut impl Write) -> IoResult<()> {
+        let (left, top, width, height) = self.canvas();
+        let (width, height) = (width * self.scale, height * self.scale);
+        let limit = |size: usize| size.min(u16::MAX as usize) as u16;
+        // The color table has a power of 2 colors, at least 2
+        let bits = (1..=8)
+            .find(|bits| 1 << bits >= self.palette.len())
+            .unwrap_or(8);
+
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&limit(width).to_le_bytes())?;
+        writer.write_all(&limit(height).to_le_bytes())?;
+        // A global color table with 8 bits per channel, the background is the first color
+        writer.write_all(&[0xf0 | (bits - 1), 0, 0])?;
+        for index in 0..1 << bits {
+            writer.write_all(&self.palette.get(index).copied().unwrap_or_default())?;
+        }
+        // Loop the animation forever
+        writer.write_all(&[0x21, 0xff, 11])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[3, 1, 0, 0, 0])?;
+
+        for frame in &self.frames {
+            // The graphic control extension: keep the frame until the next one, with a delay
+            writer.write_all(&[0x21, 0xf9, 4, 0x04])?;
+            writer.write_all(&self.delay.to_le_bytes())?;
+            writer.write_all(&[0, 0])?;
+
+            // The whole canvas is drawn for each frame
+            writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+            writer.write_all(&limit(width).to_le_bytes())?;
+            writer.write_all(&limit(height).to_le_bytes())?;
+            writer.write_all(&[0])?;
+
+            let mut pixels = Vec::with_capacity(width * height);
+            for y in 0..height {
+                let y = top + (y / self.scale) as i64 - frame.y;
+                for x in 0..width {
+                    let x = left + (x / self.scale) as i64 - frame.x;
+                    let inside = (0..frame.width as i64).contains(&x)
+                        && (0..frame.height as i64).contains(&y);
+                    pixels.push(if inside {
+                        frame.pixels[y as usize * frame.width + x as usize]
+                    } else {
+                        0
+                    });
+                }
+            }
+            let minimum = bits.max(2);
+            writer.write_all(&[minimum])?;
+            for block in lzw(&pixels, minimum).chunks(255) {
+                writer.write_all(&[block.len() as u8])?;
+                writer.write_all(block)?;
+            }
+            writer.write_all(&[0])?;
+        }
+        writer.write_all(&[0x3b])
+    }
+
+    /// Save the animation in a GIF file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).wrap_err_with(|| format!("Could not create {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer)
+            .and_then(|_| writer.flush())
+            .wrap_err_with(|| format!("Could not write the animation in {path:?}"))
+    }
+}
+
+/// Writes codes of variable sizes, from the least significant bit
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    length: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= u32::from(code) << self.length;
+        self.length += size;
+        while self.length >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.length -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.length > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compress some indices with the variable length LZW of the GIF format
+/// # Arguments
+/// * `data` - The indices to compress, each of them below `1 << minimum`
+/// * `minimum` - The minimum code size, the number of bits of the indices (at least 2)
+fn lzw(data: &[u8], minimum: u8) -> Vec<u8> {
+    let clear: u16 = 1 << minimum;
+    let end = clear + 1;
+    let mut output = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        length: 0,
+    };
+    let mut codes: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next = end + 1;
+    let mut size = minimum + 1;
+    output.write(clear, size);
+
+    let mut pixels = data.iter();
+    let Some(first) = pixels.next() else {
+        output.write(end, size);
+        return output.finish();
+    };
+    let mut prefix = u16::from(*first);
+    for pixel in pixels {
+        if let Some(code) = codes.get(&(prefix, *pixel)) {
+            prefix = *code;
+            continue;
+        }
+        output.write(prefix, size);
+        if next <= MAX_CODE {
+            codes.insert((prefix, *pixel), next);
+            next += 1;
+            // The decoder adds each code one step later, it reads the next one with this size
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        } else {
+            // The table is full, start again from the single indices
+            output.write(clear, size);
+            codes.clear();
+            next = end + 1;
+            size = minimum + 1;
+        }
+        prefix = u16::from(*pixel);
+    }
+    output.write(prefix, size);
+    output.write(end, size);
+    output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode a LZW stream of the GIF format
+    fn decode(data: &[u8], minimum: u8) -> Vec<u8> {
+        let clear = 1u16 << minimum;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..clear).map(|index| vec![index as u8]).collect();
+            table.push(Vec::new());
+            table.push(Vec::new());
+        };
+        reset(&mut table);
+        let mut size = minimum + 1;
+        let (mut position, mut output) = (0, Vec::new());
+        let mut previous: Option<Vec<u8>> = None;
+        loop {
+            let mut code = 0u16;
+            for bit in 0..size as usize {
+                let byte = data[(position + bit) / 8];
+                code |= u16::from((byte >> ((position + bit) % 8)) & 1) << bit;
+            }
+            position += size as usize;
+            if code == clear {
+                reset(&mut table);
+                size = minimum + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return output;
+            }
+            let entry = match (table.get(code as usize), &previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => [&previous[..], &previous[..1]].concat(),
+                (None, None) => panic!("Unknown first code {code}"),
+            };
+            output.extend(&entry);
+            if let Some(previous) = previous {
+                if table.len() <= MAX_CODE as usize {
+                    table.push([&previous[..], &entry[..1]].concat());
+                }
+            }
+            if table.len() == 1 << size && size < 12 {
+                size += 1;
+            }
+            previous = Some(entry);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trip() {
+        assert_eq!(decode(&lzw(&[], 2), 2), []);
+        let small = [1, 1, 1, 1, 2, 0, 1, 2, 0, 1, 3, 3, 3];
+        assert_eq!(decode(&lzw(&small, 2), 2), small);
+
+        // Enough varied data to fill the table a few times
+        let mut state = 12_345_u32;
+        let noise: Vec<u8> = (0..60_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        assert_eq!(decode(&lzw(&noise, 8), 8), noise);
+        let shades: Vec<u8> = noise.iter().map(|value| value % 5).collect();
+        assert_eq!(decode(&lzw(&shades, 3), 3), shades);
+    }
+
+    #[test]
+    fn recorder_frames() {
+        let palette = [[0, 0, 0], [255, 0, 0], [0, 255, 0]];
+        let mut recorder = Recorder::new(&palette).with_scale(2).with_delay(5);
+        let grid = Grid::from_vec(2, vec![1, 2, 2, 1]);
+        recorder.capture(&grid, |value| *value);
+        recorder.capture_at(&grid, (1, -1), |value| *value);
+        assert_eq!(recorder.frames(), 2);
+        assert_eq!(recorder.canvas(), (0, -1, 3, 3));
+
+        let mut gif = Vec::new();
+        recorder.write(&mut gif).unwrap();
+        assert_eq!(gif[..6], *b"GIF89a");
+        // The canvas of 3 * 3 elements of 2 * 2 pixels, with 4 colors in the table
+        assert_eq!(gif[6..13], [6, 0, 6, 0, 0xf1, 0, 0]);
+        assert_eq!(gif[13..25], [0, 0, 0, 255, 0, 0, 0, 255, 0, 0, 0, 0]);
+        assert_eq!(gif[gif.len() - 1], 0x3b);
+
+        // The first frame, with its control extension
+        let frame = &gif[25 + 19..];
+        assert_eq!(frame[..8], [0x21, 0xf9, 4, 0x04, 5, 0, 0, 0]);
+        assert_eq!(frame[8..18], [0x2c, 0, 0, 0, 0, 6, 0, 6, 0, 0]);
+        assert_eq!(frame[18], 2);
+        let length = frame[19] as usize;
+        let pixels = decode(&frame[20..20 + length], 2);
+        let expected: Vec<u8> = [[0, 0, 0], [1, 2, 0], [2, 1, 0]]
+            .iter()
+            .flat_map(|line| {
+                let line: Vec<u8> = line.iter().flat_map(|value| [*value; 2]).collect();
+                [line.clone(), line].concat()
+            })
+            .collect();
+        assert_eq!(pixels, expected);
+    }
+
+    #[test]
+    fn recorder_skip_and_crop() {
+        let mut recorder = Recorder::new(&[[0; 3], [255; 3]])
+            .with_skip(3)
+            .with_crop((1, 1), (2, 2));
+        let grid = Grid::tabulate(4, 4, |(x, y)| (x + y) as u8 % 2);
+        for _ in 0..7 {
+            recorder.capture(&grid, |value| *value);
+        }
+        assert_eq!(recorder.frames(), 3);
+        assert_eq!(recorder.canvas(), (1, 1, 2, 2));
+        assert_eq!(recorder.frames[0].pixels, [0, 1, 1, 0]);
+
+        // A grid partially in the cropped area, after two skipped steps
+        for _ in 0..3 {
+            recorder.capture_at(&grid, (2, 2), |value| *value);
+        }
+        assert_eq!(recorder.frames(), 4);
+        let frame = &recorder.frames[3];
+        assert_eq!((frame.x, frame.y, frame.width, frame.height), (2, 2, 1, 1));
+    }
+
+    #[test]
+    fn recorder_options() {
+        let palette = [[0; 3], [255; 3]];
+        let options = Options::parse(["--skip=2", "--crop=1,2,3,4"]);
+        assert!(Recorder::from_options(&options, &palette)
+            .unwrap()
+            .is_none());
+
+        let options = Options::parse(["--gif=steps.gif", "--scale=3", "--crop=1,2,3,4"]);
+        let (recorder, path) = Recorder::from_options(&options, &palette).unwrap().unwrap();
+        assert_eq!(path, "steps.gif");
+        assert_eq!((recorder.scale, recorder.skip), (3, 1));
+        assert_eq!(recorder.canvas(), (1, 2, 3, 4));
+
+        let options = Options::parse(["--gif=steps.gif", "--crop=1,2,3"]);
+        assert!(Recorder::from_options(&options, &palette).is_err());
+    }
+}
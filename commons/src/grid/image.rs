@@ -0,0 +1,300 @@
+//! Export a grid as an image, to inspect the large maps that do not fit in a terminal.
+//!
+//! Two formats are supported, both written without any external library:
+//! - PPM (binary `P6`), the simplest image format
+//! - PNG, with its zlib stream made of uncompressed blocks
+//! ```
+//! use commons::grid::Grid;
+//!
+//! let grid = Grid::from_vec(2, vec![true, false, false, true]);
+//! let image = grid.to_image(3, |lit| if *lit { [255, 255, 255] } else { [0, 0, 0] });
+//! assert_eq!((image.width(), image.height()), (6, 6));
+//! assert_eq!(image.get(4, 1), Some([0, 0, 0]));
+//!
+//! let mut ppm = Vec::new();
+//! image.write_ppm(&mut ppm).unwrap();
+//! assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+//! ```
+
+use std::fs::File;
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use crate::error::{Result, WrapErr};
+use crate::grid::Grid;
+
+/// A color, as its red, green and blue components
+pub type Rgb = [u8; 3];
+
+/// An image, as the colors of its pixels line by line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+/// The signature at the start of all the PNG files
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// The maximum size of an uncompressed deflate block
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+/// The CRC-32 of each byte, for the chunks of a PNG
+const CRC_TABLE: [u32; 256] = crc_table();
+
+impl<T> Grid<T> {
+    /// Draw this grid as an image
+    /// # Arguments
+    /// * `scale` - The size of the square of pixels drawn for each element
+    /// * `palette` - The color of each element
+    pub fn to_image(&self, scale: usize, palette: impl Fn(&T) -> Rgb) -> Image {
+        let (width, height) = if self.width() == 0 {
+            (0, 0)
+        } else {
+            self.size()
+        };
+        let mut image = Image::new(width * scale, height * scale, [0; 3]);
+        for ((x, y), element) in self.indexed_values() {
+            let color = palette(element);
+            for dy in 0..scale {
+                let start = (y as usize * scale + dy) * image.width + x as usize * scale;
+                image.pixels[start..start + scale].fill(color);
+            }
+        }
+        image
+    }
+}
+
+impl Image {
+    /// Create an image filled with a color
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// The number of pixels in a line
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of lines
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The color of a pixel, if it is in the image
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.pixels[y * self.width + x])
+    }
+
+    /// Change the color of a pixel, the pixels out of the image are ignored
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    /// Write this image in the binary PPM format
+    pub fn write_ppm(&self, writer: &mut impl Write) -> IoResult<()> {
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.pixels.concat())
+    }
+
+    /// Write this image in the PNG format, as 8 bits RGB without compression
+    pub fn write_png(&self, writer: &mut impl Write) -> IoResult<()> {
+        writer.write_all(&PNG_SIGNATURE)?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, deflate, no filter and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+        write_chunk(writer, b"IHDR", &header)?;
+
+        // Each line starts with its filter type, none here
+        let mut raw = Vec::with_capacity((3 * self.width + 1) * self.height);
+        for line in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(line.concat());
+        }
+        write_chunk(writer, b"IDAT", &zlib_stored(&raw))?;
+        write_chunk(writer, b"IEND", &[])
+    }
+
+    /// Save this image in a file, as a PPM or a PNG depending on its extension
+    /// # Errors
+    /// If the extension is not `ppm` or `png`, or if the file could not be written
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let png = match extension {
+            Some("png") => true,
+            Some("ppm") => false,
+            _ => bail!("Unknown image format for {path:?}, expected a .png or a .ppm"),
+        };
+        let file = File::create(path).wrap_err_with(|| format!("Could not create {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        let written = if png {
+            self.write_png(&mut writer)
+        } else {
+            self.write_ppm(&mut writer)
+        };
+        written
+            .and_then(|_| writer.flush())
+            .wrap_err_with(|| format!("Could not write the image in {path:?}"))
+    }
+}
+
+/// Write a PNG chunk, with its length and its CRC
+fn write_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> IoResult<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+    let crc = !kind
+        .iter()
+        .chain(data)
+        .fold(!0, |crc, byte| update_crc(crc, *byte));
+    writer.write_all(&crc.to_be_bytes())
+}
+
+/// Wrap some data in a zlib stream, with uncompressed deflate blocks
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let blocks = data.len() / MAX_STORED_BLOCK + 1;
+    let mut stream = Vec::with_capacity(data.len() + 5 * blocks + 6);
+    // Deflate with a 32K window, no dictionary and the check bits of the header
+    stream.extend([0x78, 0x01]);
+    let mut chunks = data.chunks(MAX_STORED_BLOCK).peekable();
+    if chunks.peek().is_none() {
+        stream.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(chunk) = chunks.next() {
+        let last = chunks.peek().is_none();
+        let length = chunk.len() as u16;
+        stream.push(u8::from(last));
+        stream.extend(length.to_le_bytes());
+        stream.extend((!length).to_le_bytes());
+        stream.extend(chunk);
+    }
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+/// The Adler-32 checksum of some data, for the end of a zlib stream
+fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65_521;
+    let (a, b) = data.iter().fold((1, 0), |(a, b), byte| {
+        let a = (a + u32::from(*byte)) % MODULO;
+        (a, (b + a) % MODULO)
+    });
+    (b << 16) | a
+}
+
+/// Add a byte to a running CRC-32
+fn update_crc(crc: u32, byte: u8) -> u32 {
+    CRC_TABLE[((crc ^ u32::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+}
+
+/// Compute the CRC-32 of each byte
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut byte = 0;
+    while byte < 256 {
+        let mut crc = byte as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[byte] = crc;
+        byte += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb = [255, 0, 0];
+    const BLUE: Rgb = [0, 0, 255];
+
+    #[test]
+    fn grid_to_ppm() {
+        let grid = Grid::from_vec(2, vec!['a', 'b']);
+        let image = grid.to_image(2, |c| if *c == 'a' { RED } else { BLUE });
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend([RED, RED, BLUE, BLUE].concat());
+        }
+        assert_eq!(ppm, expected);
+    }
+
+    #[test]
+    fn grid_to_png() {
+        let mut image = Image::new(3, 2, RED);
+        image.set(1, 1, BLUE);
+        image.set(5, 5, BLUE);
+        assert_eq!(image.get(1, 1), Some(BLUE));
+        assert_eq!(image.get(3, 0), None);
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+
+        assert_eq!(png[..8], PNG_SIGNATURE);
+        // Walk through the chunks, checking their CRC
+        let mut chunks = Vec::new();
+        let mut rest = &png[8..];
+        while !rest.is_empty() {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            let (kind, data) = (&rest[4..8], &rest[8..8 + length]);
+            let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+            let expected = !kind.iter().chain(data).fold(!0, |c, b| update_crc(c, *b));
+            assert_eq!(crc, expected);
+            chunks.push((kind.to_vec(), data.to_vec()));
+            rest = &rest[12 + length..];
+        }
+        let kinds: Vec<&[u8]> = chunks.iter().map(|(kind, _)| &kind[..]).collect();
+        assert_eq!(kinds, [b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 3, 0, 0, 0, 2, 8, 2, 0, 0, 0]);
+
+        let raw: Vec<u8> = [
+            &[0][..],
+            &[RED, RED, RED].concat(),
+            &[0],
+            &[RED, BLUE, RED].concat(),
+        ]
+        .concat();
+        let mut expected = vec![0x78, 0x01, 1, 20, 0, !20, 0xff];
+        expected.extend(&raw);
+        expected.extend(adler32(&raw).to_be_bytes());
+        assert_eq!(chunks[1].1, expected);
+        // The known CRC of an empty IEND chunk
+        assert_eq!(png[png.len() - 4..], [0xae, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn checksums() {
+        assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+        let crc = !b"123456789".iter().fold(!0, |crc, b| update_crc(crc, *b));
+        assert_eq!(crc, 0xcbf4_3926);
+
+        let data = vec![7; MAX_STORED_BLOCK + 10];
+        let stream = zlib_stored(&data);
+        assert_eq!(stream.len(), 2 + 5 + MAX_STORED_BLOCK + 5 + 10 + 4);
+        assert_eq!(stream[2..7], [0, 0xff, 0xff, 0, 0]);
+        assert_eq!(
+            zlib_stored(&[]),
+            [0x78, 0x01, 1, 0, 0, 0xff, 0xff, 0, 0, 0, 1]
+        );
+    }
+}
@@ -8,6 +8,9 @@ use std::ops::{Index, IndexMut};
 pub use point::Direction;
 pub use point::Point;
 
+pub mod diff;
+pub mod gif;
+pub mod image;
 pub mod iter;
 pub mod point;
 
@@ -8,7 +8,13 @@ pub const CLEAR_COMMAND: &str = concat!("\u{001b}[2J", "\u{001b}[H");
 #[macro_use]
 pub mod error;
 pub mod arguments;
+pub mod compression;
+pub mod dot;
 pub mod grid;
+pub mod machine;
 pub mod math;
+pub mod ocr;
 pub mod parse;
 pub mod problem;
+pub mod svg;
+pub mod terminal;
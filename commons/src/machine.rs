@@ -0,0 +1,335 @@
+//! A common interface for the virtual machines of the puzzles, with the tooling built upon it:
+//! - [Machine](Machine): Step, reset and inspect the state of a machine
+//! - [Runner](Runner): Run a machine with a step limit, loop detection and tracing
+//! ```
+//! use std::convert::Infallible;
+//! use commons::machine::{Detection, Machine, Runner, Step, Stop};
+//!
+//! /// Count up to 10 and jump back to 5
+//! struct Counter(usize);
+//!
+//! impl Machine for Counter {
+//!     type State = usize;
+//!     type Output = ();
+//!     type Error = Infallible;
+//!
+//!     fn step(&mut self) -> Result<Step<()>, Infallible> {
+//!         self.0 = if self.0 == 10 { 5 } else { self.0 + 1 };
+//!         Ok(Step::Running)
+//!     }
+//!
+//!     fn reset(&mut self) {
+//!         self.0 = 0;
+//!     }
+//!
+//!     fn position(&self) -> usize {
+//!         self.0
+//!     }
+//!
+//!     fn state(&self) -> usize {
+//!         self.0
+//!     }
+//! }
+//!
+//! let mut counter = Counter(0);
+//! let outcome = Runner::new().with_limit(100).run(&mut counter).unwrap();
+//! assert_eq!((outcome.stop, outcome.steps), (Stop::Limit, 100));
+//!
+//! counter.reset();
+//! let outcome = Runner::new().with_detection(Detection::Position).run(&mut counter).unwrap();
+//! assert_eq!(outcome.stop, Stop::Looped(5));
+//! assert_eq!((outcome.steps, outcome.last), (11, Some(10)));
+//! ```
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::io::Write;
+
+/// A machine executing a program one step at a time
+pub trait Machine {
+    /// A snapshot of the state of the machine, two machines with the same state behave the same
+    type State: Clone + Eq + Hash + Debug;
+    /// The values produced by the machine while it runs
+    type Output;
+    /// The error when a step of the machine failed
+    type Error;
+
+    /// Execute the next step of the machine
+    fn step(&mut self) -> Result<Step<Self::Output>, Self::Error>;
+
+    /// Reset the machine to its starting state
+    fn reset(&mut self);
+
+    /// The position of the next step to execute (the instruction pointer)
+    fn position(&self) -> usize;
+
+    /// A snapshot of the current state of the machine
+    fn state(&self) -> Self::State;
+
+    /// Run the machine until it stops or executes the same position twice
+    fn run_until_loop(&mut self) -> Result<Outcome, Self::Error>
+    where
+        Self: Sized,
+    {
+        Runner::new().with_detection(Detection::Position).run(self)
+    }
+}
+
+/// The result of a single step of a machine
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Step<O> {
+    /// The step was executed, the machine can continue
+    Running,
+    /// The step was executed and produced a value, the machine can continue
+    Output(O),
+    /// The machine cannot execute the step without an action from outside (an input)
+    Blocked,
+    /// The machine reached the end of its program
+    Halted,
+}
+
+/// The reason a runner stopped a machine
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Stop {
+    /// The machine reached the end of its program
+    Halted,
+    /// The machine needs an action from outside to continue
+    Blocked,
+    /// The machine was about to repeat itself from this position
+    Looped(usize),
+    /// The maximum number of steps was executed
+    Limit,
+}
+
+/// What is considered as an infinite loop
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Detection {
+    /// The machine is about to execute a position it already executed
+    Position,
+    /// The machine is back in a state it already was in (it will then repeat forever)
+    State,
+}
+
+/// How a runner ended
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Outcome {
+    /// The reason the machine was stopped
+    pub stop: Stop,
+    /// The number of steps that were executed
+    pub steps: usize,
+    /// The position of the last step that was executed
+    pub last: Option<usize>,
+}
+
+/// Inspect a machine before each step, with the number of steps executed so far
+type Trace<'a, M> = Box<dyn FnMut(usize, &M) + 'a>;
+
+/// Runs a machine until it stops, with some optional checks and tracing
+pub struct Runner<'a, M: Machine> {
+    /// The maximum number of steps to execute
+    limit: Option<usize>,
+    /// The detection of infinite loops
+    detection: Option<Detection>,
+    /// Called before each step with the number of steps executed so far
+    trace: Option<Trace<'a, M>>,
+    /// Called with each value produced by the machine
+    output: Option<Box<dyn FnMut(M::Output) + 'a>>,
+}
+
+impl<'a, M: Machine> Default for Runner<'a, M> {
+    fn default() -> Self {
+        Self {
+            limit: None,
+            detection: None,
+            trace: None,
+            output: None,
+        }
+    }
+}
+
+impl<'a, M: Machine> Runner<'a, M> {
+    /// A runner without limit, loop detection or tracing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the machine after this number of steps
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Stop the machine when it is about to loop forever
+    pub fn with_detection(mut self, detection: Detection) -> Self {
+        self.detection = Some(detection);
+        self
+    }
+
+    /// Inspect the machine before each step
+    /// # Arguments
+    /// * `trace` - Called with the number of steps executed so far and the machine
+    pub fn with_trace(mut self, trace: impl FnMut(usize, &M) + 'a) -> Self {
+        self.trace = Some(Box::new(trace));
+        self
+    }
+
+    /// Write the position and state of the machine before each step, one line per step
+    pub fn with_log<W: Write + 'a>(self, mut writer: W) -> Self {
+        self.with_trace(move |steps, machine| {
+            // The log is a debugging help, failing to write it should not stop the machine
+            let _ = writeln!(
+                writer,
+                "{steps:>8} @{:<4} {:?}",
+                machine.position(),
+                machine.state()
+            );
+        })
+    }
+
+    /// Receive the values produced by the machine (they are discarded otherwise)
+    pub fn with_output(mut self, output: impl FnMut(M::Output) + 'a) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+    /// Run the machine until it halts, blocks, loops or reaches the step limit
+    /// # Returns
+    /// How the machine stopped, or the error of the step that failed
+    pub fn run(&mut self, machine: &mut M) -> Result<Outcome, M::Error> {
+        let mut positions: HashSet<usize> = HashSet::new();
+        let mut states: HashSet<M::State> = HashSet::new();
+        let mut outcome = Outcome {
+            stop: Stop::Halted,
+            steps: 0,
+            last: None,
+        };
+        loop {
+            if self.limit.map_or(false, |limit| outcome.steps >= limit) {
+                outcome.stop = Stop::Limit;
+                return Ok(outcome);
+            }
+            let position = machine.position();
+            let repeated = match self.detection {
+                Some(Detection::Position) => !positions.insert(position),
+                Some(Detection::State) => !states.insert(machine.state()),
+                None => false,
+            };
+            if repeated {
+                outcome.stop = Stop::Looped(position);
+                return Ok(outcome);
+            }
+            if let Some(trace) = self.trace.as_mut() {
+                trace(outcome.steps, machine);
+            }
+
+            match machine.step()? {
+                Step::Running => {}
+                Step::Output(value) => {
+                    if let Some(output) = self.output.as_mut() {
+                        output(value);
+                    }
+                }
+                Step::Blocked => {
+                    outcome.stop = Stop::Blocked;
+                    return Ok(outcome);
+                }
+                Step::Halted => {
+                    outcome.stop = Stop::Halted;
+                    return Ok(outcome);
+                }
+            }
+            outcome.steps += 1;
+            outcome.last = Some(position);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Outputs the values of a register decreasing by 2, until it reaches 0, then asks for an input
+    struct Countdown {
+        value: i32,
+        cycles: usize,
+    }
+
+    impl Machine for Countdown {
+        type State = i32;
+        type Output = i32;
+        type Error = String;
+
+        fn step(&mut self) -> Result<Step<i32>, String> {
+            self.cycles += 1;
+            match self.value {
+                0 => Ok(Step::Blocked),
+                value if value < 0 => Err(format!("negative value {value}")),
+                value => {
+                    self.value -= 2;
+                    Ok(Step::Output(value))
+                }
+            }
+        }
+
+        fn reset(&mut self) {
+            self.value = 6;
+            self.cycles = 0;
+        }
+
+        fn position(&self) -> usize {
+            0
+        }
+
+        fn state(&self) -> i32 {
+            self.value
+        }
+    }
+
+    #[test]
+    fn runner_outputs_and_trace() {
+        let mut machine = Countdown {
+            value: 6,
+            cycles: 0,
+        };
+        let mut outputs = Vec::new();
+        let mut traced = Vec::new();
+        let outcome = Runner::new()
+            .with_trace(|steps, machine: &Countdown| traced.push((steps, machine.value)))
+            .with_output(|value| outputs.push(value))
+            .run(&mut machine);
+        assert_eq!(
+            outcome,
+            Ok(Outcome {
+                stop: Stop::Blocked,
+                steps: 3,
+                last: Some(0)
+            })
+        );
+        assert_eq!(outputs, [6, 4, 2]);
+        assert_eq!(traced, [(0, 6), (1, 4), (2, 2), (3, 0)]);
+
+        machine.reset();
+        let outcome = machine.run_until_loop().unwrap();
+        assert_eq!((outcome.stop, outcome.steps), (Stop::Looped(0), 1));
+
+        machine.value = 5;
+        let mut log = Vec::new();
+        let failed = Runner::new().with_log(&mut log).run(&mut machine);
+        assert_eq!(failed, Err("negative value -1".to_owned()));
+        let log = String::from_utf8(log).unwrap();
+        assert_eq!(log.lines().last(), Some("       3 @0    -1"));
+    }
+
+    #[test]
+    fn runner_state_detection() {
+        let mut machine = Countdown {
+            value: 0,
+            cycles: 0,
+        };
+        let mut runner = Runner::new().with_detection(Detection::State).with_limit(2);
+        let outcome = runner.run(&mut machine).unwrap();
+        assert_eq!((outcome.stop, outcome.steps), (Stop::Blocked, 0));
+        assert_eq!(machine.cycles, 1);
+    }
+}
@@ -0,0 +1,383 @@
+//! Read the block letters drawn by some puzzles, in one of the two fonts of the advent of code:
+//! - The small font, 6 pixels high and usually 4 pixels wide
+//! - The large font, 10 pixels high and usually 6 pixels wide
+//!
+//! The letters are found from left to right, the empty columns between them are skipped.
+//! ```
+//! use commons::grid::Grid;
+//! use commons::ocr;
+//!
+//! let screen = [
+//!     "#..#.###.",
+//!     "#..#..#..",
+//!     "####..#..",
+//!     "#..#..#..",
+//!     "#..#..#..",
+//!     "#..#.###.",
+//! ];
+//! let pixels = screen.iter().flat_map(|line| line.chars().map(|c| c == '#')).collect();
+//! assert_eq!(ocr::read(&Grid::from_vec(9, pixels)).unwrap(), "HI");
+//! ```
+
+use std::collections::HashSet;
+
+use crate::error::{Result, WrapErr};
+use crate::grid::{Grid, Point};
+
+/// A letter of a font, without the empty columns around it
+struct Glyph {
+    letter: char,
+    rows: &'static [&'static str],
+}
+
+/// The letters of the small font, 6 pixels high
+const SMALL: [Glyph; 18] = [
+    Glyph {
+        letter: 'A',
+        rows: &[".##.", "#..#", "#..#", "####", "#..#", "#..#"],
+    },
+    Glyph {
+        letter: 'B',
+        rows: &["###.", "#..#", "###.", "#..#", "#..#", "###."],
+    },
+    Glyph {
+        letter: 'C',
+        rows: &[".##.", "#..#", "#...", "#...", "#..#", ".##."],
+    },
+    Glyph {
+        letter: 'E',
+        rows: &["####", "#...", "###.", "#...", "#...", "####"],
+    },
+    Glyph {
+        letter: 'F',
+        rows: &["####", "#...", "###.", "#...", "#...", "#..."],
+    },
+    Glyph {
+        letter: 'G',
+        rows: &[".##.", "#..#", "#...", "#.##", "#..#", ".###"],
+    },
+    Glyph {
+        letter: 'H',
+        rows: &["#..#", "#..#", "####", "#..#", "#..#", "#..#"],
+    },
+    Glyph {
+        letter: 'I',
+        rows: &["###", ".#.", ".#.", ".#.", ".#.", "###"],
+    },
+    Glyph {
+        letter: 'J',
+        rows: &["..##", "...#", "...#", "...#", "#..#", ".##."],
+    },
+    Glyph {
+        letter: 'K',
+        rows: &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"],
+    },
+    Glyph {
+        letter: 'L',
+        rows: &["#...", "#...", "#...", "#...", "#...", "####"],
+    },
+    Glyph {
+        letter: 'O',
+        rows: &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."],
+    },
+    Glyph {
+        letter: 'P',
+        rows: &["###.", "#..#", "#..#", "###.", "#...", "#..."],
+    },
+    Glyph {
+        letter: 'R',
+        rows: &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"],
+    },
+    Glyph {
+        letter: 'S',
+        rows: &[".###", "#...", "#...", ".##.", "...#", "###."],
+    },
+    Glyph {
+        letter: 'U',
+        rows: &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."],
+    },
+    Glyph {
+        letter: 'Y',
+        rows: &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."],
+    },
+    Glyph {
+        letter: 'Z',
+        rows: &["####", "...#", "..#.", ".#..", "#...", "####"],
+    },
+];
+
+/// The letters of the large font, 10 pixels high
+const LARGE: [Glyph; 15] = [
+    Glyph {
+        letter: 'A',
+        rows: &[
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'B',
+        rows: &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+    },
+    Glyph {
+        letter: 'C',
+        rows: &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+    },
+    Glyph {
+        letter: 'E',
+        rows: &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    },
+    Glyph {
+        letter: 'F',
+        rows: &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    },
+    Glyph {
+        letter: 'G',
+        rows: &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+    },
+    Glyph {
+        letter: 'H',
+        rows: &[
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'J',
+        rows: &[
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+    },
+    Glyph {
+        letter: 'K',
+        rows: &[
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'L',
+        rows: &[
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+    },
+    Glyph {
+        letter: 'N',
+        rows: &[
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'P',
+        rows: &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+    },
+    Glyph {
+        letter: 'R',
+        rows: &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'X',
+        rows: &[
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+    },
+    Glyph {
+        letter: 'Z',
+        rows: &[
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+    },
+];
+
+impl Glyph {
+    /// The number of columns of this letter
+    fn width(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    /// Check if this letter is drawn with its top left corner at a position
+    fn matches(&self, lit: impl Fn(usize, usize) -> bool, (x, y): (usize, usize)) -> bool {
+        self.rows.iter().enumerate().all(|(dy, row)| {
+            row.bytes()
+                .enumerate()
+                .all(|(dx, pixel)| (pixel == b'#') == lit(x + dx, y + dy))
+        })
+    }
+}
+
+/// Read the letters drawn on a grid, the lit pixels being true
+/// # Errors
+/// If nothing is drawn, if the letters do not have the height of a known font or if a letter is
+/// unknown
+pub fn read(grid: &Grid<bool>) -> Result<String> {
+    let (width, height) = grid.size();
+    let lit = |x: usize, y: usize| x < width && y < height && grid[(x as isize, y as isize)];
+    let rows: Vec<usize> = (0..height)
+        .filter(|y| (0..width).any(|x| lit(x, *y)))
+        .collect();
+    let (top, bottom) = match (rows.first(), rows.last()) {
+        (Some(top), Some(bottom)) => (*top, *bottom),
+        _ => bail!("There are no letters to read, no pixel is lit"),
+    };
+    let font: &[Glyph] = match bottom - top + 1 {
+        6 => &SMALL,
+        10 => &LARGE,
+        other => bail!("No font has letters of {other} pixels high"),
+    };
+
+    let mut text = String::new();
+    let mut x = 0;
+    while x < width {
+        if !(top..=bottom).any(|y| lit(x, y)) {
+            x += 1;
+            continue;
+        }
+        // The longest letter first, in case it starts like another one
+        let glyph = font
+            .iter()
+            .filter(|glyph| glyph.matches(lit, (x, top)))
+            .max_by_key(|glyph| glyph.width())
+            .wrap_err_with(|| {
+                let columns = x..(x + font[0].width()).min(width);
+                let drawing: Vec<String> = (top..=bottom)
+                    .map(|y| {
+                        let pixels = columns.clone().map(|x| if lit(x, y) { '#' } else { '.' });
+                        pixels.collect()
+                    })
+                    .collect();
+                format!("Unknown letter at column {x}:\n{}", drawing.join("\n"))
+            })?;
+        text.push(glyph.letter);
+        x += glyph.width();
+    }
+    Ok(text)
+}
+
+/// Read the letters drawn by some points, the other points being dark
+/// # Errors
+/// The same as [read](read)
+pub fn read_points<'a>(points: impl IntoIterator<Item = &'a Point>) -> Result<String> {
+    let points: HashSet<Point> = points.into_iter().copied().collect();
+    if points.is_empty() {
+        bail!("There are no letters to read, no pixel is lit");
+    }
+    let min_x = points.iter().map(|point| point.x).min().unwrap_or_default();
+    let max_x = points.iter().map(|point| point.x).max().unwrap_or_default();
+    let min_y = points.iter().map(|point| point.y).min().unwrap_or_default();
+    let max_y = points.iter().map(|point| point.y).max().unwrap_or_default();
+    let grid = Grid::tabulate(
+        (max_x - min_x + 1) as usize,
+        (max_y - min_y + 1) as usize,
+        |(x, y)| points.contains(&Point::new(min_x + x as i64, min_y + y as i64)),
+    );
+    read(&grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Draw some text with a font, with some empty columns between the letters
+    fn draw(font: &[Glyph], text: &str, spacing: usize) -> Grid<bool> {
+        let glyphs: Vec<&Glyph> = text
+            .chars()
+            .map(|letter| font.iter().find(|glyph| glyph.letter == letter).unwrap())
+            .collect();
+        let height = font[0].rows.len();
+        let mut pixels: Vec<Vec<bool>> = vec![Vec::new(); height];
+        for glyph in glyphs {
+            for (row, pixels) in glyph.rows.iter().zip(pixels.iter_mut()) {
+                pixels.extend(row.chars().map(|pixel| pixel == '#'));
+                pixels.extend((0..spacing).map(|_| false));
+            }
+        }
+        let width = pixels[0].len();
+        Grid::from_vec(width, pixels.concat())
+    }
+
+    /// Parse a screen drawn with `#` and `.`
+    fn parse(screen: &str) -> Grid<bool> {
+        let width = screen.lines().next().unwrap_or_default().len();
+        let pixels = screen
+            .lines()
+            .flat_map(|line| line.chars().map(|c| c == '#'));
+        Grid::from_vec(width, pixels.collect())
+    }
+
+    #[test]
+    fn read_fonts() {
+        let small: String = SMALL.iter().map(|glyph| glyph.letter).collect();
+        assert_eq!(read(&draw(&SMALL, &small, 1)).unwrap(), small);
+        let large: String = LARGE.iter().map(|glyph| glyph.letter).collect();
+        assert_eq!(read(&draw(&LARGE, &large, 2)).unwrap(), large);
+        assert_eq!(read(&draw(&SMALL, "YAY", 0)).unwrap(), "YAY");
+
+        let screen = "\
+            ......................................\n\
+            ..###..####.###...##..####.####...##..\n\
+            ..#..#....#.#..#.#..#....#.#.......#..\n\
+            ..#..#...#..###..#......#..###.....#..\n\
+            ..###...#...#..#.#.##..#...#.......#..\n\
+            ..#....#....#..#.#..#.#....#....#..#..\n\
+            ..#....####.###...###.####.####..##...";
+        assert_eq!(read(&parse(screen)).unwrap(), "PZBGZEJ");
+    }
+
+    #[test]
+    fn read_points_set() {
+        let grid = draw(&LARGE, "HELL", 2);
+        let points: Vec<Point> = grid
+            .indexed_values()
+            .filter(|(_, lit)| **lit)
+            .map(|((x, y), _)| Point::new(x as i64 - 50, y as i64 + 7))
+            .collect();
+        assert_eq!(read_points(&points).unwrap(), "HELL");
+    }
+
+    #[test]
+    fn read_failures() {
+        assert_eq!(
+            read(&Grid::fill(5, 6, false)).unwrap_err().to_string(),
+            "There are no letters to read, no pixel is lit"
+        );
+        assert_eq!(
+            read_points(&[]).unwrap_err().to_string(),
+            "There are no letters to read, no pixel is lit"
+        );
+        assert_eq!(
+            read(&parse("##\n##\n##")).unwrap_err().to_string(),
+            "No font has letters of 3 pixels high"
+        );
+        let unknown = "#..#.#..#\n#..#.#..#\n####.#..#\n#..#.#.##\n#..#.##.#\n#..#.#..#";
+        assert_eq!(
+            read(&parse(unknown)).unwrap_err().to_string(),
+            "Unknown letter at column 5:\n#..#\n#..#\n#..#\n#.##\n##.#\n#..#"
+        );
+    }
+}
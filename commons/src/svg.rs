@@ -0,0 +1,395 @@
+//! Draw the geometry of a puzzle as an SVG image: lines, polygons, circles, diamonds and labels.
+//!
+//! The shapes are given in the coordinates of the puzzle, which can be very large: the viewport
+//! is computed from all the shapes and the coordinates are scaled to fit the size of the image.
+//! The widths of the lines, the dots and the labels are in pixels of the image.
+//! ```
+//! use commons::svg::{Style, Svg};
+//!
+//! let mut svg = Svg::new().with_size(100.0).with_margin(0.0);
+//! svg.line((0.0, 0.0), (1e9, 5e8), &Style::stroke([255, 0, 0]));
+//! svg.dot((1e9, 0.0), &Style::fill([0, 0, 255]));
+//!
+//! let mut output = Vec::new();
+//! svg.write(&mut output).unwrap();
+//! let output = String::from_utf8(output).unwrap();
+//! assert!(output.contains(r#"<line x1="0.00" y1="0.00" x2="100.00" y2="50.00""#));
+//! ```
+
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Result as IoResult, Write};
+use std::path::Path;
+
+use crate::error::{Result, WrapErr};
+use crate::grid::image::Rgb;
+
+/// A point in the coordinates of the puzzle
+pub type Position = (f64, f64);
+
+/// How a shape is drawn
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Style {
+    stroke: Option<Rgb>,
+    fill: Option<Rgb>,
+    /// The width of the lines in pixels, also the radius of the dots
+    width: f64,
+    opacity: f64,
+}
+
+/// A shape, in the coordinates of the puzzle
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Line(Position, Position),
+    Polyline(Vec<Position>),
+    Polygon(Vec<Position>),
+    Circle(Position, f64),
+    Dot(Position),
+    Label(Position, String),
+}
+
+/// An SVG image being drawn
+#[derive(Debug, Clone)]
+pub struct Svg {
+    /// The size of the longest side of the drawing, in pixels
+    size: f64,
+    /// The space around the drawing, in pixels
+    margin: f64,
+    background: Option<Rgb>,
+    /// The size of the labels, in pixels
+    font_size: f64,
+    shapes: Vec<(Shape, Style)>,
+}
+
+impl Style {
+    /// Draw the lines of the shapes in a color
+    pub fn stroke(color: Rgb) -> Self {
+        Self {
+            stroke: Some(color),
+            fill: None,
+            width: 1.0,
+            opacity: 1.0,
+        }
+    }
+
+    /// Fill the shapes with a color, without drawing their lines
+    pub fn fill(color: Rgb) -> Self {
+        Self {
+            stroke: None,
+            fill: Some(color),
+            width: 1.0,
+            opacity: 1.0,
+        }
+    }
+
+    /// Also fill the shapes with a color
+    pub fn with_fill(mut self, color: Rgb) -> Self {
+        self.fill = Some(color);
+        self
+    }
+
+    /// Change the width of the lines (in pixels), also the radius of the dots
+    pub fn with_width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Make the shapes transparent, from 0 (invisible) to 1 (opaque)
+    pub fn with_opacity(mut self, opacity: f64) -> Self {
+        self.opacity = opacity.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The attributes of this style, with a leading space
+    fn attributes(&self) -> String {
+        let color = |color: Option<Rgb>| {
+            color.map_or("none".to_owned(), |[r, g, b]| {
+                format!("#{r:02x}{g:02x}{b:02x}")
+            })
+        };
+        let mut attributes = format!(
+            r#" stroke="{}" fill="{}" stroke-width="{}""#,
+            color(self.stroke),
+            color(self.fill),
+            self.width
+        );
+        if self.opacity < 1.0 {
+            let _ = write!(attributes, r#" opacity="{}""#, self.opacity);
+        }
+        attributes
+    }
+}
+
+impl Default for Svg {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svg {
+    /// Create an empty drawing of 800 pixels with a margin of 10 pixels
+    pub fn new() -> Self {
+        Self {
+            size: 800.0,
+            margin: 10.0,
+            background: None,
+            font_size: 12.0,
+            shapes: Vec::new(),
+        }
+    }
+
+    /// Change the size of the longest side of the drawing, in pixels
+    pub fn with_size(mut self, size: f64) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// Change the space around the drawing, in pixels
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Fill the image with a color behind the shapes
+    pub fn with_background(mut self, color: Rgb) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Change the size of the labels, in pixels
+    pub fn with_font_size(mut self, font_size: f64) -> Self {
+        self.font_size = font_size;
+        self
+    }
+
+    /// Draw a line between two points
+    pub fn line(&mut self, from: Position, to: Position, style: &Style) {
+        self.shapes.push((Shape::Line(from, to), *style));
+    }
+
+    /// Draw lines through some points
+    pub fn polyline(&mut self, points: impl IntoIterator<Item = Position>, style: &Style) {
+        let points = points.into_iter().collect();
+        self.shapes.push((Shape::Polyline(points), *style));
+    }
+
+    /// Draw a closed shape through some points
+    pub fn polygon(&mut self, points: impl IntoIterator<Item = Position>, style: &Style) {
+        let points = points.into_iter().collect();
+        self.shapes.push((Shape::Polygon(points), *style));
+    }
+
+    /// Draw a circle, its radius is in the coordinates of the puzzle
+    pub fn circle(&mut self, center: Position, radius: f64, style: &Style) {
+        self.shapes.push((Shape::Circle(center, radius), *style));
+    }
+
+    /// Draw the points at a manhattan distance of a center
+    pub fn diamond(&mut self, (x, y): Position, radius: f64, style: &Style) {
+        let corners = [
+            (x, y - radius),
+            (x + radius, y),
+            (x, y + radius),
+            (x - radius, y),
+        ];
+        self.polygon(corners, style);
+    }
+
+    /// Mark a point with a dot of the fill color of the style (or of its line color), its radius
+    /// is the width of the style in pixels
+    pub fn dot(&mut self, at: Position, style: &Style) {
+        self.shapes.push((Shape::Dot(at), *style));
+    }
+
+    /// Write a text starting at a point, in the fill color of the style (or in its line color,
+    /// black if it has none)
+    pub fn label(&mut self, at: Position, text: &str, style: &Style) {
+        self.shapes
+            .push((Shape::Label(at, text.to_owned()), *style));
+    }
+
+    /// The smallest and the largest coordinates of all the shapes
+    fn bounds(&self) -> Option<(Position, Position)> {
+        let points = self.shapes.iter().flat_map(|(shape, _)| match shape {
+            Shape::Line(from, to) => vec![*from, *to],
+            Shape::Polyline(points) | Shape::Polygon(points) => points.clone(),
+            Shape::Circle((x, y), r) => vec![(x - r, y - r), (x + r, y + r)],
+            Shape::Dot(at) | Shape::Label(at, _) => vec![*at],
+        });
+        points.fold(None, |bounds, (x, y)| match bounds {
+            None => Some(((x, y), (x, y))),
+            Some(((min_x, min_y), (max_x, max_y))) => {
+                Some(((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y))))
+            }
+        })
+    }
+
+    /// Write the drawing in the SVG format
+    pub fn write(&self, writer: &mut impl Write) -> IoResult<()> {
+        let ((min_x, min_y), (max_x, max_y)) = self.bounds().unwrap_or(((0.0, 0.0), (0.0, 0.0)));
+        let longest = (max_x - min_x).max(max_y - min_y);
+        let scale = if longest > 0.0 {
+            self.size / longest
+        } else {
+            1.0
+        };
+        let width = (max_x - min_x) * scale + 2.0 * self.margin;
+        let height = (max_y - min_y) * scale + 2.0 * self.margin;
+        let x = |x: f64| (x - min_x) * scale + self.margin;
+        let y = |y: f64| (y - min_y) * scale + self.margin;
+        let points = |points: &[Position]| {
+            points
+                .iter()
+                .map(|(px, py)| format!("{:.2},{:.2}", x(*px), y(*py)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        writeln!(
+            writer,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.2} {height:.2}">"#
+        )?;
+        if let Some([r, g, b]) = self.background {
+            writeln!(
+                writer,
+                r##"<rect width="100%" height="100%" fill="#{r:02x}{g:02x}{b:02x}"/>"##
+            )?;
+        }
+        for (shape, style) in &self.shapes {
+            let attributes = style.attributes();
+            match shape {
+                Shape::Line(from, to) => writeln!(
+                    writer,
+                    r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}"{attributes}/>"#,
+                    x(from.0),
+                    y(from.1),
+                    x(to.0),
+                    y(to.1)
+                )?,
+                Shape::Polyline(line) => writeln!(
+                    writer,
+                    r#"<polyline points="{}"{attributes}/>"#,
+                    points(line)
+                )?,
+                Shape::Polygon(polygon) => writeln!(
+                    writer,
+                    r#"<polygon points="{}"{attributes}/>"#,
+                    points(polygon)
+                )?,
+                Shape::Circle(center, radius) => writeln!(
+                    writer,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}"{attributes}/>"#,
+                    x(center.0),
+                    y(center.1),
+                    radius * scale
+                )?,
+                Shape::Dot(at) => writeln!(
+                    writer,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{}"{}/>"#,
+                    x(at.0),
+                    y(at.1),
+                    style.width,
+                    Style {
+                        stroke: None,
+                        fill: style.fill.or(style.stroke),
+                        width: 0.0,
+                        ..*style
+                    }
+                    .attributes()
+                )?,
+                Shape::Label(at, text) => writeln!(
+                    writer,
+                    r#"<text x="{:.2}" y="{:.2}" font-size="{}" font-family="monospace"{}>{}</text>"#,
+                    x(at.0),
+                    y(at.1),
+                    self.font_size,
+                    Style {
+                        stroke: None,
+                        fill: style.fill.or(style.stroke).or(Some([0; 3])),
+                        ..*style
+                    }
+                    .attributes(),
+                    escape(text)
+                )?,
+            }
+        }
+        writeln!(writer, "</svg>")
+    }
+
+    /// Save the drawing in a SVG file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let file = File::create(path).wrap_err_with(|| format!("Could not create {path:?}"))?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer)
+            .and_then(|_| writer.flush())
+            .wrap_err_with(|| format!("Could not write the drawing in {path:?}"))
+    }
+}
+
+/// Escape the characters of a text that have a meaning in XML
+fn escape(text: &str) -> String {
+    text.chars()
+        .fold(String::with_capacity(text.len()), |mut escaped, c| {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                c => escaped.push(c),
+            }
+            escaped
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(svg: &Svg) -> Vec<String> {
+        let mut output = Vec::new();
+        svg.write(&mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn auto_viewport() {
+        let mut svg = Svg::new().with_size(200.0).with_margin(5.0);
+        let style = Style::stroke([0, 128, 255]).with_width(2.0);
+        svg.polyline([(-10.0, 0.0), (10.0, 0.0), (10.0, 5.0)], &style);
+        svg.circle((0.0, 0.0), 5.0, &style.with_fill([255, 0, 0]));
+        assert_eq!(
+            render(&svg),
+            [
+                r#"<svg xmlns="http://www.w3.org/2000/svg" width="210" height="110" viewBox="0 0 210.00 110.00">"#,
+                r##"<polyline points="5.00,55.00 205.00,55.00 205.00,105.00" stroke="#0080ff" fill="none" stroke-width="2"/>"##,
+                r##"<circle cx="105.00" cy="55.00" r="50.00" stroke="#0080ff" fill="#ff0000" stroke-width="2"/>"##,
+                "</svg>",
+            ]
+        );
+    }
+
+    #[test]
+    fn diamonds_and_labels() {
+        let mut svg = Svg::new()
+            .with_size(4.0)
+            .with_margin(0.0)
+            .with_background([255; 3]);
+        svg.diamond((2.0, 2.0), 2.0, &Style::fill([0, 255, 0]).with_opacity(0.5));
+        svg.dot((2.0, 2.0), &Style::fill([1, 2, 3]).with_width(3.0));
+        svg.label((0.0, 4.0), "a < b & c", &Style::stroke([9, 9, 9]));
+        assert_eq!(
+            render(&svg)[1..5],
+            [
+                r##"<rect width="100%" height="100%" fill="#ffffff"/>"##,
+                r##"<polygon points="2.00,0.00 4.00,2.00 2.00,4.00 0.00,2.00" stroke="none" fill="#00ff00" stroke-width="1" opacity="0.5"/>"##,
+                r##"<circle cx="2.00" cy="2.00" r="3" stroke="none" fill="#010203" stroke-width="0"/>"##,
+                r##"<text x="0.00" y="4.00" font-size="12" font-family="monospace" stroke="none" fill="#090909" stroke-width="1">a &lt; b &amp; c</text>"##,
+            ]
+        );
+    }
+}
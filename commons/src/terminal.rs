@@ -0,0 +1,424 @@
+//! Animate a simulation in the terminal, with the frames of the `--visualize` option.
+//!
+//! The renderer keeps what is currently displayed, so that each frame only redraws the characters
+//! that changed. The frames are grids placed at some position: the viewport shows them from their
+//! top left corner, or follows a point when asked to (without going out of the frame).
+//! ```
+//! use commons::grid::Grid;
+//! use commons::terminal::Renderer;
+//!
+//! let mut renderer = Renderer::new(Vec::new(), 3, 1).with_palette(&[('@', [255, 0, 0])]);
+//! let mut grid = Grid::fill(3, 1, '.');
+//! renderer.draw(&grid, (0, 0), |c| *c).unwrap();
+//! grid[(1, 0)] = '@';
+//! renderer.draw(&grid, (0, 0), |c| *c).unwrap();
+//!
+//! let output = String::from_utf8(renderer.finish().unwrap()).unwrap();
+//! assert!(output.ends_with("\u{1b}[1;2H\u{1b}[38;2;255;0;0m@\u{1b}[39m\u{1b}[2;1H\u{1b}[?25h"));
+//! ```
+//!
+//! The keys pressed during an animation are read with a `Keyboard`, which puts the terminal in raw
+//! mode (with `stty`) until it is dropped.
+
+use std::io::{stdin, stdout, Read, Result as IoResult, Stdout, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread::{sleep, spawn};
+use std::time::{Duration, Instant};
+
+use crate::arguments::Options;
+use crate::error::{Result, WrapErr};
+use crate::grid::image::Rgb;
+use crate::grid::Grid;
+
+/// The number of frames per second when none is given
+const DEFAULT_FPS: u32 = 30;
+
+/// A character on the screen, with its color (the default color of the terminal if none)
+type Cell = (char, Option<Rgb>);
+
+/// Draws the frames of an animation in a terminal
+pub struct Renderer<W: Write = Stdout> {
+    writer: W,
+    /// The size of the viewport on the frames, in characters
+    width: usize,
+    height: usize,
+    /// The time between two frames, none to draw them as fast as possible
+    delay: Option<Duration>,
+    /// The color of some characters
+    palette: Vec<(char, Rgb)>,
+    /// The lines displayed above the viewport
+    status: Vec<String>,
+    /// The point to keep at the center of the viewport, if any
+    focus: Option<(i64, i64)>,
+    /// The lines currently on the screen
+    front: Vec<Vec<Cell>>,
+    /// When the last frame was drawn
+    last: Option<Instant>,
+}
+
+impl Renderer {
+    /// The names of the options configuring a renderer
+    pub const OPTIONS: &'static [&'static str] = &["visualize", "fps"];
+
+    /// Create a renderer on the standard output if the `visualize` option is given, with the
+    /// frames per second of the `--fps=<FPS>` option (0 for as fast as possible)
+    pub fn from_options(options: &Options, width: usize, height: usize) -> Result<Option<Self>> {
+        if !options.has("visualize") {
+            return Ok(None);
+        }
+        let fps = match options.values("fps").first() {
+            Some(fps) => fps
+                .parse()
+                .wrap_err_with(|| format!("Invalid --fps '{fps}'"))?,
+            None => DEFAULT_FPS,
+        };
+        Ok(Some(Self::new(stdout(), width, height).with_fps(fps)))
+    }
+}
+
+impl<W: Write> Renderer<W> {
+    /// Create a renderer writing in some output, with a viewport of some size (in characters)
+    pub fn new(writer: W, width: usize, height: usize) -> Self {
+        Self {
+            writer,
+            width,
+            height,
+            delay: None,
+            palette: Vec::new(),
+            status: Vec::new(),
+            focus: None,
+            front: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// Draw at most this number of frames per second, 0 to draw them as fast as possible
+    pub fn with_fps(mut self, fps: u32) -> Self {
+        self.delay = (fps > 0).then(|| Duration::from_secs(1) / fps);
+        self
+    }
+
+    /// Draw some characters in a color (as a 24 bits color)
+    pub fn with_palette(mut self, palette: &[(char, Rgb)]) -> Self {
+        self.palette = palette.to_vec();
+        self
+    }
+
+    /// Change the size of the viewport from the next frame on
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+    }
+
+    /// Display some lines above the viewport from the next frame on
+    pub fn set_status(&mut self, status: &str) {
+        self.status = status.lines().map(str::to_owned).collect();
+    }
+
+    /// Keep a point at the center of the viewport from the next frame on, or stop following
+    pub fn follow(&mut self, focus: Option<(i64, i64)>) {
+        self.focus = focus;
+    }
+
+    /// Draw a frame, waiting for the delay since the previous one
+    /// # Arguments
+    /// * `grid` - The frame to draw
+    /// * `origin` - The position of the top left corner of the grid
+    /// * `char` - The character drawn for each element of the grid
+    pub fn draw<T>(
+        &mut self,
+        grid: &Grid<T>,
+        origin: (i64, i64),
+        char: impl Fn(&T) -> char,
+    ) -> IoResult<()> {
+        let (width, height) = if grid.width() == 0 {
+            (0, 0)
+        } else {
+            grid.size()
+        };
+        // The position of the viewport in the grid, around the focus but inside the grid
+        let corner = |focus: Option<i64>, origin: i64, size: usize, viewport: usize| {
+            let wanted = focus.map_or(0, |focus| focus - origin - viewport as i64 / 2);
+            wanted.min(size as i64 - viewport as i64).max(0)
+        };
+        let left = corner(self.focus.map(|f| f.0), origin.0, width, self.width);
+        let top = corner(self.focus.map(|f| f.1), origin.1, height, self.height);
+
+        let mut back: Vec<Vec<Cell>> = self
+            .status
+            .iter()
+            .map(|line| {
+                let mut line: Vec<Cell> = line.chars().map(|c| (c, None)).collect();
+                line.resize(self.width.max(line.len()), (' ', None));
+                line
+            })
+            .collect();
+        for y in top..top + self.height as i64 {
+            let line = (left..left + self.width as i64).map(|x| {
+                let c = grid.get((x as isize, y as isize)).map_or(' ', &char);
+                (c, self.color(c))
+            });
+            back.push(line.collect());
+        }
+
+        if let (Some(delay), Some(last)) = (self.delay, self.last) {
+            sleep(delay.saturating_sub(last.elapsed()));
+        }
+        self.redraw(back)?;
+        self.last = Some(Instant::now());
+        Ok(())
+    }
+
+    /// The color of a character, if it has one
+    fn color(&self, c: char) -> Option<Rgb> {
+        self.palette
+            .iter()
+            .find(|(colored, _)| *colored == c)
+            .map(|(_, color)| *color)
+    }
+
+    /// Update the screen with the new lines, only writing the characters that changed
+    fn redraw(&mut self, back: Vec<Vec<Cell>>) -> IoResult<()> {
+        let resized = back.len() != self.front.len()
+            || back
+                .iter()
+                .zip(&self.front)
+                .any(|(b, f)| b.len() != f.len());
+        if resized {
+            // Clear the screen and hide the cursor
+            self.writer.write_all(b"\x1b[2J\x1b[?25l")?;
+            self.front.clear();
+        }
+
+        let mut color = None;
+        for (y, line) in back.iter().enumerate() {
+            // The position of the cursor if it is on this line
+            let mut cursor = None;
+            for (x, cell) in line.iter().enumerate() {
+                let front = self.front.get(y).and_then(|line| line.get(x));
+                if front == Some(cell) {
+                    continue;
+                }
+                if cursor != Some(x) {
+                    write!(self.writer, "\x1b[{};{}H", y + 1, x + 1)?;
+                }
+                if cell.1 != color {
+                    match cell.1 {
+                        Some([r, g, b]) => write!(self.writer, "\x1b[38;2;{r};{g};{b}m")?,
+                        None => self.writer.write_all(b"\x1b[39m")?,
+                    }
+                    color = cell.1;
+                }
+                write!(self.writer, "{}", cell.0)?;
+                cursor = Some(x + 1);
+            }
+        }
+        if color.is_some() {
+            self.writer.write_all(b"\x1b[39m")?;
+        }
+        self.front = back;
+        self.writer.flush()
+    }
+
+    /// Move the cursor below the last frame and show it again
+    pub fn finish(mut self) -> IoResult<W> {
+        write!(self.writer, "\x1b[{};1H\x1b[?25h", self.front.len() + 1)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// A key pressed on the keyboard
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Escape,
+    /// Ctrl-C, which does not stop the program in raw mode
+    Interrupt,
+    Char(char),
+}
+
+/// Reads the keys pressed in the terminal without waiting for a new line nor echoing them.
+/// The terminal is restored as it was when this is dropped.
+pub struct Keyboard {
+    /// The settings of the terminal before switching to raw mode
+    settings: String,
+    /// The bytes read from the standard input
+    bytes: Receiver<Vec<u8>>,
+}
+
+impl Keyboard {
+    /// Put the terminal in raw mode and start reading the standard input in the background
+    pub fn open() -> Result<Self> {
+        let settings = stty(&["-g"]).wrap_err("The keyboard needs a terminal")?;
+        // Keep the processing of the outputs, to still go back to the start of the line
+        stty(&["raw", "-echo", "opost"])?;
+
+        let (sender, bytes) = channel();
+        spawn(move || {
+            let mut buffer = [0; 16];
+            while let Ok(read @ 1..) = stdin().read(&mut buffer) {
+                if sender.send(buffer[..read].to_vec()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            settings: settings.trim().to_owned(),
+            bytes,
+        })
+    }
+
+    /// The keys pressed since the last call, without waiting
+    pub fn keys(&self) -> Vec<Key> {
+        self.bytes.try_iter().flat_map(|b| parse_keys(&b)).collect()
+    }
+
+    /// Wait until some keys are pressed, empty only if the standard input was closed
+    pub fn wait(&self) -> Vec<Key> {
+        while let Ok(bytes) = self.bytes.recv() {
+            let mut keys = parse_keys(&bytes);
+            keys.extend(self.keys());
+            // The ignored sequences (like the function keys) are not keys to return
+            if !keys.is_empty() {
+                return keys;
+            }
+        }
+        Vec::new()
+    }
+}
+
+impl Drop for Keyboard {
+    fn drop(&mut self) {
+        // Nothing more can be done if the terminal cannot be restored
+        let _ = stty(&[&self.settings]);
+    }
+}
+
+/// Run `stty` on the terminal of the standard input, returning what it printed
+fn stty(arguments: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(arguments)
+        .stdin(Stdio::inherit())
+        .output()
+        .wrap_err("Could not run stty")?;
+    if !output.status.success() {
+        bail!(
+            "stty failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The keys in some bytes read from a terminal, with the arrows sent as escape sequences
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let mut keys = Vec::new();
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x03' => Key::Interrupt,
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                match chars.next() {
+                    Some('A') => Key::Up,
+                    Some('B') => Key::Down,
+                    Some('C') => Key::Right,
+                    Some('D') => Key::Left,
+                    // Ignore the other sequences (like the function keys)
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Escape,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draw(renderer: &mut Renderer<Vec<u8>>, grid: &Grid<char>, origin: (i64, i64)) -> String {
+        renderer.draw(grid, origin, |c| *c).unwrap();
+        String::from_utf8(std::mem::take(&mut renderer.writer)).unwrap()
+    }
+
+    #[test]
+    fn diffed_redraws() {
+        let mut renderer = Renderer::new(Vec::new(), 4, 2);
+        let mut grid = Grid::from_vec(4, "#..##..#".chars().collect());
+        assert_eq!(
+            draw(&mut renderer, &grid, (0, 0)),
+            "\x1b[2J\x1b[?25l\x1b[1;1H#..#\x1b[2;1H#..#"
+        );
+        assert_eq!(draw(&mut renderer, &grid, (0, 0)), "");
+
+        grid[(1, 0)] = 'a';
+        grid[(2, 0)] = 'b';
+        grid[(1, 1)] = 'c';
+        assert_eq!(draw(&mut renderer, &grid, (0, 0)), "\x1b[1;2Hab\x1b[2;2Hc");
+
+        // A status line redraws everything as the screen grows
+        renderer.set_status("Score: 1");
+        let redrawn = draw(&mut renderer, &grid, (0, 0));
+        assert!(redrawn.starts_with("\x1b[2J\x1b[?25l\x1b[1;1HScore: 1\x1b[2;1H#ab#"));
+        renderer.set_status("Score: 2");
+        assert_eq!(draw(&mut renderer, &grid, (0, 0)), "\x1b[1;8H2");
+    }
+
+    #[test]
+    fn palette_colors() {
+        let mut renderer =
+            Renderer::new(Vec::new(), 3, 1).with_palette(&[('#', [1, 2, 3]), ('o', [4, 5, 6])]);
+        let grid = Grid::from_vec(3, "#o.".chars().collect());
+        assert_eq!(
+            draw(&mut renderer, &grid, (0, 0)),
+            "\x1b[2J\x1b[?25l\x1b[1;1H\x1b[38;2;1;2;3m#\x1b[38;2;4;5;6mo\x1b[39m.",
+        );
+    }
+
+    #[test]
+    fn following_viewport() {
+        let mut renderer = Renderer::new(Vec::new(), 3, 1);
+        let grid = Grid::from_vec(10, "0123456789".chars().collect());
+        draw(&mut renderer, &grid, (-5, 0));
+        assert_eq!(renderer.front[0][0].0, '0');
+
+        renderer.follow(Some((0, 0)));
+        draw(&mut renderer, &grid, (-5, 0));
+        assert_eq!(renderer.front[0][0].0, '4');
+
+        // The viewport stays in the frame
+        renderer.follow(Some((4, 0)));
+        draw(&mut renderer, &grid, (-5, 0));
+        assert_eq!(renderer.front[0][0].0, '7');
+        renderer.follow(Some((-10, 0)));
+        draw(&mut renderer, &grid, (-5, 0));
+        assert_eq!(renderer.front[0][0].0, '0');
+    }
+
+    #[test]
+    fn parsed_keys() {
+        assert_eq!(
+            parse_keys(b"a\x1b[D\x1b[C\x1b\x03 \x1b[B\x1b[A"),
+            vec![
+                Key::Char('a'),
+                Key::Left,
+                Key::Right,
+                Key::Escape,
+                Key::Interrupt,
+                Key::Char(' '),
+                Key::Down,
+                Key::Up,
+            ]
+        );
+    }
+}
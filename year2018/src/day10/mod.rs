@@ -4,6 +4,7 @@ use std::fmt::{Display, Formatter, Result as FmtResult};
 use itertools::Itertools;
 
 use commons::grid::Point;
+use commons::ocr;
 use commons::{err, Report, Result, WrapErr};
 
 pub const TITLE: &str = "Day 10: The Stars Align";
@@ -12,6 +13,7 @@ pub fn run(raw: String) -> Result<()> {
     let message = parse(&raw)?;
     let (minimum, time) = message.into_minimum_size();
     println!("The message took {time}s to appear, it is:\n{minimum}");
+    println!("It reads {}", minimum.text()?);
     Ok(())
 }
 
@@ -72,6 +74,11 @@ impl Message {
         self.min = min;
     }
 
+    /// Read the letters formed by the lights
+    fn text(&self) -> Result<String> {
+        ocr::read_points(self.lights.iter().map(|light| &light.position))
+    }
+
     /// The size of the message
     fn size(&self) -> i64 {
         (self.max - self.min).manhattan_distance()
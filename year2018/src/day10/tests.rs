@@ -19,4 +19,5 @@ fn main() {
     let (message, time) = message.into_minimum_size();
     assert_eq!(time, 10_511);
     itertools::assert_equal(message.to_string().lines(), MAIN_EXPECTED.lines());
+    assert_eq!(message.text().unwrap(), "FNRGPBHR");
 }
@@ -3,14 +3,20 @@ use std::fmt::{Display, Formatter, Result as FmtResult, Write};
 
 use std::collections::HashMap;
 
-use commons::grid::Direction;
+use commons::arguments::Options;
+use commons::grid::gif::Recorder;
+use commons::grid::image::Rgb;
+use commons::grid::{Direction, Grid};
 use commons::Result;
 
 use crate::points::Point;
 
 pub const TITLE: &str = "Day 13: Mine Cart Madness";
 
-pub fn run(raw: String) -> Result<()> {
+/// The colors of the animation: the ground, the tracks and the carts
+const PALETTE: [Rgb; 3] = [[0x10, 0x10, 0x18], [0x70, 0x70, 0x70], [0xff, 0xd0, 0x20]];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let mut network = parse(&raw);
     let crash = first_part(&mut network);
     println!("The first crash happened at {},{}", crash.x, crash.y);
@@ -18,6 +24,12 @@ pub fn run(raw: String) -> Result<()> {
     let last = second_part(&mut network);
     println!("The last cart is at {},{}", last.x, last.y);
 
+    if let Some((mut recorder, path)) = Recorder::from_options(options, &PALETTE)? {
+        let ticks = animate(parse(&raw), &mut recorder);
+        recorder.save(&path)?;
+        println!("The {ticks} ticks until the last crash were saved in {path}");
+    }
+
     Ok(())
 }
 
@@ -80,6 +92,29 @@ fn second_part(network: &mut Network) -> Point {
         }
     }
 }
+
+/// Record the network until a single cart is left, returning the number of ticks
+fn animate(mut network: Network, recorder: &mut Recorder) -> usize {
+    // Only the carts move, they are drawn over the tracks at each tick
+    let tracks = network.tracks_colors();
+    let capture = |network: &Network, recorder: &mut Recorder| {
+        let mut colors = tracks.clone();
+        for point in network.carts.keys() {
+            colors[(point.x as isize, point.y as isize)] = 2;
+        }
+        recorder.capture(&colors, |color| *color);
+    };
+
+    let mut ticks = 0;
+    capture(&network, recorder);
+    while network.carts.len() > 1 {
+        network.next_tick();
+        ticks += 1;
+        capture(&network, recorder);
+    }
+    ticks
+}
+
 /// The rail network and the carts on it
 struct Network {
     /// The mine carts indexed by their position (ordered correctly since this is a BTreeMap)
@@ -98,6 +133,16 @@ impl Network {
         }
     }
 
+    /// The tracks as the colors of the palette: 0 for the ground and 1 for the tracks
+    fn tracks_colors(&self) -> Grid<u8> {
+        let (width, height) = self.tracks.keys().fold((0, 0), |(width, height), point| {
+            (width.max(point.x + 1), height.max(point.y + 1))
+        });
+        Grid::tabulate(width as usize, height as usize, |(x, y)| {
+            u8::from(self.tracks.contains_key(&Point::new(x as i64, y as i64)))
+        })
+    }
+
     /// Compute the next tick of the network, returns an Option of any crash that happened
     fn next_tick(&mut self) -> Option<Point> {
         let mut crash = None;
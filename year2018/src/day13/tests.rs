@@ -40,3 +40,14 @@ fn second_part_main() {
     let mut network = parse(MAIN);
     assert_eq!(second_part(&mut network), Point::new(111, 68));
 }
+
+#[test]
+fn animation() {
+    let mut recorder = Recorder::new(&PALETTE).with_skip(2);
+    assert_eq!(animate(parse(EXAMPLE_TWO), &mut recorder), 3);
+    assert_eq!(recorder.frames(), 2);
+    let mut gif = Vec::new();
+    recorder.write(&mut gif).unwrap();
+    // The canvas is as large as the network
+    assert_eq!(gif[6..10], [7, 0, 7, 0]);
+}
@@ -4,6 +4,9 @@ use std::str::FromStr;
 
 use std::collections::{HashMap, HashSet};
 
+use commons::grid::Grid;
+
+use super::log::{Event, Unit};
 use crate::points::Point;
 
 /// The current state of the fight
@@ -12,6 +15,8 @@ pub struct Fight {
     tiles: HashMap<Point, Tile>,
     units: BTreeMap<Point, u8>,
     dead_elf: bool,
+    /// What happened since the events were last taken, if they are logged
+    events: Option<Vec<Event>>,
 }
 
 impl Fight {
@@ -43,6 +48,49 @@ impl Fight {
         })
     }
 
+    /// Start logging what happens in the fight
+    pub fn logged(mut self) -> Self {
+        self.events = Some(Vec::new());
+        self
+    }
+
+    /// The events logged since the last call, none if they are not logged
+    pub fn take_events(&mut self) -> Vec<Event> {
+        self.events.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+
+    /// The map of the fight with `.` for the open tiles, and the units alive in reading order
+    /// with their position and hit points
+    pub fn snapshot(&self) -> (Grid<char>, Vec<(Unit, Point, u8)>) {
+        let (width, height) = self.tiles.keys().fold((0, 0), |(width, height), point| {
+            (width.max(point.x + 1), height.max(point.y + 1))
+        });
+        let map = Grid::tabulate(width as usize, height as usize, |(x, y)| {
+            match self.tiles.get(&Point::new(x as i64, y as i64)) {
+                Some(Tile::Unit { elf: false, .. }) => 'G',
+                Some(Tile::Unit { elf: true, .. }) => 'E',
+                Some(Tile::Wall) => '#',
+                None => '.',
+            }
+        });
+        let units = self
+            .units
+            .keys()
+            .filter_map(|point| match self.tiles.get(point)? {
+                Tile::Unit { elf, id, hp } => Some((Unit { elf: *elf, id: *id }, *point, *hp)),
+                Tile::Wall => None,
+            })
+            .collect();
+        (map, units)
+    }
+
+    /// Log an event if the events are logged
+    fn log(&mut self, event: impl FnOnce() -> Event) {
+        if let Some(events) = self.events.as_mut() {
+            events.push(event());
+        }
+    }
+
     /// Compute the outcome of the fight (completed rounds, remaining health)
     pub fn outcome(&self, rounds: usize) -> (usize, usize) {
         let remaining_health: usize = self
@@ -98,6 +146,9 @@ impl Fight {
 
     /// Move a `tile` from `from` to `to`
     fn move_unit(&mut self, from: Point, to: Point, tile: Tile) {
+        if let Some(unit) = tile.unit() {
+            self.log(|| Event::Move { unit, from, to });
+        }
         self.tiles.remove(&from);
         self.units.remove(&from);
         self.tiles.insert(to, tile);
@@ -144,7 +195,21 @@ impl Fight {
             })
             .min_by_key(|(cmp, _)| *cmp);
 
-        if let Some(((_, point), tile)) = target {
+        if let Some(((health, point), tile)) = target {
+            let attacker = self.tiles.get(&from).and_then(|t| t.unit());
+            if let Some((unit, target)) = attacker.zip(tile.unit()) {
+                let hp = health.saturating_sub(dmg);
+                let damage = health - hp;
+                self.log(|| Event::Attack {
+                    unit,
+                    target,
+                    damage,
+                    hp,
+                });
+                if hp == 0 {
+                    self.log(|| Event::Death { unit: target });
+                }
+            }
             if let Some(updated) = tile.damage(dmg) {
                 // Unit is alive, update its HP
                 self.tiles.insert(point, updated);
@@ -215,6 +280,7 @@ impl FromStr for Fight {
             tiles,
             units,
             dead_elf: false,
+            events: None,
         })
     }
 }
@@ -235,6 +301,14 @@ impl Tile {
         }
     }
 
+    /// The unit on this tile, if it is not a wall
+    fn unit(self) -> Option<Unit> {
+        match self {
+            Tile::Unit { elf, id, .. } => Some(Unit { elf, id }),
+            Tile::Wall => None,
+        }
+    }
+
     /// True if this tile is a goblin
     fn is_goblin(self) -> bool {
         matches!(self, Tile::Unit { elf: false, .. })
@@ -0,0 +1,306 @@
+//! The history of a fight, round by round, to replay it in the terminal or export it as text.
+//!
+//! The maps are written like in the puzzle examples, with the hit points of the units of each line
+//! on its right, so that they can be compared with the examples.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::fs::write;
+use std::io::stdout;
+
+use commons::grid::image::Rgb;
+use commons::grid::Grid;
+use commons::terminal::{Key, Keyboard, Renderer};
+use commons::{Result, WrapErr};
+
+use super::data::Fight;
+use crate::points::Point;
+
+/// The colors of the replay: the walls, the elves and the goblins
+const PALETTE: [(char, Rgb); 3] = [
+    ('#', [0x80, 0x80, 0x80]),
+    ('E', [0x40, 0xff, 0x40]),
+    ('G', [0xff, 0x40, 0x40]),
+];
+
+/// The number of rounds skipped by the up and down arrows of the replay
+const FAST_STEP: usize = 10;
+
+/// A unit of the fight, as its army and its ID
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Unit {
+    pub elf: bool,
+    pub id: u8,
+}
+
+impl Display for Unit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}{}", if self.elf { 'E' } else { 'G' }, self.id)
+    }
+}
+
+/// Something happening during the turn of a unit
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Event {
+    Move {
+        unit: Unit,
+        from: Point,
+        to: Point,
+    },
+    /// The damage really dealt (not more than the hit points of the target) and the hit points
+    /// left to the target
+    Attack {
+        unit: Unit,
+        target: Unit,
+        damage: u8,
+        hp: u8,
+    },
+    Death {
+        unit: Unit,
+    },
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Event::Move { unit, from, to } => write!(f, "{unit} moves from {from} to {to}"),
+            Event::Attack {
+                unit,
+                target,
+                damage,
+                hp,
+            } => write!(f, "{unit} hits {target} for {damage}, {hp} HP left"),
+            Event::Death { unit } => write!(f, "{unit} dies"),
+        }
+    }
+}
+
+/// The state of the fight after a round
+#[derive(Debug, Clone)]
+pub struct Round {
+    /// What happened during the round
+    pub events: Vec<Event>,
+    /// The map once the round is over
+    pub map: Grid<char>,
+    /// The units alive once the round is over, with their position and hit points
+    pub units: Vec<(Unit, Point, u8)>,
+}
+
+impl Round {
+    fn new(events: Vec<Event>, fight: &Fight) -> Self {
+        let (map, units) = fight.snapshot();
+        Self { events, map, units }
+    }
+
+    /// The lines of the map with the hit points of the units of each line, like `G(200), E(3)`
+    pub fn map_lines(&self) -> Vec<String> {
+        (0..self.map.height())
+            .map(|y| {
+                let mut line: String = (0..self.map.width())
+                    .map(|x| self.map[(x as isize, y as isize)])
+                    .collect();
+                let units: Vec<String> = self
+                    .units
+                    .iter()
+                    .filter(|(_, point, _)| point.y == y as i64)
+                    .map(|(unit, _, hp)| format!("{}({hp})", if unit.elf { 'E' } else { 'G' }))
+                    .collect();
+                if !units.is_empty() {
+                    line.push_str("   ");
+                    line.push_str(&units.join(", "));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// What a unit did during the whole fight
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Stats {
+    pub moves: usize,
+    pub attacks: usize,
+    pub damage: usize,
+    pub kills: usize,
+    /// The round where the unit died, if it did
+    pub death: Option<usize>,
+}
+
+/// A fight played until the end, round by round
+#[derive(Debug, Clone)]
+pub struct CombatLog {
+    /// The initial state (without events), then the state after each round. The last round is
+    /// the one where the fight ended, it is not a full round.
+    pub rounds: Vec<Round>,
+    /// The hit points left to the winners
+    pub hp: usize,
+}
+
+impl CombatLog {
+    /// Play a fight until the end, logging each round
+    pub fn record(fight: Fight, elves_attack: u8, goblins_attack: u8) -> Self {
+        let mut fight = fight.logged();
+        let mut rounds = vec![Round::new(Vec::new(), &fight)];
+        loop {
+            let finished = fight.next_round(elves_attack, goblins_attack);
+            rounds.push(Round::new(fight.take_events(), &fight));
+            if finished {
+                break;
+            }
+        }
+
+        let (_, hp) = fight.outcome(0);
+        Self { rounds, hp }
+    }
+
+    /// The number of rounds completed before the end of the fight
+    pub fn full_rounds(&self) -> usize {
+        self.rounds.len().saturating_sub(2)
+    }
+
+    /// True if the elves won the fight
+    pub fn elves_won(&self) -> bool {
+        let last = self.rounds.last();
+        last.is_some_and(|r| r.units.iter().any(|(unit, _, _)| unit.elf))
+    }
+
+    /// What each unit did during the fight, in the order of their IDs
+    pub fn stats(&self) -> Vec<(Unit, Stats)> {
+        let mut stats: Vec<(Unit, Stats)> = self.rounds[0]
+            .units
+            .iter()
+            .map(|(unit, _, _)| (*unit, Stats::default()))
+            .collect();
+        stats.sort_unstable_by_key(|(unit, _)| unit.id);
+
+        fn of(stats: &mut [(Unit, Stats)], unit: Unit) -> Option<&mut Stats> {
+            stats.iter_mut().find(|(u, _)| *u == unit).map(|(_, s)| s)
+        }
+        for (round, events) in self.rounds.iter().map(|r| &r.events).enumerate() {
+            for event in events {
+                match *event {
+                    Event::Move { unit, .. } => {
+                        of(&mut stats, unit).into_iter().for_each(|s| s.moves += 1)
+                    }
+                    Event::Attack {
+                        unit, damage, hp, ..
+                    } => {
+                        if let Some(stats) = of(&mut stats, unit) {
+                            stats.attacks += 1;
+                            stats.damage += damage as usize;
+                            stats.kills += usize::from(hp == 0);
+                        }
+                    }
+                    Event::Death { unit } => of(&mut stats, unit).into_iter().for_each(|s| {
+                        s.death = Some(round);
+                    }),
+                }
+            }
+        }
+        stats
+    }
+
+    /// Save the log in a text file
+    pub fn save(&self, path: &str) -> Result<()> {
+        write(path, self.to_string()).wrap_err_with(|| format!("Can't save the log to {path:?}"))
+    }
+
+    /// Show the rounds in the terminal, the left and right arrows going to the previous or the
+    /// next round (the up and down arrows by 10 rounds), `q` or escape to quit (or closing the
+    /// standard input)
+    pub fn replay(&self) -> Result<()> {
+        let keyboard = Keyboard::open().wrap_err("The replay needs a terminal")?;
+        let mut renderer = Renderer::new(stdout(), 0, 0).with_palette(&PALETTE);
+        let last = self.rounds.len() - 1;
+        let mut current = 0;
+        loop {
+            let round = &self.rounds[current];
+            let mut lines = round.map_lines();
+            lines.push(String::new());
+            lines.extend(round.events.iter().map(Event::to_string));
+            let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+            let frame = Grid::tabulate(width, lines.len(), |(x, y)| {
+                lines[y].chars().nth(x).unwrap_or(' ')
+            });
+
+            let title = match current {
+                0 => "Initially".to_owned(),
+                n if n == last => format!("Round {n}, the fight ends"),
+                n => format!("Round {n}"),
+            };
+            renderer.set_status(&format!(
+                "{title} ({current}/{last}), use the arrows to move between the rounds, q to quit"
+            ));
+            renderer.resize(width, lines.len());
+            renderer.draw(&frame, (0, 0), |c| *c)?;
+
+            let keys = keyboard.wait();
+            if keys.is_empty() {
+                // No more keys can be pressed
+                renderer.finish()?;
+                return Ok(());
+            }
+            for key in keys {
+                current = match key {
+                    Key::Left => current.saturating_sub(1),
+                    Key::Right => (current + 1).min(last),
+                    Key::Up => current.saturating_sub(FAST_STEP),
+                    Key::Down => (current + FAST_STEP).min(last),
+                    Key::Escape | Key::Interrupt | Key::Char('q') => {
+                        renderer.finish()?;
+                        return Ok(());
+                    }
+                    _ => current,
+                };
+            }
+        }
+    }
+}
+
+impl Display for CombatLog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let last = self.rounds.len() - 1;
+        for (index, round) in self.rounds.iter().enumerate() {
+            match index {
+                0 => writeln!(f, "Initially:")?,
+                _ => {
+                    writeln!(f, "Round {index}:")?;
+                    for event in &round.events {
+                        writeln!(f, "  {event}")?;
+                    }
+                    if index == last {
+                        writeln!(f, "When the combat ends:")?;
+                    } else if index == 1 {
+                        writeln!(f, "After 1 round:")?;
+                    } else {
+                        writeln!(f, "After {index} rounds:")?;
+                    }
+                }
+            }
+            for line in round.map_lines() {
+                writeln!(f, "{line}")?;
+            }
+            writeln!(f)?;
+        }
+
+        let rounds = self.full_rounds();
+        let winners = if self.elves_won() { "Elves" } else { "Goblins" };
+        writeln!(f, "Combat ends after {rounds} full rounds")?;
+        writeln!(f, "{winners} win with {} total hit points left", self.hp)?;
+        writeln!(f, "Outcome: {rounds} * {} = {}", self.hp, rounds * self.hp)?;
+        writeln!(f)?;
+
+        writeln!(f, "Units:")?;
+        for (unit, stats) in self.stats() {
+            let fate = match stats.death {
+                Some(round) => format!("died in round {round}"),
+                None => "survived".to_owned(),
+            };
+            writeln!(
+                f,
+                "  {unit}: {} moves, {} attacks for {} damage, {} kills, {fate}",
+                stats.moves, stats.attacks, stats.damage, stats.kills
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,10 +1,15 @@
+use commons::arguments::Options;
 use commons::{Result, WrapErr};
 
+use log::CombatLog;
+
 mod data;
+mod log;
 
 pub const TITLE: &str = "Day 15: Beverage Bandits";
+pub const OPTIONS: &[&str] = &["log", "replay-fight", "attack"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let fight = parse(&raw)?;
     let (remaining, hp) = fight.clone().first_part();
     println!("The fight finishes with an outcome of {}", remaining * hp);
@@ -14,6 +19,27 @@ pub fn run(raw: String) -> Result<()> {
         .wrap_err("Didn't find an outcome where the elves won without casualties")?;
     println!("The elves win with an outcome of {}", remaining * hp);
 
+    let log_path = options.values("log").first().cloned();
+    if log_path.is_some() || options.has("replay-fight") {
+        let elves_attack = match options.values("attack").first() {
+            Some(attack) => attack
+                .parse()
+                .wrap_err_with(|| format!("Invalid --attack '{attack}'"))?,
+            None => 3,
+        };
+        let log = CombatLog::record(fight, elves_attack, 3);
+        if options.has("replay-fight") {
+            log.replay()?;
+        }
+        if let Some(path) = log_path {
+            log.save(&path)?;
+            println!(
+                "The {} rounds of the fight were saved in {path}",
+                log.full_rounds()
+            );
+        }
+    }
+
     Ok(())
 }
 
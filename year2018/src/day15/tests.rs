@@ -1,3 +1,6 @@
+use commons::grid::Grid;
+
+use super::log::CombatLog;
 use super::*;
 
 const EXAMPLE_ONE: &str = include_str!("example_1.txt");
@@ -14,7 +17,9 @@ fn next_move_order_test() {
     let expected = parse(include_str!("move_expected.txt")).unwrap();
     before.next_round(0, 0);
 
-    assert_eq!(before.to_string(), expected.to_string())
+    let (before, expected) = (before.to_string(), expected.to_string());
+    let diff = Grid::from_text(&before).diff(&Grid::from_text(&expected), |c| *c);
+    assert_eq!(before, expected, "{diff}");
 }
 
 #[test]
@@ -94,3 +99,68 @@ fn second_part_main() {
     let fight = parse(MAIN).unwrap();
     assert_eq!(fight.second_part().unwrap(), (43, 1187));
 }
+
+#[test]
+fn combat_log_example_a() {
+    let log = CombatLog::record(parse(EXAMPLE_ONE).unwrap(), 3, 3);
+    assert_eq!(
+        (log.full_rounds(), log.hp, log.elves_won()),
+        (47, 590, false)
+    );
+    assert_eq!(
+        log.rounds[1].map_lines(),
+        [
+            "#######",
+            "#..G..#   G(200)",
+            "#...EG#   E(197), G(197)",
+            "#.#G#G#   G(200), G(197)",
+            "#...#E#   E(197)",
+            "#.....#",
+            "#######",
+        ]
+    );
+    assert_eq!(
+        log.rounds[47].map_lines(),
+        [
+            "#######",
+            "#G....#   G(200)",
+            "#.G...#   G(131)",
+            "#.#.#G#   G(59)",
+            "#...#.#",
+            "#....G#   G(200)",
+            "#######",
+        ]
+    );
+
+    let text = log.to_string();
+    assert!(text.contains("Round 1:\n  G1 moves from (2, 1) to (3, 1)\n"));
+    assert!(text.contains(
+        "After 2 rounds:\n#######\n#...G.#   G(200)\n#..GEG#   G(200), E(188), G(194)\n"
+    ));
+    assert!(text.ends_with(
+        "Outcome: 47 * 590 = 27730
+
+Units:
+  G1: 5 moves, 22 attacks for 66 damage, 0 kills, survived
+  E2: 0 moves, 23 attacks for 69 damage, 0 kills, died in round 23
+  G3: 3 moves, 23 attacks for 68 damage, 1 kills, survived
+  G4: 0 moves, 47 attacks for 141 damage, 0 kills, survived
+  G5: 7 moves, 42 attacks for 125 damage, 1 kills, survived
+  E6: 0 moves, 47 attacks for 141 damage, 0 kills, died in round 47
+"
+    ));
+}
+
+#[test]
+fn combat_log_elves_win() {
+    let log = CombatLog::record(parse(EXAMPLE_ONE).unwrap(), 15, 3);
+    assert_eq!(
+        (log.full_rounds(), log.hp, log.elves_won()),
+        (29, 172, true)
+    );
+    let stats = log.stats();
+    // No elf dies with this attack power, and every goblin is killed by one of them
+    assert!(stats.iter().all(|(unit, s)| unit.elf == s.death.is_none()));
+    let kills: usize = stats.iter().map(|(_, s)| s.kills).sum();
+    assert_eq!(kills, 4);
+}
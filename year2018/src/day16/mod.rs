@@ -1,22 +1,41 @@
-use std::collections::{hash_map::Entry, HashMap, HashSet};
-
+use commons::arguments::Options;
 use commons::Result;
-use instructions::{Int, OpCode};
-use parse::Sample;
+use instructions::inference::Inference;
+use instructions::trace::Tracer;
+use parse::{Instruction, Sample};
 
 pub use super::instructions;
 
 mod parse;
 
 pub const TITLE: &str = "Day 16: Chronal Classification";
+pub const OPTIONS: &[&str] = &["explain"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(&raw)?;
-    let (first, possible) = find_possible(&data.samples);
+    let (first, mut possible) = find_possible(&data.samples);
     println!("{first} samples behave like 3+ op codes");
-    let result = data.execute(&sieve(possible)).0[0];
+    let solved = possible.solve();
+    if options.has("explain") {
+        possible
+            .explanation()
+            .iter()
+            .for_each(|step| println!("{step}"));
+    }
+    let codes = solved?;
+    let mut tracer = Tracer::from_options(options)?;
+    let result = data.execute(&codes, tracer.as_mut())?.0[0];
     println!("The register 0 contains {result} after executing the program");
 
+    if let Some(mut tracer) = tracer {
+        let program: Vec<String> = data
+            .program
+            .iter()
+            .map(|i| format!("{:?} {} {} {}", codes[&i.code], i.a, i.b, i.c))
+            .collect();
+        tracer.write_profile(&program)?;
+    }
+
     Ok(())
 }
 
@@ -24,59 +43,19 @@ fn parse(s: &str) -> Result<parse::Program> {
     s.parse()
 }
 
-/// Find the possible corresponding OpCode for each instruction code
+/// Observe the samples to deduce the possible OpCode for each instruction code
 /// Also for first part, return the number of sample that behave like 3+ OpCodes
-fn find_possible(samples: &[Sample]) -> (usize, HashMap<Int, HashSet<OpCode>>) {
+fn find_possible(samples: &[Sample]) -> (usize, Inference) {
+    let mut inference = Inference::default();
     let mut more_than_three = 0;
-    let mut codes = HashMap::with_capacity(OpCode::ALL.len());
-    samples.iter().for_each(|sample| {
-        let possible: HashSet<OpCode> = corresponding_codes(sample).collect();
-        if possible.len() >= 3 {
+    for sample in samples {
+        let Instruction { code, a, b, c } = sample.instruction;
+        if inference.observe(code, [a, b, c], &sample.before.0, &sample.after.0) >= 3 {
             more_than_three += 1;
         }
-
-        match codes.entry(sample.instruction.code) {
-            Entry::Vacant(empty) => {
-                empty.insert(possible);
-            }
-            Entry::Occupied(mut full) => {
-                *full.get_mut() = full.get().intersection(&possible).copied().collect();
-            }
-        };
-    });
-
-    (more_than_three, codes)
-}
-
-/// An iterator over the OpCode that are possible for the given sample
-fn corresponding_codes(sample: &Sample) -> impl Iterator<Item = OpCode> + '_ {
-    OpCode::ALL.iter().copied().filter(move |code| {
-        let mut registers = sample.before.clone();
-        match sample.instruction.execute(&mut registers, *code) {
-            Ok(()) => registers == sample.after,
-            Err(_) => false,
-        }
-    })
-}
-
-/// Find a guaranteed mapping Int -> OpCode, fix it, then do it again until all are attributed
-fn sieve(mut possible: HashMap<Int, HashSet<OpCode>>) -> HashMap<Int, OpCode> {
-    let mut found = HashMap::with_capacity(OpCode::ALL.len());
-    while let Some((int, code)) = possible.iter().find_map(|(code, s)| {
-        if s.len() == 1 {
-            s.iter().next().map(|c| (*code, *c))
-        } else {
-            None
-        }
-    }) {
-        possible.retain(|_, codes| {
-            codes.remove(&code);
-            !codes.is_empty()
-        });
-        found.insert(int, code);
     }
 
-    found
+    (more_than_three, inference)
 }
 
 #[cfg(test)]
@@ -1,12 +1,14 @@
+use std::io::Stdout;
 use std::str::FromStr;
 
 use commons::{err, Report, Result, WrapErr};
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use commons::parse::sep_by_empty_lines;
 
-use super::instructions::{Int, OpCode};
+use super::instructions::trace::Tracer;
+use super::instructions::{self, Int, OpCode};
 
 /// The value of the registers at a certain point in time
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -48,18 +50,32 @@ pub struct Program {
 }
 
 impl Program {
-    /// Execute the full program with the given codes mappings
-    pub fn execute(&self, codes: &HashMap<Int, OpCode>) -> Register {
+    /// Execute the full program with the given codes mappings, recording it if there is a tracer
+    pub fn execute(
+        &self,
+        codes: &BTreeMap<Int, OpCode>,
+        mut tracer: Option<&mut Tracer<Stdout>>,
+    ) -> Result<Register> {
         let mut register = Register([0; 4]);
-        self.program.iter().for_each(|instruction| {
+        for (line, instruction) in self.program.iter().enumerate() {
             if let Some(code) = codes.get(&instruction.code) {
+                let before = register.0;
                 instruction
                     .execute(&mut register, *code)
-                    .expect("Failed program after finding the op codes");
+                    .wrap_err("Failed program after finding the op codes")?;
+                if let Some(tracer) = tracer.as_deref_mut() {
+                    let resolved = instructions::Instruction {
+                        code: *code,
+                        a: instruction.a,
+                        b: instruction.b,
+                        c: instruction.c,
+                    };
+                    tracer.record(line, &resolved, &before, &register.0)?;
+                }
             }
-        });
+        }
 
-        register
+        Ok(register)
     }
 }
 
@@ -13,9 +13,9 @@ fn example() {
 #[test]
 fn main() {
     let program = parse(MAIN).unwrap();
-    let (first, possible) = find_possible(&program.samples);
+    let (first, mut possible) = find_possible(&program.samples);
     assert_eq!(first, 677);
 
-    let found = sieve(possible);
-    assert_eq!(program.execute(&found).0[0], 540);
+    let found = possible.solve().unwrap();
+    assert_eq!(program.execute(&found, None).unwrap().0[0], 540);
 }
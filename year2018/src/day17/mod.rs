@@ -1,14 +1,20 @@
+use commons::arguments::Options;
 use commons::Result;
 
 mod spring;
 
 pub const TITLE: &str = "Day 17: Reservoir Research";
+pub const OPTIONS: &[&str] = &["image"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let mut scan = parse(&raw)?;
     scan.fill();
     println!("The scan contains {} wet tiles", scan.wet_tiles());
     println!("The scan contains {} water tiles", scan.water());
+    if let Some(path) = options.values("image").first() {
+        scan.image(2).save(path)?;
+        println!("The scan was saved in {path}");
+    }
 
     Ok(())
 }
@@ -6,7 +6,8 @@ use commons::{err, Report, Result, WrapErr};
 use itertools::Itertools;
 use std::collections::HashMap;
 
-use commons::grid::Point;
+use commons::grid::image::Image;
+use commons::grid::{Grid, Point};
 
 /// The result of the ground scan (input to the problem)
 #[derive(Debug, Clone)]
@@ -126,6 +127,33 @@ impl Scan {
         }
     }
 
+    /// The char representing a point of the scan for display
+    fn char_at(&self, point: Point) -> char {
+        if point == Self::SPRING {
+            '+'
+        } else if let Some(tile) = self.tiles.get(&point) {
+            tile.char()
+        } else {
+            '.'
+        }
+    }
+
+    /// Draw the scan as an image, from the spring to its lowest point
+    pub fn image(&self, scale: usize) -> Image {
+        let width = (self.max.x - self.min.x + 1) as usize;
+        let height = (self.max.y + 1) as usize;
+        let grid = Grid::tabulate(width, height, |(x, y)| {
+            self.char_at(Point::new(self.min.x + x as i64, y as i64))
+        });
+        grid.to_image(scale, |char| match char {
+            '+' => [220, 40, 40],
+            '|' => [140, 190, 230],
+            '~' => [30, 90, 200],
+            '#' => [110, 70, 40],
+            _ => [235, 215, 170],
+        })
+    }
+
     /// True if the current tile is a wall or water
     fn is_full(&self, current: &Point) -> bool {
         self.tiles.get(current).map_or(false, |t| t.is_occupied())
@@ -213,18 +241,8 @@ impl FromStr for Scan {
 impl Display for Scan {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         (0..(self.max.y + 1)).try_for_each(|y| {
-            (self.min.x..(self.max.x + 1)).try_for_each(|x| {
-                let point = Point::new(x, y);
-                let char = if point == Self::SPRING {
-                    '+'
-                } else if let Some(tile) = self.tiles.get(&point) {
-                    tile.char()
-                } else {
-                    '.'
-                };
-
-                f.write_char(char)
-            })?;
+            (self.min.x..(self.max.x + 1))
+                .try_for_each(|x| f.write_char(self.char_at(Point::new(x, y))))?;
 
             f.write_char('\n')
         })
@@ -1,3 +1,5 @@
+use commons::grid::Grid;
+
 use super::*;
 
 const EXAMPLE: &str = include_str!("example.txt");
@@ -11,6 +13,15 @@ fn example() {
     assert_eq!(scan.water(), 29);
 }
 
+#[test]
+fn example_filled() {
+    let mut scan = parse(EXAMPLE).unwrap();
+    scan.fill();
+    let (filled, expected) = (scan.to_string(), include_str!("example_filled.txt"));
+    let diff = Grid::from_text(&filled).diff(&Grid::from_text(expected), |c| *c);
+    assert_eq!(filled, expected, "{diff}");
+}
+
 #[test]
 fn main() {
     let mut scan = parse(MAIN).unwrap();
@@ -18,3 +29,19 @@ fn main() {
     assert_eq!(scan.wet_tiles(), 36_171);
     assert_eq!(scan.water(), 28_204);
 }
+
+#[test]
+fn image() {
+    let mut scan = parse(EXAMPLE).unwrap();
+    scan.fill();
+    let display = scan.to_string();
+    let image = scan.image(2);
+    let width = display.lines().next().unwrap().len();
+    assert_eq!(image.width(), 2 * width);
+    assert_eq!(image.height(), 2 * display.lines().count());
+
+    let spring = display.find('+').unwrap();
+    assert_eq!(image.get(2 * spring + 1, 1), Some([220, 40, 40]));
+    let water = display.lines().nth(6).unwrap().find('~').unwrap();
+    assert_eq!(image.get(2 * water, 12), Some([30, 90, 200]));
+}
@@ -1,20 +1,35 @@
 use std::fmt::{Display, Formatter, Result as FmtResult, Write};
 use std::str::FromStr;
 
+use commons::arguments::Options;
+use commons::grid::gif::Recorder;
+use commons::grid::image::Rgb;
 use commons::grid::Grid;
 use commons::{bail, err, Report, Result, WrapErr};
 
 pub const TITLE: &str = "Day 18: Settlers of The North Pole";
 
-pub fn run(raw: String) -> Result<()> {
+/// The number of minutes recorded in the animation, enough to see the cycle of the area
+const ANIMATED_MINUTES: usize = 600;
+
+/// The colors of the animation: the open ground, the trees and the lumberyards
+const PALETTE: [Rgb; 3] = [[0xd8, 0xc8, 0x98], [0x20, 0x80, 0x30], [0x70, 0x40, 0x20]];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let area = parse(&raw)?;
     let (trees, lumberyard) = first_part(area.clone()).trees_and_lumberyards();
     let first = trees * lumberyard;
     println!("After 10 minutes: {trees} trees and {lumberyard} lumberyards: {first} resources");
 
-    let second = second_part(area).wrap_err("Could not find the period of the system")?;
+    let second = second_part(area.clone()).wrap_err("Could not find the period of the system")?;
     println!("After one billion minutes: {second} resources");
 
+    if let Some((mut recorder, path)) = Recorder::from_options(options, &PALETTE)? {
+        animate(area, ANIMATED_MINUTES, &mut recorder);
+        recorder.save(&path)?;
+        println!("The first {ANIMATED_MINUTES} minutes were saved in {path}");
+    }
+
     Ok(())
 }
 
@@ -74,6 +89,17 @@ fn second_part(mut area: Area) -> Option<isize> {
     Some(result)
 }
 
+/// Record the initial area then its state after each minute
+fn animate(mut area: Area, minutes: usize, recorder: &mut Recorder) {
+    let mut swap = area.clone();
+    recorder.capture(&area.0, |tile| tile.color());
+    (0..minutes).for_each(|_| {
+        area.compute_next(&mut swap);
+        std::mem::swap(&mut area, &mut swap);
+        recorder.capture(&area.0, |tile| tile.color());
+    });
+}
+
 /// Find the first period and its starting index
 fn find_period(diffs: &[isize]) -> Option<(Vec<isize>, usize)> {
     (0..diffs.len()).find_map(|start| {
@@ -245,6 +271,15 @@ impl Tile {
             Tile::Lumberyard => '#',
         }
     }
+
+    /// The index of the color of this Tile in the palette of the animation
+    const fn color(self) -> u8 {
+        match self {
+            Tile::Open => 0,
+            Tile::Trees => 1,
+            Tile::Lumberyard => 2,
+        }
+    }
 }
 
 #[cfg(test)]
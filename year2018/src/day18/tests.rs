@@ -9,6 +9,17 @@ fn first_part_example() {
     assert_eq!(first_part(area).trees_and_lumberyards(), (37, 31));
 }
 
+#[test]
+fn animation_example() {
+    let mut recorder = Recorder::new(&PALETTE).with_crop((0, 0), (5, 5));
+    animate(parse(EXAMPLE).unwrap(), 10, &mut recorder);
+    assert_eq!(recorder.frames(), 11);
+    let mut gif = Vec::new();
+    recorder.write(&mut gif).unwrap();
+    // The canvas is cropped to the top left corner of the area
+    assert_eq!(gif[6..10], [5, 0, 5, 0]);
+}
+
 #[test]
 fn first_part_main() {
     let area = parse(MAIN).unwrap();
@@ -13,13 +13,13 @@ fn first_part_example() {
 #[test]
 fn first_part_main() {
     let program = parse(MAIN).unwrap();
-    let last = run_optimized(program, 0).unwrap();
+    let last = run_optimized(program, 0, None).unwrap();
     assert_eq!(last, 2_520);
 }
 
 #[test]
 fn second_part_main() {
     let program = parse(MAIN).unwrap();
-    let last = run_optimized(program, 1).unwrap();
+    let last = run_optimized(program, 1, None).unwrap();
     assert_eq!(last, 27_941_760);
 }
@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use commons::grid::image::{Image, Rgb};
+use commons::grid::{Grid, Point};
+
+/// The color of the walls in the heat map
+const WALL: Rgb = [0x20, 0x20, 0x20];
+
+/// The colors of the closest and of the furthest rooms in the heat map
+const HEAT: [Rgb; 2] = [[0x30, 0x40, 0xff], [0xff, 0x30, 0x20]];
+
+/// The facility drawn like in the puzzle, with the rooms and the doors between them
+pub struct Maze {
+    /// The rooms (`.` and `X` for the start), the doors (`|` and `-`) and the walls (`#`)
+    tiles: Grid<char>,
+    /// The number of doors to cross to reach each room or door (none for the walls)
+    distances: Grid<Option<u16>>,
+}
+
+impl Maze {
+    /// Draw the maze of an explored facility
+    /// # Arguments
+    /// * `rooms` - The number of doors to cross to reach each room
+    /// * `doors` - The rooms on both sides of each door
+    pub fn new(rooms: &HashMap<Point, u16>, doors: &HashSet<(Point, Point)>) -> Self {
+        let (min, max) =
+            rooms
+                .keys()
+                .fold((Point::new(0, 0), Point::new(0, 0)), |(min, max), room| {
+                    let min = Point::new(min.x.min(room.x), min.y.min(room.y));
+                    let max = Point::new(max.x.max(room.x), max.y.max(room.y));
+                    (min, max)
+                });
+        // Each room is surrounded by walls or doors, shared with the rooms next to it
+        let width = 2 * (max.x - min.x + 1) as usize + 1;
+        let height = 2 * (max.y - min.y + 1) as usize + 1;
+        let tile = |room: Point| {
+            let x = 2 * (room.x - min.x) + 1;
+            let y = 2 * (room.y - min.y) + 1;
+            (x as isize, y as isize)
+        };
+
+        let mut tiles = Grid::fill(width, height, '#');
+        let mut distances = Grid::fill(width, height, None);
+        for (room, distance) in rooms {
+            let char = if *room == Point::new(0, 0) { 'X' } else { '.' };
+            tiles[tile(*room)] = char;
+            distances[tile(*room)] = Some(*distance);
+        }
+        for &(from, to) in doors {
+            let ((x1, y1), (x2, y2)) = (tile(from), tile(to));
+            let door = ((x1 + x2) / 2, (y1 + y2) / 2);
+            tiles[door] = if y1 == y2 { '|' } else { '-' };
+            // A door is as far as the furthest of its rooms
+            let distance = rooms.get(&from).max(rooms.get(&to)).copied();
+            distances[door] = distance;
+        }
+
+        Self { tiles, distances }
+    }
+
+    /// Draw the distances to the rooms and to the doors, from the closest in blue to the furthest
+    /// in red (and the walls in black)
+    /// # Arguments
+    /// * `scale` - The size of the square of pixels drawn for each tile
+    pub fn heat_map(&self, scale: usize) -> Image {
+        let max = self
+            .distances
+            .as_ref()
+            .iter()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0);
+        self.distances.to_image(scale, |distance| match distance {
+            Some(distance) => {
+                let ratio = f64::from(*distance) / f64::from(max.max(1));
+                let [near, far] = HEAT;
+                let mut color = [0; 3];
+                for (c, (near, far)) in color.iter_mut().zip(near.into_iter().zip(far)) {
+                    *c = (f64::from(near) + (f64::from(far) - f64::from(near)) * ratio) as u8;
+                }
+                color
+            }
+            None => WALL,
+        })
+    }
+}
+
+impl Display for Maze {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.tiles.fmt(f)
+    }
+}
@@ -2,16 +2,39 @@ use std::ops::Add;
 
 use std::collections::{HashMap, HashSet};
 
+use commons::arguments::Options;
 use commons::grid::Point;
 use commons::Result;
 
+mod maze;
+
 pub const TITLE: &str = "Day 20: A Regular Map";
+pub const OPTIONS: &[&str] = &["maze", "image"];
 
-pub fn run(regex: String) -> Result<()> {
-    let map = build_map(&regex);
+pub fn run(regex: String, options: &Options) -> Result<()> {
+    let image = options.values("image");
+    let draw = options.has("maze") || !image.is_empty();
+    let mut doors = HashSet::new();
+    let map = explore(&regex, |from, to| {
+        // The doors are only needed to draw the maze
+        if draw {
+            doors.insert((from, to));
+        }
+    });
     println!("The furthest room is {} doors away", first_part(&map));
     println!("{} rooms are more than 1000 doors away", second_part(&map));
 
+    if draw {
+        let maze = maze::Maze::new(&map, &doors);
+        if options.has("maze") {
+            print!("{maze}");
+        }
+        if let Some(path) = image.first() {
+            maze.heat_map(2).save(path)?;
+            println!("The distances to the rooms were saved in {path}");
+        }
+    }
+
     Ok(())
 }
 
@@ -27,8 +50,9 @@ fn second_part(map: &HashMap<Point, u16>) -> usize {
     map.values().filter(|room| **room >= 1000).count()
 }
 
-/// Explore the entire regex given to build a map of the facility
-fn build_map(regex: &str) -> HashMap<Point, u16> {
+/// Explore the entire regex given to build a map of the facility, calling `door` with the rooms
+/// on both sides of each door crossed (several times for the doors crossed more than once)
+fn explore(regex: &str, mut door: impl FnMut(Point, Point)) -> HashMap<Point, u16> {
     let mut rooms: HashMap<Point, u16> = HashMap::with_capacity(regex.len());
 
     let mut stack = vec![]; // Store the previous groups data
@@ -68,7 +92,9 @@ fn build_map(regex: &str) -> HashMap<Point, u16> {
 
             current.iter_mut().for_each(|point| {
                 let distance = rooms.get(point).copied().unwrap_or_default() + 1;
+                let from = *point;
                 *point = point.add(&offset);
+                door(from, *point);
                 rooms
                     .entry(*point)
                     .and_modify(|room| *room = distance.min(*room))
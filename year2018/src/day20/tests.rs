@@ -1,3 +1,5 @@
+use commons::grid::Grid;
+
 use super::*;
 
 const ONE: &str = include_str!("example_1.txt");
@@ -5,6 +7,20 @@ const TWO: &str = include_str!("example_2.txt");
 const THREE: &str = include_str!("example_3.txt");
 const MAIN: &str = include_str!("data.txt");
 
+/// Explore the entire regex given to build a map of the facility
+fn build_map(regex: &str) -> HashMap<Point, u16> {
+    explore(regex, |_, _| {})
+}
+
+/// Explore the entire regex given to draw the maze of the facility
+fn build_maze(regex: &str) -> maze::Maze {
+    let mut doors = HashSet::new();
+    let rooms = explore(regex, |from, to| {
+        doors.insert((from, to));
+    });
+    maze::Maze::new(&rooms, &doors)
+}
+
 #[test]
 fn first_part_example_one() {
     let map = build_map(ONE);
@@ -34,3 +50,27 @@ fn second_part_main() {
     let map = build_map(MAIN);
     assert_eq!(second_part(&map), 8517);
 }
+
+#[test]
+fn maze_examples() {
+    let expected = [
+        (ONE, include_str!("example_1_maze.txt")),
+        (TWO, include_str!("example_2_maze.txt")),
+        (THREE, include_str!("example_3_maze.txt")),
+    ];
+    for (regex, maze) in expected {
+        let built = build_maze(regex).to_string();
+        let diff = Grid::from_text(&built).diff(&Grid::from_text(maze), |c| *c);
+        assert_eq!(built, maze, "{diff}");
+    }
+}
+
+#[test]
+fn heat_map_example_one() {
+    let image = build_maze(ONE).heat_map(1);
+    assert_eq!((image.width(), image.height()), (11, 11));
+    // The walls are black, the start is the coldest room and the furthest room the hottest
+    assert_eq!(image.get(0, 0), Some([0x20, 0x20, 0x20]));
+    assert_eq!(image.get(5, 5), Some([0x30, 0x40, 0xff]));
+    assert_eq!(image.get(9, 1), Some([0xff, 0x30, 0x20]));
+}
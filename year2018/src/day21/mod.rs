@@ -1,22 +1,35 @@
 use std::collections::HashSet;
+use std::io::Stdout;
 
+use commons::arguments::Options;
 use commons::{Result, WrapErr};
 
-use super::instructions::{Int, Program};
+use super::instructions::optimizer::Optimizer;
+use super::instructions::trace::Tracer;
+use super::instructions::{index, Int, OpCode, Program};
 
 pub const TITLE: &str = "Day 21: Chronal Conversion";
+pub const OPTIONS: &[&str] = &["decompile"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let mut program = parse(&raw)?;
+    if options.has("decompile") {
+        println!("The decompiled program:\n{}", program.decompile()?);
+    }
+    let mut tracer = Tracer::from_options(options)?;
     println!(
         "The program will halt after the fewest cycles for input {}",
-        first_exit_value(&mut program)?.wrap_err("No exit values were found")?
+        first_exit_value(&mut program, tracer.as_mut())?.wrap_err("No exit values were found")?
     );
     println!(
         "The program will halt after the most cycles for input {}",
-        last_exit_value(&mut program)?.wrap_err("No exit values were found")?
+        last_exit_value(&mut program, tracer.as_mut())?.wrap_err("No exit values were found")?
     );
 
+    if let Some(mut tracer) = tracer {
+        tracer.write_profile(&program.instructions)?;
+    }
+
     Ok(())
 }
 
@@ -25,9 +38,17 @@ fn parse(s: &str) -> Result<Program> {
 }
 
 /// Find the first possible exit value of a program
-fn first_exit_value(program: &mut Program) -> Result<Option<Int>> {
-    while step_optimized(program)?.is_some() {
-        if let Some(exit_value) = possible_exit_value(program) {
+fn first_exit_value(
+    program: &mut Program,
+    mut tracer: Option<&mut Tracer<Stdout>>,
+) -> Result<Option<Int>> {
+    let optimizer = Optimizer::new(program);
+    let check = exit_check(program)?;
+    while optimizer
+        .step_traced(program, tracer.as_deref_mut())?
+        .is_some()
+    {
+        if let Some(exit_value) = possible_exit_value(program, check) {
             return Ok(Some(exit_value));
         }
     }
@@ -36,15 +57,23 @@ fn first_exit_value(program: &mut Program) -> Result<Option<Int>> {
 }
 
 /// Find the last non duplicate exit value of a program
-fn last_exit_value(program: &mut Program) -> Result<Option<Int>> {
+fn last_exit_value(
+    program: &mut Program,
+    mut tracer: Option<&mut Tracer<Stdout>>,
+) -> Result<Option<Int>> {
     let mut last: Option<Int> = None;
     let mut seen: HashSet<Int> = HashSet::new();
 
     // Note: 0 is NOT an exit value for the program, so this will loop indefinitely
     // We need to exit manually as soon as an exit value is seen a second time
+    let optimizer = Optimizer::new(program);
+    let check = exit_check(program)?;
     program.reset();
-    while step_optimized(program)?.is_some() {
-        if let Some(exit_value) = possible_exit_value(program) {
+    while optimizer
+        .step_traced(program, tracer.as_deref_mut())?
+        .is_some()
+    {
+        if let Some(exit_value) = possible_exit_value(program, check) {
             // If the exit value was already present in the map, we have found all of them
             if !seen.insert(exit_value) {
                 break;
@@ -57,29 +86,29 @@ fn last_exit_value(program: &mut Program) -> Result<Option<Int>> {
     Ok(last)
 }
 
-/// Run the program for one step, optimizing the biggest procedure call
-///
-/// This might be exclusive to this particular input ?
-fn step_optimized(program: &mut Program) -> Result<Option<()>> {
-    // Optimize the procedure 18-25
-    if program.registers[program.ip_index] == 18 {
-        program.registers[1] = program.registers[2] / 256;
-        program.registers[5] = 1;
-        program.registers[4] = 26;
-        Ok(Some(()))
-    } else {
-        program.step()
-    }
+/// Find the only instruction reading #0: the comparison that exits the program when equal
+/// # Returns
+/// The line of that instruction, and the register #0 is compared to
+fn exit_check(program: &Program) -> Result<(usize, usize)> {
+    program
+        .instructions
+        .iter()
+        .enumerate()
+        .find_map(|(line, inst)| match (inst.code, inst.a, inst.b) {
+            (OpCode::EqRR, 0, other) | (OpCode::EqRR, other, 0) if other != 0 => {
+                Some((line, index(other).ok()?))
+            }
+            _ => None,
+        })
+        .wrap_err("No instruction compares a register with #0")
 }
 
-/// Every time the program runs the instruction 28 (eqrr 3 0 1) it can exit
-/// As #0 is never touched anywhere, this means that #3 value is an exit value
+/// Every time the program runs the exit check it can exit
+/// As #0 is never touched anywhere, this means that the compared register is an exit value
 /// Since if #0 was set to that value, then the program would have exited right here
-///
-/// This might be exclusive to this particular input ?
-fn possible_exit_value(program: &Program) -> Option<Int> {
-    if program.line == 28 {
-        Some(program.registers[3])
+fn possible_exit_value(program: &Program, (line, register): (usize, usize)) -> Option<Int> {
+    if program.line == line {
+        program.registers.get(register).copied()
     } else {
         None
     }
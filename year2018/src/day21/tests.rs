@@ -5,13 +5,13 @@ const MAIN: &str = include_str!("data.txt");
 #[test]
 fn first_part_main() {
     let mut program = parse(MAIN).unwrap();
-    let first = first_exit_value(&mut program).unwrap().unwrap();
+    let first = first_exit_value(&mut program, None).unwrap().unwrap();
     assert_eq!(first, 16_311_888);
 }
 
 #[test]
 fn second_part_main() {
     let mut program = parse(MAIN).unwrap();
-    let last = last_exit_value(&mut program).unwrap().unwrap();
+    let last = last_exit_value(&mut program, None).unwrap().unwrap();
     assert_eq!(last, 1_413_889);
 }
@@ -0,0 +1,851 @@
+//! A decompiler turning a `Program` into structured pseudocode
+//!
+//! The instructions are first lifted into statements:
+//! - Writes to the instruction pointer become jumps (`goto`, `halt`, or a conditional jump when
+//!   the instruction adds the result of the previous comparison to the instruction pointer)
+//! - Reads of the instruction pointer become the constant line number
+//!
+//! The statements are then simplified (dead assignments removed, single use values inlined,
+//! conditional jumps over a `goto` merged into one) before loops and conditionals are recovered.
+//! ```
+//! use advent_of_code_2018::instructions::Program;
+//!
+//! let program: Program = "#ip 3\n\
+//!     seti 10 0 1\n\
+//!     addi 0 1 0\n\
+//!     addi 1 -1 1\n\
+//!     eqri 1 0 2\n\
+//!     addr 3 2 3\n\
+//!     seti 0 0 3"
+//!     .parse()
+//!     .unwrap();
+//! let code = program.decompile().unwrap();
+//! assert_eq!(
+//!     code.to_string(),
+//!     "0 | b = 10\n  | do {\n1 |     a += 1\n2 |     b -= 1\n4 | } while b != 0\n"
+//! );
+//! ```
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use commons::{ensure, Result};
+
+use super::{Instruction, Int, OpCode, Program};
+
+/// The default names of the registers
+pub(super) const NAMES: [&str; 6] = ["a", "b", "c", "d", "e", "f"];
+
+/// The name of the register bound to the instruction pointer
+const IP_NAME: &str = "ip";
+
+/// A binary operator in an expression
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    Add,
+    Mul,
+    And,
+    Or,
+    Greater,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Op {
+    /// Compute the result of this operator
+    pub fn apply(self, a: Int, b: Int) -> Int {
+        match self {
+            Self::Add => a + b,
+            Self::Mul => a * b,
+            Self::And => a & b,
+            Self::Or => a | b,
+            Self::Greater => Int::from(a > b),
+            Self::LessOrEqual => Int::from(a <= b),
+            Self::Equal => Int::from(a == b),
+            Self::NotEqual => Int::from(a != b),
+        }
+    }
+
+    /// True if this operator produces a boolean (0 or 1)
+    pub fn is_comparison(self) -> bool {
+        matches!(
+            self,
+            Self::Greater | Self::LessOrEqual | Self::Equal | Self::NotEqual
+        )
+    }
+
+    /// The comparison that is true when this one is false
+    fn negate(self) -> Option<Self> {
+        match self {
+            Self::Greater => Some(Self::LessOrEqual),
+            Self::LessOrEqual => Some(Self::Greater),
+            Self::Equal => Some(Self::NotEqual),
+            Self::NotEqual => Some(Self::Equal),
+            _ => None,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Mul => "*",
+            Self::And => "&",
+            Self::Or => "|",
+            Self::Greater => ">",
+            Self::LessOrEqual => "<=",
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+        }
+    }
+}
+
+/// An expression computed from the registers
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    /// A constant value
+    Value(Int),
+    /// The value of a register
+    Register(usize),
+    /// A binary operation
+    Binary(Op, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Build a binary operation, folding constants and redundant boolean tests
+    pub fn binary(op: Op, left: Expr, right: Expr) -> Self {
+        match (op, left, right) {
+            (op, Self::Value(a), Self::Value(b)) => Self::Value(op.apply(a, b)),
+            (Op::NotEqual, test, Self::Value(0)) if test.is_comparison() => test,
+            (Op::Equal, test, Self::Value(0)) if test.is_comparison() => test.negate(),
+            (op, left, right) => Self::Binary(op, Box::new(left), Box::new(right)),
+        }
+    }
+
+    /// True if this expression produces a boolean (0 or 1)
+    pub fn is_comparison(&self) -> bool {
+        matches!(self, Self::Binary(op, _, _) if op.is_comparison())
+    }
+
+    /// The condition that is true when this one is false
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Binary(op, left, right) => match op.negate() {
+                Some(negated) => Self::Binary(negated, left, right),
+                None => Self::binary(Op::Equal, Self::Binary(op, left, right), Self::Value(0)),
+            },
+            other => Self::binary(Op::Equal, other, Self::Value(0)),
+        }
+    }
+
+    /// The number of times a register is read by this expression
+    pub fn reads(&self, register: usize) -> usize {
+        match self {
+            Self::Value(_) => 0,
+            Self::Register(r) => usize::from(*r == register),
+            Self::Binary(_, left, right) => left.reads(register) + right.reads(register),
+        }
+    }
+
+    /// The set of registers read by this expression (as a bit set)
+    fn registers(&self) -> u8 {
+        match self {
+            Self::Value(_) => 0,
+            Self::Register(r) => 1 << r,
+            Self::Binary(_, left, right) => left.registers() | right.registers(),
+        }
+    }
+
+    /// Replace the reads of a register by another expression
+    fn substitute(self, register: usize, by: &Expr) -> Self {
+        match self {
+            Self::Register(r) if r == register => by.clone(),
+            Self::Binary(op, left, right) => Self::binary(
+                op,
+                left.substitute(register, by),
+                right.substitute(register, by),
+            ),
+            other => other,
+        }
+    }
+
+    /// Add a constant to this expression, merging it in an existing constant term if possible
+    fn offset(self, by: Int) -> Self {
+        match self {
+            Self::Binary(Op::Add, left, right) => match (*left, *right) {
+                (Self::Value(v), other) => Self::binary(Op::Add, Self::Value(v + by), other),
+                (other, Self::Value(v)) => Self::binary(Op::Add, other, Self::Value(v + by)),
+                (left, right) => {
+                    Self::binary(Op::Add, Self::binary(Op::Add, left, right), Self::Value(by))
+                }
+            },
+            other => Self::binary(Op::Add, other, Self::Value(by)),
+        }
+    }
+
+    /// Write this expression using the given register names
+    fn write(&self, names: &[String; 6], parent: Option<Op>, out: &mut String) {
+        match self {
+            Self::Value(v) => out.push_str(&v.to_string()),
+            Self::Register(r) => out.push_str(&names[*r]),
+            Self::Binary(op, left, right) => {
+                // Only the usual arithmetic precedence is left implicit
+                let parenthesis = parent.is_some_and(|parent| match (parent, op) {
+                    (Op::Add, Op::Mul) => false,
+                    (parent, Op::Add | Op::Mul) if parent.is_comparison() => false,
+                    (parent, op) => parent != *op || op.is_comparison(),
+                });
+                if parenthesis {
+                    out.push('(');
+                }
+                left.write(names, Some(*op), out);
+                out.push(' ');
+                out.push_str(op.symbol());
+                out.push(' ');
+                right.write(names, Some(*op), out);
+                if parenthesis {
+                    out.push(')');
+                }
+            }
+        }
+    }
+}
+
+/// A statement of the decompiled program, jump targets are line numbers
+/// (the number of lines for a jump out of the program)
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Statement {
+    /// Set a register to the value of an expression
+    Assign(usize, Expr),
+    /// Jump to a line
+    Goto(usize),
+    /// Jump to a line if the expression is not 0
+    Branch(Expr, usize),
+    /// Jump to the line computed by an expression
+    Jump(Expr),
+}
+
+impl Statement {
+    /// The number of times a register is read by this statement
+    fn reads(&self, register: usize) -> usize {
+        match self {
+            Self::Assign(_, expr) | Self::Branch(expr, _) | Self::Jump(expr) => {
+                expr.reads(register)
+            }
+            Self::Goto(_) => 0,
+        }
+    }
+
+    /// Replace the reads of a register by another expression
+    fn substitute(self, register: usize, by: &Expr) -> Self {
+        match self {
+            Self::Assign(r, expr) => Self::Assign(r, expr.substitute(register, by)),
+            Self::Branch(expr, to) => Self::Branch(expr.substitute(register, by), to),
+            Self::Jump(expr) => Self::Jump(expr.substitute(register, by)),
+            goto => goto,
+        }
+    }
+
+    /// The line this statement explicitly jumps to
+    fn target(&self) -> Option<usize> {
+        match self {
+            Self::Goto(to) | Self::Branch(_, to) => Some(*to),
+            _ => None,
+        }
+    }
+
+    /// The first line a computed jump can land on.
+    /// Registers are assumed to be positive, so `k + r` can only land after `k`.
+    fn jump_start(&self) -> Option<usize> {
+        match self {
+            Self::Jump(Expr::Binary(Op::Add, left, right)) => match (left.as_ref(), right.as_ref())
+            {
+                (Expr::Value(k), _) | (_, Expr::Value(k)) => Some(usize::try_from(*k).unwrap_or(0)),
+                _ => Some(0),
+            },
+            Self::Jump(_) => Some(0),
+            _ => None,
+        }
+    }
+}
+
+/// A basic block: a range of lines that is always executed from start to end
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Block {
+    /// The first line of the block
+    pub start: usize,
+    /// The line after the last line of the block
+    pub end: usize,
+    /// The start of the blocks that can be executed next (the number of lines for the exit)
+    pub successors: Vec<usize>,
+}
+
+/// A decompiled program, displayed as structured pseudocode
+#[derive(Debug, Clone)]
+pub struct Decompiled {
+    /// The names of the registers
+    names: [String; 6],
+    /// The statement of each line, None if it was merged into another one
+    statements: Vec<Option<Statement>>,
+}
+
+impl Decompiled {
+    /// Decompile a program
+    pub fn new(program: &Program) -> Result<Self> {
+        let len = program.instructions.len();
+        let statements = program
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(line, instruction)| {
+                let previous = line.checked_sub(1).map(|p| &program.instructions[p]);
+                lift(instruction, previous, line, program.ip_index, len).map(Some)
+            })
+            .collect::<Result<_>>()?;
+
+        let mut names = NAMES.map(String::from);
+        names[program.ip_index] = IP_NAME.to_owned();
+        let mut decompiled = Self { names, statements };
+        while decompiled.remove_dead() | decompiled.inline() | decompiled.merge() {}
+        Ok(decompiled)
+    }
+
+    /// Change the name of a register in the pseudocode
+    pub fn rename(&mut self, register: usize, name: &str) {
+        self.names[register] = name.to_owned();
+    }
+
+    /// The statement of each line, None if it was merged into another one
+    pub fn statements(&self) -> &[Option<Statement>] {
+        &self.statements
+    }
+
+    /// The basic blocks of the program.
+    /// Computed jumps are assumed to land on the start of a block at or after their constant.
+    pub fn blocks(&self) -> Vec<Block> {
+        let len = self.statements.len();
+        let mut leaders = BTreeSet::from([self.next_live(0)]);
+        self.lines().for_each(|(line, statement)| {
+            leaders.extend(statement.target());
+            leaders.extend(statement.jump_start().map(|start| self.next_live(start)));
+            if !matches!(statement, Statement::Assign(..)) {
+                leaders.insert(self.next_live(line + 1));
+            }
+        });
+        leaders.remove(&len);
+
+        let starts: Vec<usize> = leaders.into_iter().collect();
+        starts
+            .iter()
+            .enumerate()
+            .map(|(i, &start)| {
+                let end = starts.get(i + 1).copied().unwrap_or(len);
+                let last = (start..end).rev().find_map(|l| self.statements[l].as_ref());
+                let successors = match last {
+                    Some(Statement::Goto(to)) => vec![*to],
+                    Some(Statement::Branch(_, to)) => vec![end, *to],
+                    Some(jump @ Statement::Jump(_)) => {
+                        let from = jump.jump_start().unwrap_or(0);
+                        let mut all: Vec<usize> =
+                            starts.iter().copied().filter(|s| *s >= from).collect();
+                        all.push(len);
+                        all
+                    }
+                    _ => vec![end],
+                };
+                Block {
+                    start,
+                    end,
+                    successors,
+                }
+            })
+            .collect()
+    }
+
+    /// The remaining statements with their line
+    fn lines(&self) -> impl Iterator<Item = (usize, &Statement)> {
+        self.statements
+            .iter()
+            .enumerate()
+            .filter_map(|(line, statement)| Some((line, statement.as_ref()?)))
+    }
+
+    /// The first line at or after `from` that still has a statement
+    fn next_live(&self, from: usize) -> usize {
+        (from..self.statements.len())
+            .find(|&line| self.statements[line].is_some())
+            .unwrap_or(self.statements.len())
+    }
+
+    /// The lines that can be executed after a line
+    fn successors(&self, line: usize) -> Vec<usize> {
+        match &self.statements[line] {
+            Some(Statement::Goto(to)) => vec![*to],
+            Some(Statement::Branch(_, to)) => vec![line + 1, *to],
+            Some(jump @ Statement::Jump(_)) => {
+                (jump.jump_start().unwrap_or(0)..=self.statements.len()).collect()
+            }
+            _ => vec![line + 1],
+        }
+    }
+
+    /// True if a line can be jumped to
+    fn targeted(&self, line: usize) -> bool {
+        self.lines().any(|(_, statement)| {
+            statement.target() == Some(line) || statement.jump_start().is_some_and(|s| s <= line)
+        })
+    }
+
+    /// The jumps into the lines `lo..=hi` from outside of them
+    /// # Returns
+    /// The targets of those jumps, None for a computed jump
+    fn entries(&self, lo: usize, hi: usize) -> impl Iterator<Item = Option<usize>> + '_ {
+        self.lines()
+            .filter(move |(line, _)| *line < lo || *line > hi)
+            .filter_map(move |(_, statement)| match statement.jump_start() {
+                Some(start) if start <= hi => Some(None),
+                _ => statement
+                    .target()
+                    .filter(|to| (lo..=hi).contains(to))
+                    .map(Some),
+            })
+    }
+
+    /// Compute the registers live after each line (the first register is live at the exit)
+    fn liveness(&self) -> Vec<u8> {
+        let len = self.statements.len();
+        let mut live_in = vec![0u8; len + 1];
+        live_in[len] = 1;
+        let live_out = |live_in: &[u8], line: usize| {
+            self.successors(line)
+                .into_iter()
+                .fold(0, |live, next| live | live_in[next])
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for line in (0..len).rev() {
+                let out = live_out(&live_in, line);
+                let live = match &self.statements[line] {
+                    Some(Statement::Assign(r, expr)) => (out & !(1 << r)) | expr.registers(),
+                    Some(Statement::Branch(expr, _)) | Some(Statement::Jump(expr)) => {
+                        out | expr.registers()
+                    }
+                    _ => out,
+                };
+                if live != live_in[line] {
+                    live_in[line] = live;
+                    changed = true;
+                }
+            }
+        }
+
+        (0..len).map(|line| live_out(&live_in, line)).collect()
+    }
+
+    /// Point all jumps to a line that still has a statement
+    fn normalize(&mut self) {
+        let targets: Vec<Option<usize>> = self
+            .statements
+            .iter()
+            .map(|s| s.as_ref()?.target().map(|to| self.next_live(to)))
+            .collect();
+        self.statements
+            .iter_mut()
+            .zip(targets)
+            .for_each(|(statement, target)| {
+                if let (Some(Statement::Goto(to) | Statement::Branch(_, to)), Some(target)) =
+                    (statement, target)
+                {
+                    *to = target;
+                }
+            });
+    }
+
+    /// Remove the assignments whose value is never read
+    /// # Returns
+    /// True if any assignment was removed
+    fn remove_dead(&mut self) -> bool {
+        let live = self.liveness();
+        let mut changed = false;
+        for (line, statement) in self.statements.iter_mut().enumerate() {
+            if let Some(Statement::Assign(r, _)) = statement {
+                if live[line] & (1 << *r) == 0 {
+                    *statement = None;
+                    changed = true;
+                }
+            }
+        }
+        if changed {
+            self.normalize();
+        }
+        changed
+    }
+
+    /// Inline an assignment into the next statement if it is the only one reading it
+    /// # Returns
+    /// True if an assignment was inlined
+    fn inline(&mut self) -> bool {
+        let live = self.liveness();
+        let len = self.statements.len();
+        for line in 0..len {
+            let Some(Statement::Assign(r, expr)) = &self.statements[line] else {
+                continue;
+            };
+            let next = self.next_live(line + 1);
+            if next >= len || self.targeted(next) {
+                continue;
+            }
+            let Some(statement) = &self.statements[next] else {
+                continue;
+            };
+            let redefined = matches!(statement, Statement::Assign(to, _) if to == r);
+            if statement.reads(*r) != 1 || (live[next] & (1 << r) != 0 && !redefined) {
+                continue;
+            }
+
+            let (r, expr) = (*r, expr.clone());
+            self.statements[next] = self.statements[next].take().map(|s| s.substitute(r, &expr));
+            self.statements[line] = None;
+            self.normalize();
+            return true;
+        }
+        false
+    }
+
+    /// Merge a conditional jump over a `goto` into a single conditional jump
+    /// # Returns
+    /// True if a jump was merged
+    fn merge(&mut self) -> bool {
+        let len = self.statements.len();
+        for line in 0..len {
+            let Some(Statement::Branch(condition, to)) = &self.statements[line] else {
+                continue;
+            };
+            let next = self.next_live(line + 1);
+            if next >= len || *to != self.next_live(next + 1) || self.targeted(next) {
+                continue;
+            }
+            let Some(Statement::Goto(goto)) = self.statements[next] else {
+                continue;
+            };
+
+            self.statements[line] = Some(Statement::Branch(condition.clone().negate(), goto));
+            self.statements[next] = None;
+            self.normalize();
+            return true;
+        }
+        false
+    }
+}
+
+impl Display for Decompiled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut writer = Writer {
+            code: self,
+            lines: Vec::new(),
+            labels: BTreeSet::new(),
+        };
+        writer.block(0, self.statements.len(), 0, None);
+
+        let width = self.statements.len().saturating_sub(1).to_string().len();
+        let mut labelled = BTreeSet::new();
+        for (line, depth, text) in writer.lines {
+            let indent = "    ".repeat(depth);
+            if let Some(line) = line.filter(|l| writer.labels.contains(l) && labelled.insert(*l)) {
+                writeln!(f, "{:>width$} | {indent}L{line}:", "")?;
+            }
+            let number = line.map_or_else(String::new, |line| line.to_string());
+            writeln!(f, "{number:>width$} | {indent}{text}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The kind of a recovered loop
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LoopKind {
+    /// A loop without condition, exited with `break`
+    Infinite,
+    /// A loop with its condition checked at the end
+    DoWhile,
+}
+
+/// The innermost loop containing the statements being written
+#[derive(Debug, Copy, Clone)]
+struct Scope {
+    head: usize,
+    exit: usize,
+    kind: LoopKind,
+}
+
+/// Recovers the structure of a decompiled program while writing it
+struct Writer<'a> {
+    code: &'a Decompiled,
+    /// The line number, depth and text of each written line
+    lines: Vec<(Option<usize>, usize, String)>,
+    /// The lines that are the target of a `goto`
+    labels: BTreeSet<usize>,
+}
+
+impl Writer<'_> {
+    fn push(&mut self, line: Option<usize>, depth: usize, text: String) {
+        self.lines.push((line, depth, text));
+    }
+
+    fn expr(&self, expr: &Expr) -> String {
+        let mut out = String::new();
+        expr.write(&self.code.names, None, &mut out);
+        out
+    }
+
+    /// Write the lines `start..end`
+    fn block(&mut self, start: usize, end: usize, depth: usize, scope: Option<Scope>) {
+        let mut line = self.code.next_live(start);
+        while line < end {
+            line = self.code.next_live(self.structure(line, end, depth, scope));
+        }
+    }
+
+    /// Write the statement at a line, along with the loop or conditional it starts
+    /// # Returns
+    /// The line after the written statements
+    fn structure(&mut self, line: usize, end: usize, depth: usize, scope: Option<Scope>) -> usize {
+        if let Some(latch) = self.latch(line, end) {
+            return self.write_loop(line, latch, depth);
+        }
+
+        match &self.code.statements[line] {
+            Some(Statement::Assign(r, expr)) => {
+                let text = self.assign(*r, expr);
+                self.push(Some(line), depth, text);
+            }
+            Some(Statement::Goto(to)) => {
+                let text = self.goto(*to, scope);
+                self.push(Some(line), depth, text);
+            }
+            Some(Statement::Jump(expr)) => {
+                let text = format!("goto {}", self.expr(expr));
+                self.push(Some(line), depth, text);
+            }
+            Some(Statement::Branch(condition, to)) => {
+                return self.write_branch(line, condition, *to, end, depth, scope);
+            }
+            None => {}
+        }
+        line + 1
+    }
+
+    /// Find the last jump back to a line that makes it the head of a loop (entered only there)
+    fn latch(&self, head: usize, end: usize) -> Option<usize> {
+        (head..end).rev().find(|&line| {
+            match &self.code.statements[line] {
+                Some(Statement::Goto(to) | Statement::Branch(_, to)) if *to == head => {}
+                _ => return false,
+            }
+            self.code
+                .entries(head, line)
+                .all(|target| target == Some(head))
+        })
+    }
+
+    fn write_loop(&mut self, head: usize, latch: usize, depth: usize) -> usize {
+        let condition = match &self.code.statements[latch] {
+            Some(Statement::Branch(condition, _)) => Some(self.expr(condition)),
+            _ => None,
+        };
+        let scope = Scope {
+            head,
+            exit: self.code.next_live(latch + 1),
+            kind: match condition {
+                Some(_) => LoopKind::DoWhile,
+                None => LoopKind::Infinite,
+            },
+        };
+
+        let open = if condition.is_some() {
+            "do {"
+        } else {
+            "loop {"
+        };
+        self.push(None, depth, open.to_owned());
+        self.block(head, latch, depth + 1, Some(scope));
+        let close = match condition {
+            Some(condition) => format!("}} while {condition}"),
+            None => "}".to_owned(),
+        };
+        self.push(Some(latch), depth, close);
+        latch + 1
+    }
+
+    fn write_branch(
+        &mut self,
+        line: usize,
+        condition: &Expr,
+        to: usize,
+        end: usize,
+        depth: usize,
+        scope: Option<Scope>,
+    ) -> usize {
+        let first = self.code.next_live(line + 1);
+        let structured = self.escape(to, scope).is_none()
+            && to > first
+            && to <= end
+            && self.code.entries(first, to - 1).next().is_none();
+        if !structured {
+            let text = format!("if {} {{ {} }}", self.expr(condition), self.goto(to, scope));
+            self.push(Some(line), depth, text);
+            return line + 1;
+        }
+
+        let text = format!("if {} {{", self.expr(&condition.clone().negate()));
+        self.push(Some(line), depth, text);
+
+        // The `then` part ends with a jump over an `else` part only entered from this branch
+        let last = (first..to)
+            .rev()
+            .find(|&l| self.code.statements[l].is_some());
+        if let Some(last) = last {
+            if let Some(Statement::Goto(after)) = self.code.statements[last] {
+                if after > to && after <= end && self.code.entries(to, after - 1).count() == 1 {
+                    self.block(first, last, depth + 1, scope);
+                    self.push(None, depth, "} else {".to_owned());
+                    self.block(to, after, depth + 1, scope);
+                    self.push(None, depth, "}".to_owned());
+                    return after;
+                }
+            }
+        }
+
+        self.block(first, to, depth + 1, scope);
+        self.push(None, depth, "}".to_owned());
+        to
+    }
+
+    fn assign(&self, r: usize, expr: &Expr) -> String {
+        let name = &self.code.names[r];
+        if let Expr::Binary(op, left, right) = expr {
+            if !op.is_comparison() {
+                let other = match (left.as_ref(), right.as_ref()) {
+                    (Expr::Register(l), other) if *l == r => Some(other),
+                    (other, Expr::Register(r2)) if *r2 == r => Some(other),
+                    _ => None,
+                };
+                if let Some(other) = other {
+                    return match (op, other) {
+                        (Op::Add, Expr::Value(v)) if *v < 0 => format!("{name} -= {}", -v),
+                        _ => format!("{name} {}= {}", op.symbol(), self.expr(other)),
+                    };
+                }
+            }
+        }
+        format!("{name} = {}", self.expr(expr))
+    }
+
+    /// A jump out of the current loop or the program
+    fn escape(&self, to: usize, scope: Option<Scope>) -> Option<&'static str> {
+        if to >= self.code.statements.len() {
+            return Some("halt");
+        }
+        let scope = scope?;
+        if to == scope.exit {
+            Some("break")
+        } else if to == scope.head && scope.kind == LoopKind::Infinite {
+            Some("continue")
+        } else {
+            None
+        }
+    }
+
+    fn goto(&mut self, to: usize, scope: Option<Scope>) -> String {
+        match self.escape(to, scope) {
+            Some(escape) => escape.to_owned(),
+            None => {
+                self.labels.insert(to);
+                format!("goto L{to}")
+            }
+        }
+    }
+}
+
+/// Lift an instruction into a statement
+/// # Arguments
+/// * `instruction` - The instruction to lift
+/// * `previous` - The instruction before it, to detect conditional jumps
+/// * `line` - The line of the instruction
+/// * `ip` - The register bound to the instruction pointer
+/// * `len` - The number of instructions of the program
+fn lift(
+    instruction: &Instruction,
+    previous: Option<&Instruction>,
+    line: usize,
+    ip: usize,
+    len: usize,
+) -> Result<Statement> {
+    let register = |r: Int| -> Result<Expr> {
+        ensure!((0..6).contains(&r), "{r} is out of bounds for a register");
+        Ok(if r as usize == ip {
+            Expr::Value(line as Int)
+        } else {
+            Expr::Register(r as usize)
+        })
+    };
+    let Instruction { code, a, b, c } = *instruction;
+    let binary = |op: Op, left: Expr, right: Expr| Expr::binary(op, left, right);
+    let expr = match code {
+        OpCode::AddR => binary(Op::Add, register(a)?, register(b)?),
+        OpCode::AddI => binary(Op::Add, register(a)?, Expr::Value(b)),
+        OpCode::MulR => binary(Op::Mul, register(a)?, register(b)?),
+        OpCode::MulI => binary(Op::Mul, register(a)?, Expr::Value(b)),
+        OpCode::BitAndR => binary(Op::And, register(a)?, register(b)?),
+        OpCode::BitAndI => binary(Op::And, register(a)?, Expr::Value(b)),
+        OpCode::BitOrR => binary(Op::Or, register(a)?, register(b)?),
+        OpCode::BitOrI => binary(Op::Or, register(a)?, Expr::Value(b)),
+        OpCode::SetR => register(a)?,
+        OpCode::SetI => Expr::Value(a),
+        OpCode::GreaterIR => binary(Op::Greater, Expr::Value(a), register(b)?),
+        OpCode::GreaterRI => binary(Op::Greater, register(a)?, Expr::Value(b)),
+        OpCode::GreaterRR => binary(Op::Greater, register(a)?, register(b)?),
+        OpCode::EqIR => binary(Op::Equal, Expr::Value(a), register(b)?),
+        OpCode::EqRI => binary(Op::Equal, register(a)?, Expr::Value(b)),
+        OpCode::EqRR => binary(Op::Equal, register(a)?, register(b)?),
+    };
+    register(c)?;
+    if c as usize != ip {
+        return Ok(Statement::Assign(c as usize, expr));
+    }
+
+    let target = |value: Int| usize::try_from(value).map_or(len, |value| value.min(len));
+    Ok(match expr {
+        Expr::Value(v) => Statement::Goto(target(v + 1)),
+        Expr::Binary(Op::Add, ref left, ref right) => {
+            let flag = match (left.as_ref(), right.as_ref()) {
+                (Expr::Register(r), Expr::Value(_)) | (Expr::Value(_), Expr::Register(r)) => {
+                    Some(*r)
+                }
+                _ => None,
+            };
+            let compared = previous.is_some_and(|p| {
+                Some(p.c) == flag.map(|f| f as Int)
+                    && matches!(
+                        p.code,
+                        OpCode::GreaterIR
+                            | OpCode::GreaterRI
+                            | OpCode::GreaterRR
+                            | OpCode::EqIR
+                            | OpCode::EqRI
+                            | OpCode::EqRR
+                    )
+            });
+            match flag {
+                Some(flag) if compared => Statement::Branch(
+                    Expr::binary(Op::NotEqual, Expr::Register(flag), Expr::Value(0)),
+                    target(line as Int + 2),
+                ),
+                _ => Statement::Jump(expr.offset(1)),
+            }
+        }
+        expr => Statement::Jump(expr.offset(1)),
+    })
+}
@@ -0,0 +1,296 @@
+//! Deduce which numeric code corresponds to which `OpCode`
+//!
+//! Each code starts with all the op codes as candidates, which are reduced by:
+//! - Observing samples of the registers before and after executing an instruction with that code
+//! - Adding constraints on the candidates of a code
+//!
+//! Solving then propagates the unique assignments until each code has a single op code.
+//! Each deduction made along the way is kept to explain the result.
+//! ```
+//! use advent_of_code_2018::instructions::inference::Inference;
+//! use advent_of_code_2018::instructions::OpCode;
+//!
+//! let mut inference = Inference::default();
+//! // Code 9 with the operands 2 1 2 wrote 2 in #2
+//! let matching = inference.observe(9, [2, 1, 2], &[3, 2, 1, 1], &[3, 2, 2, 1]);
+//! assert_eq!(matching, 3); // MulR, AddI and SetI
+//! inference.constrain(9, [OpCode::AddI, OpCode::AddR]);
+//! let codes = inference.solve().unwrap();
+//! assert_eq!(codes[&9], OpCode::AddI);
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::{Int, OpCode};
+
+/// The candidates of each code, refined by samples and constraints
+#[derive(Debug, Clone, Default)]
+pub struct Inference {
+    /// The op codes that are still possible for each code
+    candidates: BTreeMap<Int, BTreeSet<OpCode>>,
+    /// The number of samples observed so far
+    samples: usize,
+    /// Each deduction step, in order
+    explanation: Vec<Deduction>,
+}
+
+/// A step of the deduction of the op codes
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Deduction {
+    /// A sample ruled out some op codes for a code
+    Sample {
+        sample: usize,
+        code: Int,
+        removed: BTreeSet<OpCode>,
+    },
+    /// A constraint ruled out some op codes for a code
+    Constraint {
+        code: Int,
+        removed: BTreeSet<OpCode>,
+    },
+    /// The code had a single candidate left, which was removed from the other codes
+    OnlyCandidate {
+        code: Int,
+        op: OpCode,
+        removed_from: Vec<Int>,
+    },
+    /// The op code was only possible for this code, its other candidates were removed
+    OnlyCode {
+        code: Int,
+        op: OpCode,
+        removed: BTreeSet<OpCode>,
+    },
+}
+
+/// The reason the op codes could not be deduced
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum InferenceError {
+    /// No op code is possible for this code
+    Contradiction(Int),
+    /// Nothing more can be deduced, but these codes still have many candidates
+    Ambiguous(BTreeMap<Int, BTreeSet<OpCode>>),
+}
+
+impl Inference {
+    /// The op codes that are still possible for a code (None if it was never seen)
+    pub fn candidates(&self, code: Int) -> Option<&BTreeSet<OpCode>> {
+        self.candidates.get(&code)
+    }
+
+    /// Each deduction step made so far, in order
+    pub fn explanation(&self) -> &[Deduction] {
+        &self.explanation
+    }
+
+    /// Observe a sample of an instruction execution
+    /// # Arguments
+    /// * `code` - The numeric code of the instruction
+    /// * `operands` - The operands `a`, `b` and `c` of the instruction
+    /// * `before` - The registers before the instruction
+    /// * `after` - The registers after the instruction
+    ///
+    /// # Returns
+    /// The number of op codes that behave like the sample
+    pub fn observe(
+        &mut self,
+        code: Int,
+        operands: [Int; 3],
+        before: &[Int],
+        after: &[Int],
+    ) -> usize {
+        self.samples += 1;
+        let matching: BTreeSet<OpCode> = matching(operands, before, after).collect();
+        let count = matching.len();
+        let removed = self.restrict(code, &matching);
+        if !removed.is_empty() {
+            self.explanation.push(Deduction::Sample {
+                sample: self.samples,
+                code,
+                removed,
+            });
+        }
+        count
+    }
+
+    /// Restrict the candidates of a code to some op codes
+    pub fn constrain(&mut self, code: Int, allowed: impl IntoIterator<Item = OpCode>) {
+        let allowed: BTreeSet<OpCode> = allowed.into_iter().collect();
+        let removed = self.restrict(code, &allowed);
+        if !removed.is_empty() {
+            self.explanation
+                .push(Deduction::Constraint { code, removed });
+        }
+    }
+
+    /// Propagate the unique assignments until all the codes are deduced
+    /// # Returns
+    /// The op code of each code, or the reason they could not all be deduced
+    pub fn solve(&mut self) -> Result<BTreeMap<Int, OpCode>, InferenceError> {
+        let mut found: BTreeMap<Int, OpCode> = BTreeMap::new();
+        loop {
+            if let Some((code, _)) = self.candidates.iter().find(|(_, ops)| ops.is_empty()) {
+                return Err(InferenceError::Contradiction(*code));
+            }
+            if found.len() == self.candidates.len() {
+                return Ok(found);
+            }
+
+            let unknown = |code: &Int| !found.contains_key(code);
+            let only_candidate = self
+                .candidates
+                .iter()
+                .filter(|(code, _)| unknown(code))
+                .find_map(|(code, ops)| match ops.iter().next() {
+                    Some(op) if ops.len() == 1 => Some((*code, *op)),
+                    _ => None,
+                });
+            if let Some((code, op)) = only_candidate {
+                let removed_from = self
+                    .candidates
+                    .iter_mut()
+                    .filter_map(|(other, ops)| {
+                        (*other != code && ops.remove(&op)).then_some(*other)
+                    })
+                    .collect();
+                self.explanation.push(Deduction::OnlyCandidate {
+                    code,
+                    op,
+                    removed_from,
+                });
+                found.insert(code, op);
+                continue;
+            }
+
+            let only_code = OpCode::ALL.iter().find_map(|op| {
+                let mut codes = self
+                    .candidates
+                    .iter()
+                    .filter(|(_, ops)| ops.contains(op))
+                    .map(|(code, _)| *code);
+                match (codes.next(), codes.next()) {
+                    (Some(code), None) if unknown(&code) => Some((code, *op)),
+                    _ => None,
+                }
+            });
+            if let Some((code, op)) = only_code {
+                let removed = self.restrict(code, &BTreeSet::from([op]));
+                self.explanation
+                    .push(Deduction::OnlyCode { code, op, removed });
+                continue;
+            }
+
+            let ambiguous = self
+                .candidates
+                .iter()
+                .filter(|(code, _)| unknown(code))
+                .map(|(code, ops)| (*code, ops.clone()))
+                .collect();
+            return Err(InferenceError::Ambiguous(ambiguous));
+        }
+    }
+
+    /// Keep only the allowed candidates of a code
+    /// # Returns
+    /// The candidates that were removed
+    fn restrict(&mut self, code: Int, allowed: &BTreeSet<OpCode>) -> BTreeSet<OpCode> {
+        let candidates = self
+            .candidates
+            .entry(code)
+            .or_insert_with(|| OpCode::ALL.iter().copied().collect());
+        let removed = candidates.difference(allowed).copied().collect();
+        candidates.retain(|op| allowed.contains(op));
+        removed
+    }
+}
+
+/// The op codes that behave like a sample
+/// # Arguments
+/// * `operands` - The operands `a`, `b` and `c` of the instruction
+/// * `before` - The registers before the instruction
+/// * `after` - The registers after the instruction
+pub fn matching<'a>(
+    [a, b, c]: [Int; 3],
+    before: &'a [Int],
+    after: &'a [Int],
+) -> impl Iterator<Item = OpCode> + 'a {
+    OpCode::ALL.iter().copied().filter(move |code| {
+        let mut registers = before.to_vec();
+        match code.apply(&mut registers, a, b, c) {
+            Ok(()) => registers == after,
+            Err(_) => false,
+        }
+    })
+}
+
+/// Write a set of op codes as `{AddR, AddI}`
+fn write_ops(f: &mut Formatter<'_>, ops: &BTreeSet<OpCode>) -> FmtResult {
+    write!(f, "{{")?;
+    for (i, op) in ops.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{op:?}")?;
+    }
+    write!(f, "}}")
+}
+
+impl Display for Deduction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Sample {
+                sample,
+                code,
+                removed,
+            } => {
+                write!(f, "Sample {sample} rules out ")?;
+                write_ops(f, removed)?;
+                write!(f, " for code {code}")
+            }
+            Self::Constraint { code, removed } => {
+                write!(f, "A constraint rules out ")?;
+                write_ops(f, removed)?;
+                write!(f, " for code {code}")
+            }
+            Self::OnlyCandidate {
+                code,
+                op,
+                removed_from,
+            } => {
+                write!(f, "Code {code} can only be {op:?}")?;
+                if !removed_from.is_empty() {
+                    let codes: Vec<String> = removed_from.iter().map(Int::to_string).collect();
+                    match codes.as_slice() {
+                        [other] => write!(f, ", so code {other} is not {op:?}")?,
+                        _ => write!(f, ", so codes {} are not {op:?}", codes.join(", "))?,
+                    }
+                }
+                Ok(())
+            }
+            Self::OnlyCode { code, op, removed } => {
+                write!(f, "{op:?} is only possible for code {code}, so it is not ")?;
+                write_ops(f, removed)
+            }
+        }
+    }
+}
+
+impl Error for InferenceError {}
+
+impl Display for InferenceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Contradiction(code) => write!(f, "No op code is possible for the code {code}"),
+            Self::Ambiguous(codes) => {
+                write!(f, "The op codes are ambiguous:")?;
+                for (i, (code, ops)) in codes.iter().enumerate() {
+                    let separator = if i > 0 { "," } else { "" };
+                    write!(f, "{separator} {code} is one of ")?;
+                    write_ops(f, ops)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -4,8 +4,16 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
+use commons::machine::{Machine, Step};
 use commons::{bail, ensure, err, Report, Result, WrapErr};
 
+use decompiler::Decompiled;
+
+pub mod decompiler;
+pub mod inference;
+pub mod optimizer;
+pub mod trace;
+
 /// The type of an integer in the system
 pub type Int = i64;
 
@@ -47,6 +55,11 @@ impl Program {
         index(self.registers[self.ip_index]).wrap_err_with(|| self.error())
     }
 
+    /// Decompile this program into structured pseudocode
+    pub fn decompile(&self) -> Result<Decompiled> {
+        Decompiled::new(self)
+    }
+
     /// Reset this program to its starting state
     pub fn reset(&mut self) {
         self.line = 0;
@@ -64,6 +77,32 @@ impl Program {
     }
 }
 
+impl Machine for Program {
+    /// The registers, including the instruction pointer
+    type State = [Int; 6];
+    type Output = ();
+    type Error = Report;
+
+    fn step(&mut self) -> Result<Step<()>> {
+        Ok(match Program::step(self)? {
+            Some(()) => Step::Running,
+            None => Step::Halted,
+        })
+    }
+
+    fn reset(&mut self) {
+        Program::reset(self);
+    }
+
+    fn position(&self) -> usize {
+        usize::try_from(self.registers[self.ip_index]).unwrap_or(usize::MAX)
+    }
+
+    fn state(&self) -> [Int; 6] {
+        self.registers
+    }
+}
+
 /// An instruction with an OpCode and its inputs
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Instruction {
@@ -263,3 +302,6 @@ impl FromStr for OpCode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests;
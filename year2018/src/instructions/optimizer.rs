@@ -0,0 +1,358 @@
+//! Recognize the slow loops of the device programs and execute them in closed form
+//!
+//! The loops are matched whatever their address and the registers they use, the operands of
+//! commutative instructions can also be swapped. The recognized idioms are:
+//! - The divisor sum (a nested loop adding to `sum` each `b` for which `b * d == n`)
+//! ```text
+//! start:      seti 1 _ D      # d = 1
+//!             mulr B D T      # do { do { if b * d == n {
+//!             eqrr T N T
+//!             addr T ip ip
+//!             addi ip 1 ip
+//!             addr B S S      #     sum += b
+//!             addi D 1 D      # } d += 1
+//!             gtrr D N T
+//!             addr ip T ip
+//!             seti start _ ip # } while d <= n
+//!             addi B 1 B      # b += 1
+//!             gtrr B N T
+//!             addr T ip ip
+//!             seti start-1 _ ip # } while b <= n
+//! ```
+//! - The division (a loop looking for the first `q` for which `(q + 1) * k > c`)
+//! ```text
+//! start:      addi Q 1 T      # loop { if (q + 1) * k > c { break }
+//!             muli T k T
+//!             gtrr T C T
+//!             addr T ip ip
+//!             addi ip 1 ip
+//!             seti start+7 _ ip
+//!             addi Q 1 Q      #     q += 1
+//!             seti start-1 _ ip # }
+//! ```
+
+use std::io::Write;
+
+use commons::Result;
+
+use super::trace::Tracer;
+use super::{Instruction, Int, OpCode, Program};
+
+/// A loop that can be executed in closed form
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Idiom {
+    /// Add to `sum` the divisors of `number` that are greater or equal to `divisor`
+    DivisorSum {
+        divisor: usize,
+        quotient: usize,
+        number: usize,
+        sum: usize,
+        temp: usize,
+    },
+    /// Set `quotient` to the quotient of `dividend` by `divisor` if it is greater
+    Division {
+        quotient: usize,
+        dividend: usize,
+        divisor: Int,
+        temp: usize,
+    },
+}
+
+/// A loop found in a program
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Found {
+    /// The first line of the loop
+    pub start: usize,
+    /// The line executed after the loop
+    pub end: usize,
+    /// The loop itself
+    pub idiom: Idiom,
+}
+
+/// Executes a program, replacing the loops it recognized by their closed form
+#[derive(Debug, Clone)]
+pub struct Optimizer {
+    /// The loop starting at each line of the program
+    loops: Vec<Option<Found>>,
+}
+
+impl Optimizer {
+    /// Find the loops that can be optimized in a program
+    pub fn new(program: &Program) -> Self {
+        let loops = (0..program.instructions.len())
+            .map(|start| find(program, start))
+            .collect();
+        Self { loops }
+    }
+
+    /// The loops found in the program
+    pub fn found(&self) -> impl Iterator<Item = &Found> {
+        self.loops.iter().flatten()
+    }
+
+    /// Execute the program until it halts, returning the value of the first register (0)
+    pub fn run(&self, program: &mut Program) -> Result<Int> {
+        loop {
+            if self.step(program)?.is_none() {
+                break Ok(program.registers[0]);
+            }
+        }
+    }
+
+    /// Execute the next step of the program, or a whole loop at once if it starts there
+    pub fn step(&self, program: &mut Program) -> Result<Option<()>> {
+        let line = program.next_instruction()?;
+        if let Some(found) = self.loops.get(line).copied().flatten() {
+            if found.idiom.apply(&mut program.registers) {
+                program.line = line;
+                program.registers[program.ip_index] = found.end as Int;
+                return Ok(Some(()));
+            }
+        }
+        program.step()
+    }
+
+    /// Execute the next step of the program, recording it in the tracer if there is one
+    pub fn step_traced<W: Write>(
+        &self,
+        program: &mut Program,
+        tracer: Option<&mut Tracer<W>>,
+    ) -> Result<Option<()>> {
+        match tracer {
+            Some(tracer) => tracer.step(program, |program| self.step(program)),
+            None => self.step(program),
+        }
+    }
+}
+
+impl Idiom {
+    /// Apply the effects of the loop on the registers
+    /// # Returns
+    /// False if the registers do not allow a closed form (nothing was changed then)
+    fn apply(self, registers: &mut [Int; 6]) -> bool {
+        match self {
+            Self::DivisorSum {
+                divisor,
+                quotient,
+                number,
+                sum,
+                temp,
+            } => {
+                let (from, n) = (registers[divisor], registers[number]);
+                if from < 1 || from > n {
+                    return false;
+                }
+                registers[sum] += divisors(n).filter(|d| *d >= from).sum::<Int>();
+                registers[divisor] = n + 1;
+                registers[quotient] = n + 1;
+                registers[temp] = 1;
+            }
+            Self::Division {
+                quotient,
+                dividend,
+                divisor,
+                temp,
+            } => {
+                let result = registers[dividend].div_euclid(divisor);
+                registers[quotient] = registers[quotient].max(result);
+                registers[temp] = 1;
+            }
+        }
+        true
+    }
+}
+
+/// All the divisors of a positive number
+fn divisors(n: Int) -> impl Iterator<Item = Int> {
+    (1..)
+        .take_while(move |d| d * d <= n)
+        .filter(move |d| n % d == 0)
+        .flat_map(move |d| {
+            let pair = n / d;
+            std::iter::once(d).chain((pair != d).then_some(pair))
+        })
+}
+
+/// An operand in a pattern of instructions
+#[derive(Debug, Copy, Clone)]
+enum Arg {
+    /// Any register (other than the instruction pointer), bound to the variable at this index
+    Reg(usize),
+    /// A positive value, bound to the variable at this index
+    Const(usize),
+    /// This exact value
+    Value(Int),
+    /// The register bound to the instruction pointer
+    Ip,
+    /// The line of the start of the pattern plus this offset
+    Line(Int),
+    /// Anything
+    Any,
+}
+
+use Arg::{Any, Const, Ip, Line, Reg, Value};
+
+/// An instruction in a pattern
+type Step = (OpCode, Arg, Arg, Arg);
+
+/// The variables of the divisor sum
+const B: usize = 0;
+const D: usize = 1;
+const N: usize = 2;
+const S: usize = 3;
+const T: usize = 4;
+
+const DIVISOR_SUM: [Step; 14] = [
+    (OpCode::SetI, Value(1), Any, Reg(D)),
+    (OpCode::MulR, Reg(B), Reg(D), Reg(T)),
+    (OpCode::EqRR, Reg(T), Reg(N), Reg(T)),
+    (OpCode::AddR, Reg(T), Ip, Ip),
+    (OpCode::AddI, Ip, Value(1), Ip),
+    (OpCode::AddR, Reg(B), Reg(S), Reg(S)),
+    (OpCode::AddI, Reg(D), Value(1), Reg(D)),
+    (OpCode::GreaterRR, Reg(D), Reg(N), Reg(T)),
+    (OpCode::AddR, Ip, Reg(T), Ip),
+    (OpCode::SetI, Line(0), Any, Ip),
+    (OpCode::AddI, Reg(B), Value(1), Reg(B)),
+    (OpCode::GreaterRR, Reg(B), Reg(N), Reg(T)),
+    (OpCode::AddR, Reg(T), Ip, Ip),
+    (OpCode::SetI, Line(-1), Any, Ip),
+];
+
+/// The variables of the division
+const Q: usize = 0;
+const C: usize = 1;
+const K: usize = 2;
+
+const DIVISION: [Step; 8] = [
+    (OpCode::AddI, Reg(Q), Value(1), Reg(T)),
+    (OpCode::MulI, Reg(T), Const(K), Reg(T)),
+    (OpCode::GreaterRR, Reg(T), Reg(C), Reg(T)),
+    (OpCode::AddR, Reg(T), Ip, Ip),
+    (OpCode::AddI, Ip, Value(1), Ip),
+    (OpCode::SetI, Line(7), Any, Ip),
+    (OpCode::AddI, Reg(Q), Value(1), Reg(Q)),
+    (OpCode::SetI, Line(-1), Any, Ip),
+];
+
+/// Find the loop starting at a line of a program
+fn find(program: &Program, start: usize) -> Option<Found> {
+    let at = |pattern: &[Step]| {
+        let instructions = program.instructions.get(start..start + pattern.len())?;
+        let mut matcher = Matcher {
+            ip: program.ip_index as Int,
+            start: start as Int,
+            bound: [None; 5],
+            registers: [false; 5],
+        };
+        matcher
+            .matches(pattern, instructions)
+            .then(|| (start + pattern.len(), matcher.bound.map(|b| b.unwrap_or(0))))
+    };
+    let register = |value: Int| value as usize;
+
+    if let Some((end, bound)) = at(&DIVISOR_SUM) {
+        let idiom = Idiom::DivisorSum {
+            divisor: register(bound[B]),
+            quotient: register(bound[D]),
+            number: register(bound[N]),
+            sum: register(bound[S]),
+            temp: register(bound[T]),
+        };
+        return Some(Found { start, end, idiom });
+    }
+    if let Some((end, bound)) = at(&DIVISION) {
+        let idiom = Idiom::Division {
+            quotient: register(bound[Q]),
+            dividend: register(bound[C]),
+            divisor: bound[K],
+            temp: register(bound[T]),
+        };
+        return Some(Found { start, end, idiom });
+    }
+    None
+}
+
+/// Matches instructions against a pattern, binding its variables
+#[derive(Debug, Clone)]
+struct Matcher {
+    /// The register bound to the instruction pointer
+    ip: Int,
+    /// The line of the first instruction
+    start: Int,
+    /// The values bound to the variables of the pattern
+    bound: [Option<Int>; 5],
+    /// True for the variables bound to a register
+    registers: [bool; 5],
+}
+
+impl Matcher {
+    /// Match the instructions against the pattern, trying both orders for commutative operands
+    /// # Returns
+    /// True if they matched (the variables are then bound), false otherwise
+    fn matches(&mut self, pattern: &[Step], instructions: &[Instruction]) -> bool {
+        let (Some(&(code, a, b, c)), Some(instruction)) = (pattern.first(), instructions.first())
+        else {
+            return true;
+        };
+        if code != instruction.code {
+            return false;
+        }
+
+        let commutative = matches!(
+            code,
+            OpCode::AddR | OpCode::MulR | OpCode::BitAndR | OpCode::BitOrR | OpCode::EqRR
+        );
+        let orders = if commutative {
+            vec![(a, b), (b, a)]
+        } else {
+            vec![(a, b)]
+        };
+        orders.into_iter().any(|(a, b)| {
+            let mut next = self.clone();
+            let matched = next.bind(a, instruction.a)
+                && next.bind(b, instruction.b)
+                && next.bind(c, instruction.c)
+                && next.matches(&pattern[1..], &instructions[1..]);
+            if matched {
+                *self = next;
+            }
+            matched
+        })
+    }
+
+    /// Bind an argument of the pattern to the operand of an instruction
+    fn bind(&mut self, arg: Arg, operand: Int) -> bool {
+        match arg {
+            Reg(var) => {
+                if !(0..6).contains(&operand) || operand == self.ip {
+                    return false;
+                }
+                self.bind_variable(var, operand, true)
+            }
+            Const(var) => operand > 0 && self.bind_variable(var, operand, false),
+            Value(value) => operand == value,
+            Ip => operand == self.ip,
+            Line(offset) => operand == self.start + offset,
+            Any => true,
+        }
+    }
+
+    /// Bind a variable to a value, registers bound to different variables must be different
+    fn bind_variable(&mut self, var: usize, value: Int, register: bool) -> bool {
+        match self.bound[var] {
+            Some(bound) => bound == value,
+            None if register
+                && (0..self.bound.len())
+                    .any(|other| self.registers[other] && self.bound[other] == Some(value)) =>
+            {
+                false
+            }
+            None => {
+                self.bound[var] = Some(value);
+                self.registers[var] = register;
+                true
+            }
+        }
+    }
+}
@@ -0,0 +1,403 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use commons::machine::{Detection, Machine, Runner, Stop};
+
+use super::decompiler::Block;
+use super::inference::{Deduction, Inference, InferenceError};
+use super::optimizer::{Found, Idiom, Optimizer};
+use super::trace::{Breakpoint, Tracer};
+use super::*;
+
+const DAY_19: &str = include_str!("../day19/data.txt");
+const DAY_21: &str = include_str!("../day21/data.txt");
+
+#[test]
+fn decompile_conditionals() {
+    let program: Program = "#ip 5
+eqri 0 3 1
+addr 1 5 5
+seti 4 0 5
+seti 7 0 2
+seti 5 0 5
+seti 9 0 2
+addr 2 2 0"
+        .parse()
+        .unwrap();
+    let code = program.decompile().unwrap();
+    assert_eq!(
+        code.to_string(),
+        "1 | if a == 3 {
+3 |     c = 7
+  | } else {
+5 |     c = 9
+  | }
+6 | a = c + c
+"
+    );
+}
+
+#[test]
+fn decompile_day_19() {
+    let program: Program = DAY_19.parse().unwrap();
+    let mut code = program.decompile().unwrap();
+    code.rename(0, "sum");
+    code.rename(5, "n");
+    assert_eq!(
+        code.to_string(),
+        " 0 | goto L17
+   | L1:
+ 1 | b = 1
+   | do {
+ 2 |     d = 1
+   |     do {
+ 5 |         if b * d == n {
+ 7 |             sum += b
+   |         }
+ 8 |         d += 1
+10 |     } while d <= n
+12 |     b += 1
+14 | } while b <= n
+16 | halt
+   | L17:
+17 | n += 2
+20 | n = 19 * n * n * 11
+23 | c = (c + 1) * 22 + 6
+24 | n += c
+25 | goto 26 + sum
+26 | goto L1
+27 | c = 27
+28 | c *= 28
+29 | c += 29
+30 | c *= 30
+31 | c *= 14
+32 | c *= 32
+33 | n += c
+34 | sum = 0
+35 | goto L1
+"
+    );
+}
+
+#[test]
+fn decompile_day_21() {
+    let program: Program = DAY_21.parse().unwrap();
+    let code = program.decompile().unwrap();
+    assert_eq!(
+        code.to_string(),
+        " 0 | d = 123
+   | do {
+ 2 |     d = (d & 456) == 72
+ 3 | } while d == 0
+ 5 | d = 0
+   | do {
+ 6 |     c = d | 65536
+ 7 |     d = 10736359
+   |     loop {
+12 |         d = (((d + (c & 255)) & 16777215) * 65899) & 16777215
+14 |         if 256 > c { break }
+17 |         b = 0
+   |         loop {
+21 |             if (b + 1) * 256 > c { break }
+24 |             b += 1
+25 |         }
+26 |         c = b
+27 |     }
+29 | } while d != a
+"
+    );
+
+    let block = |start, end, successors: &[usize]| Block {
+        start,
+        end,
+        successors: successors.to_vec(),
+    };
+    assert_eq!(
+        code.blocks(),
+        vec![
+            block(0, 2, &[2]),
+            block(2, 5, &[5, 2]),
+            block(5, 6, &[6]),
+            block(6, 12, &[12]),
+            block(12, 17, &[17, 29]),
+            block(17, 21, &[21]),
+            block(21, 24, &[24, 26]),
+            block(24, 26, &[21]),
+            block(26, 29, &[12]),
+            block(29, 31, &[31, 6]),
+        ]
+    );
+}
+
+/// Run a program with and without the optimizer, checking that they end in the same state
+fn check_optimized(program: &Program) -> Optimizer {
+    let optimizer = Optimizer::new(program);
+    let mut plain = program.clone();
+    let mut optimized = program.clone();
+    plain.run().unwrap();
+    optimizer.run(&mut optimized).unwrap();
+    assert_eq!(plain.registers, optimized.registers);
+    optimizer
+}
+
+#[test]
+fn optimize_divisor_sum() {
+    // The divisor sum at another address, with other registers and swapped operands
+    let program: Program = "#ip 0
+seti 12 0 4
+seti 2 0 3
+addi 0 0 0
+seti 1 0 1
+mulr 1 3 5
+eqrr 4 5 5
+addr 0 5 0
+addi 0 1 0
+addr 2 3 2
+addi 1 1 1
+gtrr 1 4 5
+addr 5 0 0
+seti 3 0 0
+addi 3 1 3
+gtrr 3 4 5
+addr 0 5 0
+seti 2 0 0"
+        .parse()
+        .unwrap();
+    let optimizer = check_optimized(&program);
+    let idiom = Idiom::DivisorSum {
+        divisor: 3,
+        quotient: 1,
+        number: 4,
+        sum: 2,
+        temp: 5,
+    };
+    let found: Vec<Found> = optimizer.found().copied().collect();
+    assert_eq!(
+        found,
+        vec![Found {
+            start: 3,
+            end: 17,
+            idiom
+        }]
+    );
+}
+
+#[test]
+fn optimize_division() {
+    let program: Program = "#ip 1
+seti 1000 0 2
+seti 0 0 5
+addi 5 1 3
+muli 3 7 3
+gtrr 3 2 3
+addr 1 3 1
+addi 1 1 1
+seti 9 0 1
+addi 5 1 5
+seti 1 0 1
+setr 5 0 0"
+        .parse()
+        .unwrap();
+    let optimizer = check_optimized(&program);
+    let idiom = Idiom::Division {
+        quotient: 5,
+        dividend: 2,
+        divisor: 7,
+        temp: 3,
+    };
+    let found: Vec<Found> = optimizer.found().copied().collect();
+    assert_eq!(
+        found,
+        vec![Found {
+            start: 2,
+            end: 10,
+            idiom
+        }]
+    );
+
+    let mut program = program;
+    assert_eq!(optimizer.run(&mut program).unwrap(), 142);
+}
+
+#[test]
+fn optimize_days() {
+    let starts = |data: &str| -> Vec<usize> {
+        let program: Program = data.parse().unwrap();
+        let optimizer = Optimizer::new(&program);
+        optimizer.found().map(|found| found.start).collect()
+    };
+    assert_eq!(starts(DAY_19), vec![2]);
+    assert_eq!(starts(DAY_21), vec![18]);
+}
+
+#[test]
+fn trace_watch_and_breakpoints() {
+    let mut program: Program = DAY_21.parse().unwrap();
+    let optimizer = Optimizer::new(&program);
+    let mut tracer = Tracer::new(Vec::new())
+        .with_watch(3)
+        .with_breakpoint("line == 28".parse().unwrap())
+        .with_breakpoint("r2 >= 65536".parse().unwrap());
+    while tracer.hits().len() < 3 {
+        optimizer
+            .step_traced(&mut program, Some(&mut tracer))
+            .unwrap();
+    }
+
+    // The first exit value is in #3 when reaching the line 28
+    let hits: Vec<(usize, usize, Int)> = tracer
+        .hits()
+        .iter()
+        .map(|hit| (hit.breakpoint, hit.line, hit.registers[3]))
+        .collect();
+    assert_eq!(
+        hits,
+        vec![(1, 6, 0), (0, 28, 16_311_888), (1, 6, 16_311_888)]
+    );
+    assert_eq!(tracer.profile()[28], 1);
+    assert_eq!(tracer.profile()[18], 2);
+
+    let log = String::from_utf8(tracer.into_inner()).unwrap();
+    let lines: Vec<&str> = log.lines().take(4).collect();
+    assert_eq!(
+        lines,
+        vec![
+            "    0 | d: 0 -> 123",
+            "    1 | d: 123 -> 72",
+            "    2 | d: 72 -> 1",
+            "    5 | d: 1 -> 0",
+        ]
+    );
+}
+
+#[test]
+fn trace_watch_missing_register() {
+    // The programs of the day 16 only have 4 registers, `e` is never changed
+    let mut tracer = Tracer::new(Vec::new()).with_watch(0).with_watch(4);
+    tracer
+        .record(0, &"addi 0 1 0", &[0; 4], &[1, 0, 0, 0])
+        .unwrap();
+    tracer
+        .record(1, &"addi 1 1 1", &[1, 0, 0, 0], &[1, 1, 0, 0])
+        .unwrap();
+    let log = String::from_utf8(tracer.into_inner()).unwrap();
+    assert_eq!(log, "    0 | a: 0 -> 1\n");
+}
+
+#[test]
+fn breakpoints() {
+    let parse = |s: &str| s.parse::<Breakpoint>().map(|b| b.to_string());
+    assert_eq!(parse("r3==5").unwrap(), "d == 5");
+    assert_eq!(parse(" line != b ").unwrap(), "line != b");
+    assert_eq!(parse("a<=-3").unwrap(), "a <= -3");
+    assert!(parse("a = 3").is_err());
+    assert!(parse("g > 3").is_err());
+    assert!(parse("1 < 2").is_err());
+
+    let breakpoint: Breakpoint = "c > line".parse().unwrap();
+    assert!(breakpoint.check(3, &[0, 0, 4, 0, 0, 0]));
+    assert!(!breakpoint.check(4, &[0, 0, 4, 0, 0, 0]));
+}
+
+#[test]
+fn infer_op_codes() {
+    let mut inference = Inference::default();
+    inference.constrain(1, [OpCode::AddR, OpCode::AddI]);
+    inference.constrain(2, [OpCode::AddR, OpCode::MulR]);
+    inference.constrain(3, [OpCode::MulR, OpCode::SetI]);
+    // SetI, GreaterIR, GreaterRI and GreaterRR write 1 in #2
+    let matching = inference.observe(3, [1, 0, 2], &[0, 7, 0, 0], &[0, 7, 1, 0]);
+    assert_eq!(matching, 4);
+    assert_eq!(
+        inference.candidates(3),
+        Some(&BTreeSet::from([OpCode::SetI]))
+    );
+    assert_eq!(inference.candidates(4), None);
+
+    let found = inference.solve().unwrap();
+    assert_eq!(
+        found,
+        BTreeMap::from([(1, OpCode::AddI), (2, OpCode::AddR), (3, OpCode::SetI)])
+    );
+    let explanation: Vec<String> = inference.explanation()[3..]
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        explanation,
+        [
+            "Sample 1 rules out {MulR} for code 3",
+            "Code 3 can only be SetI",
+            "AddI is only possible for code 1, so it is not {AddR}",
+            "Code 1 can only be AddI",
+            "AddR is only possible for code 2, so it is not {MulR}",
+            "Code 2 can only be AddR",
+        ]
+    );
+    assert_eq!(
+        inference.explanation()[0],
+        Deduction::Constraint {
+            code: 1,
+            removed: OpCode::ALL[2..].iter().copied().collect()
+        }
+    );
+}
+
+#[test]
+fn infer_op_codes_failures() {
+    let mut inference = Inference::default();
+    inference.constrain(1, [OpCode::AddR, OpCode::AddI]);
+    inference.constrain(2, [OpCode::AddR, OpCode::AddI]);
+    inference.constrain(3, [OpCode::AddI, OpCode::SetI]);
+    // SetI is only possible for code 3, but nothing tells codes 1 and 2 apart
+    let error = inference.solve().unwrap_err();
+    assert_eq!(
+        error,
+        InferenceError::Ambiguous(BTreeMap::from([
+            (1, BTreeSet::from([OpCode::AddR, OpCode::AddI])),
+            (2, BTreeSet::from([OpCode::AddR, OpCode::AddI])),
+        ]))
+    );
+    assert_eq!(
+        error.to_string(),
+        "The op codes are ambiguous: 1 is one of {AddR, AddI}, 2 is one of {AddR, AddI}"
+    );
+
+    let mut inference = Inference::default();
+    // Only SetI writes 5 in #0
+    inference.observe(7, [5, 0, 0], &[0, 0, 0, 0], &[5, 0, 0, 0]);
+    inference.constrain(7, [OpCode::AddI]);
+    assert_eq!(inference.solve(), Err(InferenceError::Contradiction(7)));
+    assert_eq!(
+        InferenceError::Contradiction(7).to_string(),
+        "No op code is possible for the code 7"
+    );
+}
+
+#[test]
+fn program_as_machine() {
+    // Loops forever on the line 4 once b reaches 3
+    let mut program: Program = "#ip 5
+addi 1 1 1
+gtri 1 2 2
+addr 5 2 5
+seti -1 0 5
+seti 3 0 5"
+        .parse()
+        .unwrap();
+    let outcome = Runner::new().with_limit(20).run(&mut program).unwrap();
+    assert_eq!(outcome.stop, Stop::Limit);
+
+    program.reset();
+    let outcome = Runner::new()
+        .with_detection(Detection::State)
+        .run(&mut program)
+        .unwrap();
+    assert_eq!(outcome.stop, Stop::Looped(4));
+    assert_eq!(outcome.steps, 12);
+    assert_eq!(program.state(), [0, 3, 1, 0, 0, 4]);
+
+    // The position detection also stops the first loop of the program
+    program.reset();
+    let outcome = program.run_until_loop().unwrap();
+    assert_eq!((outcome.stop, outcome.last), (Stop::Looped(0), Some(3)));
+}
@@ -0,0 +1,415 @@
+//! Trace the execution of the device programs
+//!
+//! A `Tracer` records each executed instruction to build a profile of the hot lines, and can:
+//! - Log each instruction with the registers it changed (`--trace` on the command line)
+//! - Log the changes of some registers (`--watch=a,d`)
+//! - Stop on conditions on the registers or the line (`--break="d > 10"`, `--break="line == 28"`)
+//! - Print the profile of the hot lines once the program ends (`--profile`)
+//! ```
+//! use advent_of_code_2018::instructions::trace::Tracer;
+//! use advent_of_code_2018::instructions::Program;
+//!
+//! let mut program: Program = "#ip 1\naddi 0 2 0\naddi 0 3 0".parse().unwrap();
+//! let mut tracer = Tracer::new(Vec::new())
+//!     .with_trace()
+//!     .with_breakpoint("a > 4".parse().unwrap());
+//! while tracer.step(&mut program, Program::step).unwrap().is_some() {}
+//!
+//! assert_eq!(tracer.profile(), &[1, 1]);
+//! assert_eq!(tracer.hits().len(), 1);
+//! assert_eq!(
+//!     String::from_utf8(tracer.into_inner()).unwrap(),
+//!     "    0 | AddI 0 2 0 | a: 0 -> 2\n    \
+//!          1 | AddI 0 3 0 | a: 2 -> 5\n\
+//!      Breakpoint `a > 4` at line 1: [5, 1, 0, 0, 0, 0]\n"
+//! );
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult, Write as _};
+use std::io::{BufRead, Stdout, Write};
+use std::str::FromStr;
+
+use commons::arguments::Options;
+use commons::{bail, err, Report, Result, WrapErr};
+
+use super::decompiler::NAMES;
+use super::{Int, Program};
+
+/// The number of lines shown in the profile
+const PROFILE_LINES: usize = 10;
+
+/// Records the execution of a program
+#[derive(Debug)]
+pub struct Tracer<W> {
+    /// Where the trace, the watched changes and the breakpoints are written
+    output: W,
+    /// True to log every executed instruction
+    trace: bool,
+    /// The registers whose changes are logged
+    watch: Vec<usize>,
+    /// The conditions to stop on, with their value at the previous step
+    breakpoints: Vec<(Breakpoint, bool)>,
+    /// True to wait for the standard input to continue after a breakpoint
+    pause: bool,
+    /// The number of executions of each line
+    profile: Vec<u64>,
+    /// The breakpoints that were hit
+    hits: Vec<Hit>,
+}
+
+/// A breakpoint that was hit
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hit {
+    /// The index of the breakpoint
+    pub breakpoint: usize,
+    /// The line that was executed when it was hit
+    pub line: usize,
+    /// The registers after executing that line
+    pub registers: Vec<Int>,
+}
+
+impl Tracer<Stdout> {
+    /// The names of the options configuring a tracer
+    pub const OPTIONS: &'static [&'static str] = &["trace", "watch", "break", "profile"];
+
+    /// A tracer configured from some options, None if none of its options were given:
+    /// `--trace`, `--watch=<REGISTERS>`, `--break=<CONDITION>` and `--profile`
+    pub fn from_options(options: &Options) -> Result<Option<Self>> {
+        let mut tracer = Self::new(std::io::stdout()).with_pause();
+        let mut enabled = options.has("profile");
+        if options.has("trace") {
+            tracer = tracer.with_trace();
+            enabled = true;
+        }
+        for registers in options.values("watch") {
+            for register in registers.split(',') {
+                tracer = tracer.with_watch(register_index(register.trim())?);
+                enabled = true;
+            }
+        }
+        for breakpoint in options.values("break") {
+            tracer = tracer.with_breakpoint(breakpoint.parse()?);
+            enabled = true;
+        }
+
+        Ok(enabled.then_some(tracer))
+    }
+}
+
+impl<W: Write> Tracer<W> {
+    /// A tracer writing to this output, that only builds the profile by default
+    pub fn new(output: W) -> Self {
+        Self {
+            output,
+            trace: false,
+            watch: Vec::new(),
+            breakpoints: Vec::new(),
+            pause: false,
+            profile: Vec::new(),
+            hits: Vec::new(),
+        }
+    }
+
+    /// Log each instruction and the registers it changed
+    pub fn with_trace(mut self) -> Self {
+        self.trace = true;
+        self
+    }
+
+    /// Log the changes of a register
+    pub fn with_watch(mut self, register: usize) -> Self {
+        self.watch.push(register);
+        self
+    }
+
+    /// Log the registers each time this breakpoint becomes true
+    pub fn with_breakpoint(mut self, breakpoint: Breakpoint) -> Self {
+        self.breakpoints.push((breakpoint, false));
+        self
+    }
+
+    /// Wait for a line on the standard input after a breakpoint is hit
+    pub fn with_pause(mut self) -> Self {
+        self.pause = true;
+        self
+    }
+
+    /// The number of executions of each line
+    pub fn profile(&self) -> &[u64] {
+        &self.profile
+    }
+
+    /// The breakpoints that were hit
+    pub fn hits(&self) -> &[Hit] {
+        &self.hits
+    }
+
+    /// Get back the output
+    pub fn into_inner(self) -> W {
+        self.output
+    }
+
+    /// Execute a step of a program, recording it
+    /// # Arguments
+    /// * `program` - The program to execute
+    /// * `step` - The way to execute the step (`Program::step` or the step of an optimizer)
+    pub fn step<S>(&mut self, program: &mut Program, step: S) -> Result<Option<()>>
+    where
+        S: FnOnce(&mut Program) -> Result<Option<()>>,
+    {
+        let before = program.registers;
+        let result = step(program)?;
+        if result.is_some() {
+            // Like in the puzzle, show the registers before the instruction pointer is incremented
+            let mut after = program.registers;
+            after[program.ip_index] -= 1;
+            let instruction = &program.instructions[program.line];
+            self.record(program.line, instruction, &before, &after)?;
+        }
+        Ok(result)
+    }
+
+    /// Record the execution of an instruction
+    /// # Arguments
+    /// * `line` - The line of the instruction
+    /// * `instruction` - The instruction, to log it
+    /// * `before` - The registers before executing it
+    /// * `after` - The registers after executing it
+    pub fn record(
+        &mut self,
+        line: usize,
+        instruction: &dyn Display,
+        before: &[Int],
+        after: &[Int],
+    ) -> Result<()> {
+        if self.profile.len() <= line {
+            self.profile.resize(line + 1, 0);
+        }
+        self.profile[line] += 1;
+
+        let changes = before.iter().zip(after).enumerate();
+        let mut changes = changes.filter(|(_, (before, after))| before != after);
+        if self.trace {
+            let mut log = format!("{line:>5} | {instruction} |");
+            for (register, (before, after)) in changes {
+                write!(log, " {}: {before} -> {after}", NAMES[register])?;
+            }
+            self.write(&log)?;
+        } else if changes.any(|(register, _)| self.watch.contains(&register)) {
+            let mut log = format!("{line:>5} |");
+            // The watched registers missing from the program (like `e` for 4 registers) never change
+            for register in self.watch.iter().copied() {
+                match (before.get(register), after.get(register)) {
+                    (Some(before), Some(after)) if before != after => {
+                        write!(log, " {}: {before} -> {after}", NAMES[register])?;
+                    }
+                    _ => {}
+                }
+            }
+            self.write(&log)?;
+        }
+
+        for index in 0..self.breakpoints.len() {
+            let (breakpoint, was_true) = &mut self.breakpoints[index];
+            let is_true = breakpoint.check(line, after);
+            let hit = is_true && !*was_true;
+            *was_true = is_true;
+            if hit {
+                let log = format!("Breakpoint `{breakpoint}` at line {line}: {after:?}");
+                self.write(&log)?;
+                self.hits.push(Hit {
+                    breakpoint: index,
+                    line,
+                    registers: after.to_vec(),
+                });
+                if self.pause {
+                    self.write("Press enter to continue")?;
+                    std::io::stdin()
+                        .lock()
+                        .read_line(&mut String::new())
+                        .wrap_err("Could not read the standard input")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the most executed lines, hottest first
+    /// # Arguments
+    /// * `instructions` - The instructions of the program, to show them with their line
+    pub fn write_profile<I: Display>(&mut self, instructions: &[I]) -> Result<()> {
+        let total: u64 = self.profile.iter().sum();
+        let mut lines: Vec<(usize, u64)> = self.profile.iter().copied().enumerate().collect();
+        lines.sort_by_key(|(line, count)| (std::cmp::Reverse(*count), *line));
+
+        let mut report = format!("Profile of {total} executed instructions:");
+        for (line, count) in lines.into_iter().take(PROFILE_LINES) {
+            let percent = 100.0 * count as f64 / total.max(1) as f64;
+            let instruction = instructions
+                .get(line)
+                .map_or_else(String::new, |i| i.to_string());
+            write!(
+                report,
+                "\n{line:>5} | {instruction:<20} | {count:>12} | {percent:>5.1}%"
+            )?;
+        }
+        self.write(&report)
+    }
+
+    fn write(&mut self, line: &str) -> Result<()> {
+        writeln!(self.output, "{line}").wrap_err("Could not write the trace")
+    }
+}
+
+/// A condition on the registers or the executed line: `<OPERAND> <OP> <OPERAND>`
+/// - The operands are a register (`a` to `f` or `r0` to `r5`), `line` or a number
+/// - The operator is one of `==`, `!=`, `<`, `<=`, `>`, `>=`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Breakpoint {
+    left: Operand,
+    comparison: Comparison,
+    right: Operand,
+}
+
+impl Breakpoint {
+    /// True if this condition holds after executing a line
+    pub fn check(&self, line: usize, registers: &[Int]) -> bool {
+        match (
+            self.left.get(line, registers),
+            self.right.get(line, registers),
+        ) {
+            (Some(left), Some(right)) => self.comparison.check(left, right),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Operand {
+    Register(usize),
+    Line,
+    Value(Int),
+}
+
+impl Operand {
+    fn get(self, line: usize, registers: &[Int]) -> Option<Int> {
+        match self {
+            Self::Register(register) => registers.get(register).copied(),
+            Self::Line => Int::try_from(line).ok(),
+            Self::Value(value) => Some(value),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    LessOrEqual,
+    GreaterOrEqual,
+    Less,
+    Greater,
+}
+
+impl Comparison {
+    /// All the comparisons, the longest symbols first to parse them
+    const ALL: [Self; 6] = [
+        Self::Equal,
+        Self::NotEqual,
+        Self::LessOrEqual,
+        Self::GreaterOrEqual,
+        Self::Less,
+        Self::Greater,
+    ];
+
+    fn check(self, left: Int, right: Int) -> bool {
+        match self {
+            Self::Equal => left == right,
+            Self::NotEqual => left != right,
+            Self::LessOrEqual => left <= right,
+            Self::GreaterOrEqual => left >= right,
+            Self::Less => left < right,
+            Self::Greater => left > right,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Equal => "==",
+            Self::NotEqual => "!=",
+            Self::LessOrEqual => "<=",
+            Self::GreaterOrEqual => ">=",
+            Self::Less => "<",
+            Self::Greater => ">",
+        }
+    }
+}
+
+/// The index of a register from its name (`a` to `f` or `r0` to `r5`)
+fn register_index(name: &str) -> Result<usize> {
+    NAMES
+        .iter()
+        .position(|n| *n == name)
+        .or_else(|| name.strip_prefix('r')?.parse().ok().filter(|r| *r < 6))
+        .wrap_err_with(|| format!("Unknown register {name} (expected a to f or r0 to r5)"))
+}
+
+impl FromStr for Operand {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s == "line" {
+            Ok(Self::Line)
+        } else if let Ok(value) = s.parse() {
+            Ok(Self::Value(value))
+        } else {
+            register_index(s).map(Self::Register)
+        }
+    }
+}
+
+impl FromStr for Breakpoint {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (left, comparison, right) = Comparison::ALL
+            .iter()
+            .find_map(|c| {
+                let (left, right) = s.split_once(c.symbol())?;
+                Some((left, *c, right))
+            })
+            .ok_or_else(|| err!("Expected a condition like 'a == 10', got: {s}"))?;
+        let breakpoint = Self {
+            left: left.parse()?,
+            comparison,
+            right: right.parse()?,
+        };
+        if let (Operand::Value(_), Operand::Value(_)) = (breakpoint.left, breakpoint.right) {
+            bail!("The condition {s} does not depend on the program");
+        }
+        Ok(breakpoint)
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Register(register) => write!(f, "{}", NAMES[*register]),
+            Self::Line => write!(f, "line"),
+            Self::Value(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} {} {}",
+            self.left,
+            self.comparison.symbol(),
+            self.right
+        )
+    }
+}
@@ -0,0 +1,92 @@
+//! Compare the execution engines of the IntCode processor on some of the heaviest days.
+//!
+//! Run with `cargo bench -p advent_of_code_2019`
+use std::time::{Duration, Instant};
+
+use advent_of_code_2019::int_code::{Engine, IntCodeInput, Processor, ReadError, Status};
+
+/// The number of times each workload is run, the fastest run is kept
+const ITERATIONS: usize = 10;
+
+fn main() {
+    bench(
+        "Day 9: Sensor Boost",
+        include_str!("../src/day09/data.txt"),
+        sensor_boost,
+    );
+    bench(
+        "Day 19: Tractor Beam",
+        include_str!("../src/day19/data.txt"),
+        tractor_beam,
+    );
+    bench(
+        "Day 23: Category Six",
+        include_str!("../src/day23/data.txt"),
+        category_six,
+    );
+}
+
+/// Run a workload with each engine, displaying the fastest time of each one
+fn bench(title: &str, data: &str, workload: fn(&[i64], Engine) -> i64) {
+    let memory = data.parse::<IntCodeInput>().expect("valid input").data;
+    println!("{title}");
+    let mut results = Vec::with_capacity(2);
+    for engine in [Engine::Interpreted, Engine::PreDecoded] {
+        let mut fastest = Duration::MAX;
+        let mut result = 0;
+        for _ in 0..ITERATIONS {
+            let start = Instant::now();
+            result = workload(&memory, engine);
+            fastest = fastest.min(start.elapsed());
+        }
+        println!("  {engine:?}: {result} in {}μs", fastest.as_micros());
+        results.push(result);
+    }
+    assert!(
+        results.windows(2).all(|w| w[0] == w[1]),
+        "The engines disagree on {title}"
+    );
+}
+
+/// Run the BOOST program, returning the coordinates it outputs
+fn sensor_boost(memory: &[i64], engine: Engine) -> i64 {
+    let mut boost = Processor::with_initial_inputs(memory, &[2]).with_engine(engine);
+    boost
+        .read_next()
+        .expect("BOOST should output the coordinates")
+}
+
+/// Count the positions pulled by the beam in the 50x50 area
+fn tractor_beam(memory: &[i64], engine: Engine) -> i64 {
+    let mut pulled = 0;
+    for y in 0..50 {
+        for x in 0..50 {
+            let mut drone = Processor::with_initial_inputs(memory, &[x, y]).with_engine(engine);
+            pulled += drone.read_next().expect("the drone should output a state");
+        }
+    }
+    pulled
+}
+
+/// Run the network of 50 computers until the first packet to 255, returning its Y value
+fn category_six(memory: &[i64], engine: Engine) -> i64 {
+    let mut computers: Vec<Processor> = (0..50)
+        .map(|port| Processor::with_initial_inputs(memory, &[port]).with_engine(engine))
+        .collect();
+
+    loop {
+        for n in 0..computers.len() {
+            let mut packet = [0; 3];
+            match computers[n].read_next_array(&mut packet, 3) {
+                (3, _) if packet[0] == 255 => return packet[2],
+                (3, _) => {
+                    let recipient = &mut computers[packet[0] as usize];
+                    recipient.write_int(packet[1]);
+                    recipient.write_int(packet[2]);
+                }
+                (_, Some(ReadError::Blocked(Status::RequireInput))) => computers[n].write_int(-1),
+                (_, status) => panic!("Computer {n} stopped with {status:?}"),
+            }
+        }
+    }
+}
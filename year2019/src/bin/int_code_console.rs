@@ -0,0 +1,81 @@
+//! Run any ascii IntCode program interactively in the console
+use std::path::PathBuf;
+
+use advent_of_code_2019::int_code::console::Console;
+use advent_of_code_2019::int_code::io::Ascii;
+use advent_of_code_2019::int_code::{IntCodeInput, Processor};
+use commons::err;
+use commons::error::Result;
+
+fn main() -> Result<()> {
+    let args = parse_arguments().unwrap_or_else(|reason| {
+        println!("{reason}\n");
+        print_help_and_exit()
+    });
+
+    let program: IntCodeInput = commons::problem::load(args.program)?.parse()?;
+    let mut console = Console::stdio();
+    if let Some(script) = args.script {
+        console = console.with_script_file(&script)?;
+    }
+
+    let mut processor = Processor::new(&program.data);
+    let status = processor.run_with_io(&mut Ascii::new(&mut console))?;
+    println!("\nThe program stopped: {status:?}");
+
+    if let Some(transcript) = args.transcript {
+        console.save_transcript(&transcript)?;
+        println!("Transcript saved to {transcript:?}");
+    }
+    Ok(())
+}
+
+/// The arguments of the console
+struct Arguments {
+    /// The IntCode program to run
+    program: PathBuf,
+    /// The script to replay before reading the console input
+    script: Option<PathBuf>,
+    /// The file where to save the transcript once the program stops
+    transcript: Option<PathBuf>,
+}
+
+fn parse_arguments() -> Result<Arguments> {
+    let mut args = std::env::args().skip(1);
+    let mut program: Option<PathBuf> = None;
+    let mut script: Option<PathBuf> = None;
+    let mut transcript: Option<PathBuf> = None;
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .map(PathBuf::from)
+                .ok_or_else(|| err!("missing value for argument {arg}"))
+        };
+        match arg.as_str() {
+            "-s" | "--script" => script = Some(value()?),
+            "-t" | "--transcript" => transcript = Some(value()?),
+            "-h" | "--help" => print_help_and_exit(),
+            _ if program.is_none() && !arg.starts_with('-') => program = Some(PathBuf::from(arg)),
+            _ => return Err(err!("unknown argument: {arg}")),
+        }
+    }
+
+    Ok(Arguments {
+        program: program.ok_or_else(|| err!("'program' is required"))?,
+        script,
+        transcript,
+    })
+}
+
+fn print_help_and_exit() -> ! {
+    println!(
+        "Run an ascii IntCode program interactively
+  Usage: int_code_console <PROGRAM> [--script <FILE>] [--transcript <FILE>]
+  Options:
+  -s, --script <FILE>      Lines to send to the program before reading the console input
+  -t, --transcript <FILE>  Save everything that happened in the console once the program stops
+  -h, --help               Print help
+  Type :help in the console for the console commands"
+    );
+    std::process::exit(1)
+}
@@ -1,11 +1,15 @@
 use std::collections::HashMap;
 
+use commons::arguments::Options;
+use commons::grid::image::Rgb;
 use commons::grid::{Direction, Point};
+use commons::svg::{Style, Svg};
 use commons::{Result, WrapErr};
 
 pub const TITLE: &str = "Day 3: Crossed Wires";
+pub const OPTIONS: &[&str] = &["svg"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let crossed = parse(&raw);
     let closest = closest(&crossed[..]).wrap_err("Could not find closest !")?;
     let (shortest, length) = shortest(&crossed[..]).wrap_err("Could not find shortest !")?;
@@ -14,15 +18,18 @@ pub fn run(raw: String) -> Result<()> {
     println!("Closest cross to origin : {closest} with distance {distance}");
     println!("Shortest cross to origin : {shortest} with length {length}");
 
+    if let Some(path) = options.values("svg").first() {
+        drawing(&raw, &crossed).save(path)?;
+        println!("The wires were drawn in {path}");
+    }
+
     Ok(())
 }
 
-fn parse(s: &str) -> Vec<(Point, i64)> {
-    fn parse_cable(line: &str) -> Option<HashMap<Point, i64>> {
-        let mut current = Point::default();
-        let mut from_origin = 0;
-        let mut acc: HashMap<Point, i64> = HashMap::new();
-        for movement in line.split(',') {
+/// The moves of a cable, as their direction and length
+fn parse_moves(line: &str) -> Option<Vec<(Direction, i64)>> {
+    line.split(',')
+        .map(|movement| {
             let mut chars = movement.trim().chars();
             let direction = match chars.next() {
                 Some('R') => Direction::East,
@@ -31,7 +38,18 @@ fn parse(s: &str) -> Vec<(Point, i64)> {
                 Some('L') => Direction::West,
                 _ => return None,
             };
-            let end = chars.collect::<String>().parse::<i64>().ok()?;
+            let length = chars.as_str().parse::<i64>().ok()?;
+            Some((direction, length))
+        })
+        .collect()
+}
+
+fn parse(s: &str) -> Vec<(Point, i64)> {
+    fn parse_cable(line: &str) -> Option<HashMap<Point, i64>> {
+        let mut current = Point::default();
+        let mut from_origin = 0;
+        let mut acc: HashMap<Point, i64> = HashMap::new();
+        for (direction, end) in parse_moves(line)? {
             for _ in 0..end {
                 current = current.moved(direction);
                 from_origin += 1;
@@ -99,5 +117,41 @@ fn shortest(crossed: &[(Point, i64)]) -> Option<(Point, i64)> {
     min.map(move |x| (x, distance))
 }
 
+/// Draw the cables from the origin, with their crossings
+fn drawing(s: &str, crossed: &[(Point, i64)]) -> Svg {
+    const COLORS: [Rgb; 2] = [[0xd0, 0x30, 0x30], [0x30, 0x60, 0xd0]];
+    let position = |point: Point| (point.x as f64, point.y as f64);
+
+    let mut svg = Svg::new().with_background([0xff; 3]);
+    for (moves, color) in s.lines().filter_map(parse_moves).zip(COLORS.iter().cycle()) {
+        let corners = moves
+            .iter()
+            .scan(Point::default(), |current, (direction, length)| {
+                *current += direction.offset().multiply(*length);
+                Some(position(*current))
+            });
+        let corners = std::iter::once((0.0, 0.0)).chain(corners);
+        svg.polyline(corners, &Style::stroke(*color).with_opacity(0.8));
+    }
+
+    let cross = Style::fill([0x20, 0x20, 0x20]).with_width(2.0);
+    crossed
+        .iter()
+        .for_each(|(point, _)| svg.dot(position(*point), &cross));
+    let marked = [
+        ("origin", Some(Point::default())),
+        ("closest", closest(crossed)),
+        ("shortest", shortest(crossed).map(|(point, _)| point)),
+    ];
+    let mark = Style::fill([0x20, 0xa0, 0x20]).with_width(4.0);
+    for (name, point) in marked {
+        if let Some(point) = point {
+            svg.dot(position(point), &mark);
+            svg.label(position(point), name, &mark);
+        }
+    }
+    svg
+}
+
 #[cfg(test)]
 mod tests;
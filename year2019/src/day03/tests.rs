@@ -40,3 +40,14 @@ fn solve_test() {
     assert_eq!(529, closest.manhattan_distance());
     assert_eq!(20_386, length);
 }
+
+#[test]
+fn drawing_test() {
+    let mut svg = Vec::new();
+    drawing(A, &parse(A)).write(&mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert_eq!(svg.matches("<polyline").count(), 2);
+    // The crossings, then a dot and a label for the origin, the closest and the shortest crossings
+    assert_eq!(svg.matches("<circle").count(), 4 + 3);
+    assert_eq!(svg.matches("<text").count(), 3);
+}
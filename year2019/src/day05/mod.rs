@@ -37,7 +37,7 @@ fn run_program(program: &[i64], input: i64) -> Option<i64> {
         },
     );
 
-    if let Status::Halted = status {
+    if let Ok(Status::Halted) = status {
         if output_count == 0 {
             None
         } else {
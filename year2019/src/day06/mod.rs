@@ -1,19 +1,26 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::{Result, WrapErr};
 
 pub const TITLE: &str = "Day 6: Universal Orbit Map";
+pub const OPTIONS: &[&str] = &["dot"];
 type PlanetName<'a> = &'a str;
 const COM: &str = "COM";
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let orbits = parse(&raw);
-    let from_origin = depth_first_search(COM, orbits).wrap_err("DFS error !")?;
+    let from_origin = depth_first_search(COM, &orbits).wrap_err("DFS error !")?;
     let first = check_sum(&from_origin);
     let second = shortest_path(&from_origin, "YOU", "SAN").wrap_err("YOU or SAN not found")?;
 
     println!("The orbit check sum is {first}");
     println!("The shortest path from YOU to SAN is {second}");
+    if let Some(path) = options.values("dot").first() {
+        orbit_graph(&orbits, &from_origin, "YOU", "SAN").save(path)?;
+        println!("The orbit map was described in {path}");
+    }
     Ok(())
 }
 
@@ -65,7 +72,7 @@ fn shortest_path(
 /// Starts from the given center and builds a map of Planet -> Path to planet from center via DFS
 fn depth_first_search<'a>(
     center: &'a str,
-    orbits: HashMap<PlanetName<'a>, Vec<PlanetName<'a>>>,
+    orbits: &HashMap<PlanetName<'a>, Vec<PlanetName<'a>>>,
 ) -> Option<HashMap<PlanetName<'a>, Vec<PlanetName<'a>>>> {
     let center: PlanetName = center;
     let mut origins: HashMap<PlanetName, Vec<PlanetName>> = HashMap::new();
@@ -89,5 +96,44 @@ fn depth_first_search<'a>(
     Some(origins)
 }
 
+/// The orbit map as a graph from each center to the planets orbiting it, with the orbital
+/// transfers from start to end highlighted (if both are in the map)
+fn orbit_graph(
+    orbits: &HashMap<PlanetName, Vec<PlanetName>>,
+    from_origin: &HashMap<PlanetName, Vec<PlanetName>>,
+    start: &str,
+    end: &str,
+) -> Dot {
+    // The planets of the transfer: the ones orbited by only one of start and end, plus the last
+    // one orbited by both
+    let mut transfer = HashSet::new();
+    if let (Some(to_start), Some(to_end)) = (from_origin.get(&start), from_origin.get(&end)) {
+        let common_part = to_start.iter().zip(to_end).filter(|x| x.0 == x.1).count();
+        transfer.extend(&to_start[common_part.saturating_sub(1)..]);
+        transfer.extend(&to_end[common_part..]);
+        transfer.extend([start, end]);
+    }
+
+    let mut dot = Dot::directed("orbits").with_attribute("rankdir", "LR");
+    dot.node(COM, &[("shape", "doublecircle")]);
+    for planet in [start, end] {
+        if from_origin.contains_key(&planet) {
+            dot.node(planet, &[("style", "filled"), ("fillcolor", "gold")]);
+        }
+    }
+    let mut centers: Vec<_> = orbits.iter().collect();
+    centers.sort_unstable();
+    for (center, planets) in centers {
+        for planet in planets {
+            if transfer.contains(center) && transfer.contains(planet) {
+                dot.edge(center, planet, &[("color", "red"), ("penwidth", "2")]);
+            } else {
+                dot.edge(center, planet, &[]);
+            }
+        }
+    }
+    dot
+}
+
 #[cfg(test)]
 mod tests;
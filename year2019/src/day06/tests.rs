@@ -7,7 +7,7 @@ const DATA: &str = include_str!("data.txt");
 #[test]
 fn checked_sum() {
     let orbits = parse(A);
-    let from_origin = depth_first_search(COM, orbits).unwrap();
+    let from_origin = depth_first_search(COM, &orbits).unwrap();
     let result = check_sum(&from_origin);
 
     assert_eq!(42, result);
@@ -16,16 +16,29 @@ fn checked_sum() {
 #[test]
 fn shortest_paths() {
     let orbits = parse(B);
-    let from_origin = depth_first_search(COM, orbits).unwrap();
+    let from_origin = depth_first_search(COM, &orbits).unwrap();
     let result = shortest_path(&from_origin, "YOU", "SAN").unwrap();
 
     assert_eq!(4, result);
 }
 
+#[test]
+fn orbit_graphs() {
+    let orbits = parse(B);
+    let from_origin = depth_first_search(COM, &orbits).unwrap();
+    let dot = orbit_graph(&orbits, &from_origin, "YOU", "SAN").to_string();
+
+    assert_eq!(13, dot.matches(" -> ").count());
+    // YOU -> K -> J -> E -> D -> I -> SAN, the transfers going through D
+    assert_eq!(6, dot.matches("color=\"red\"").count());
+    assert!(dot.contains("\"D\" -> \"I\" [color=\"red\""));
+    assert!(dot.contains("\"C\" -> \"D\";"));
+}
+
 #[test]
 fn solve_test() {
     let orbits = parse(DATA);
-    let from_origin = depth_first_search(COM, orbits).unwrap();
+    let from_origin = depth_first_search(COM, &orbits).unwrap();
     let first = check_sum(&from_origin);
     let second = shortest_path(&from_origin, "YOU", "SAN").unwrap();
 
@@ -2,7 +2,8 @@ use itertools::Itertools;
 
 use commons::Result;
 
-use super::int_code::{IntCodeInput, Processor, Status};
+use super::int_code::network::{Network, Route, Router};
+use super::int_code::{IntCodeInput, Processor};
 
 pub const TITLE: &str = "Day 7: Amplification Circuit";
 
@@ -52,26 +53,44 @@ fn maximum_output(memory: &[i64], base: &[u8]) -> i64 {
 
 /// Runs the amplifier with the given memory and phases.
 fn amplifier(memory: &[i64], inputs: &[u8]) -> Option<i64> {
-    let mut programs: Vec<_> = inputs
-        .iter()
-        .map(|phase| Processor::with_initial_inputs(memory, &[*phase as i64]))
-        .collect();
-
-    let mut done = false;
-    let mut input = Some(0);
-    while !done {
-        for program in programs.iter_mut() {
-            program.write_int(input?);
-            input = program.read_next().ok();
-
-            // Check if program is halting next (in that case we can stop)
-            if let Ok(Status::Halted) = program.run() {
-                done = true;
-            }
+    let mut amplifiers: Network<i64> = Network::new(
+        inputs
+            .iter()
+            .map(|phase| Processor::with_initial_inputs(memory, &[*phase as i64])),
+    );
+
+    amplifiers.send(0, &0).ok()?;
+    amplifiers
+        .run(&mut Feedback {
+            size: inputs.len(),
+            thrust: None,
+        })
+        .ok()
+}
+
+/// Routes the signal of each amplifier to the next one, the last one feeding back the first one
+struct Feedback {
+    /// The number of amplifiers
+    size: usize,
+    /// The last signal sent by the last amplifier
+    thrust: Option<i64>,
+}
+
+impl Router for Feedback {
+    type Packet = i64;
+    type Output = i64;
+
+    fn route(&mut self, from: usize, signal: i64) -> Route<i64, i64> {
+        if from + 1 == self.size {
+            self.thrust = Some(signal);
         }
+        Route::Deliver((from + 1) % self.size, signal)
     }
 
-    input
+    /// The amplifiers are done once they all halted
+    fn on_idle(&mut self) -> Route<i64, i64> {
+        self.thrust.take().map_or(Route::Discard, Route::Stop)
+    }
 }
 
 #[cfg(test)]
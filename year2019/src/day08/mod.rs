@@ -2,7 +2,9 @@ use std::fmt::{Debug, Display, Formatter};
 
 use itertools::Itertools;
 
-use commons::{bail, Result};
+use commons::grid::Grid;
+use commons::ocr;
+use commons::{bail, Result, WrapErr};
 
 pub const TITLE: &str = "Day 8: Space Image Format";
 const WIDTH: usize = 25;
@@ -14,6 +16,7 @@ pub fn run(raw: String) -> Result<()> {
     image.build();
     println!("Image checksum is {} * {} =  {}", w, t, w * t);
     println!("{image}");
+    println!("It reads {}", image.text()?);
 
     Ok(())
 }
@@ -107,6 +110,14 @@ impl Image {
         self.built_image = Some(flattened);
     }
 
+    /// Read the letters of the built image
+    pub fn text(&self) -> Result<String> {
+        let layer = self
+            .built_image
+            .wrap_err("The image must be built to be read")?;
+        ocr::read(&Grid::tabulate(WIDTH, HEIGHT, |(x, y)| layer[y][x] == 1))
+    }
+
     /// Formats a layer to a String.
     fn layer_representation(layer: [[u8; WIDTH]; HEIGHT]) -> String {
         layer
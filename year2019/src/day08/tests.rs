@@ -8,4 +8,5 @@ fn solve_test() {
     let (_, w, t) = image.check_sum();
     image.build();
     assert_eq!(2_375, w * t);
+    assert_eq!(image.text().unwrap(), "RKHRY");
 }
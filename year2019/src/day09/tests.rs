@@ -8,15 +8,17 @@ fn solve_test() {
     let mut test_process = Processor::with_initial_inputs(&memory, &[1]);
     let mut output_count: usize = 0;
     let mut current: i64 = 0;
-    test_process.run_with_callbacks(
-        0,
-        |_| None,
-        |_, out| {
-            current = out;
-            output_count += 1;
-            Ok(())
-        },
-    );
+    test_process
+        .run_with_callbacks(
+            0,
+            |_| None,
+            |_, out| {
+                current = out;
+                output_count += 1;
+                Ok(())
+            },
+        )
+        .unwrap();
     assert_eq!(output_count, 1, "The TEST program should output once only");
     assert_eq!(2_752_191_671, current);
 
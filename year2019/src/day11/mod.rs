@@ -2,9 +2,10 @@ use itertools::Itertools;
 use std::collections::HashMap;
 
 use commons::grid::{Direction, Point};
+use commons::ocr;
 use commons::Result;
 
-use super::int_code::{IntCodeError, IntCodeInput, Processor, Status};
+use super::int_code::{IntCodeError, IntCodeInput, Processor, ReadError, Status};
 
 pub const TITLE: &str = "Day 11: Space Police";
 
@@ -20,6 +21,7 @@ pub fn run(raw: String) -> Result<()> {
         "The robot painted something:\n{}",
         paint_hull(&memory, &mut hull)?
     );
+    println!("It reads {}", hull_text(&hull)?);
 
     Ok(())
 }
@@ -40,9 +42,11 @@ fn paint_hull(memory: &[i64], hull: &mut HashMap<Point, u8>) -> Result<String, I
         program.write_int(*hull.get(&position).unwrap_or(&0) as i64);
         let (read, status) = program.read_next_array(&mut outputs, 2);
 
-        // Check if the status is now Halted
-        if let Some(Status::Halted) = status {
-            break;
+        // Check if the status is now Halted, or if the robot failed
+        match status {
+            Some(ReadError::Blocked(Status::Halted)) => break,
+            Some(ReadError::Failed(error)) => return Err(error),
+            _ => {}
         }
 
         // Collect the robot output
@@ -71,6 +75,15 @@ fn paint_hull(memory: &[i64], hull: &mut HashMap<Point, u8>) -> Result<String, I
     Ok(hull_representation(hull, position, min, max))
 }
 
+/// Read the letters painted in white on the hull
+fn hull_text(hull: &HashMap<Point, u8>) -> Result<String> {
+    ocr::read_points(
+        hull.iter()
+            .filter(|(_, color)| **color == 1)
+            .map(|(position, _)| position),
+    )
+}
+
 /// Computes the String representation of the current painting job
 fn hull_representation(
     hull: &HashMap<Point, u8>,
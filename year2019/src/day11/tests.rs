@@ -17,4 +17,5 @@ fn solve_test() {
     let second_paint: String = paint_hull(&memory, &mut hull).unwrap();
 
     assert_eq!(EXPECTED, &second_paint);
+    assert_eq!(hull_text(&hull).unwrap(), "PZRFPRKC");
 }
@@ -1,26 +1,76 @@
-use std::fmt::{Display, Formatter};
+use std::io::stdout;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
-use itertools::Itertools;
-
-use commons::Result;
-use commons::{CLEAR_COMMAND, TO_TOP};
+use commons::arguments::Options;
+use commons::grid::image::Rgb;
+use commons::grid::Grid;
+use commons::terminal::{Key, Keyboard, Renderer};
+use commons::{ensure, Result, WrapErr};
 
+use super::int_code::io::IntCodeIo;
 use super::int_code::{IntCodeInput, Processor, Status};
 
 pub const TITLE: &str = "Day 13: Care Package";
-/// The delay in milliseconds between printing each frame
-const FRAME_DELAY: u64 = 0;
+pub const OPTIONS: &[&str] = &["play", "replay", "record"];
+
+/// The colors of the tiles of the cabinet screen
+const PALETTE: [(char, Rgb); 4] = [
+    ('#', [0x80, 0x80, 0x80]),
+    ('X', [0x40, 0x90, 0xff]),
+    ('@', [0x40, 0xff, 0x40]),
+    ('O', [0xff, 0xff, 0x40]),
+];
+
+/// The frames per second when playing, unless `--fps` is given
+const PLAY_FPS: u32 = 8;
 
-pub fn run(raw: String) -> Result<()> {
-    println!("{CLEAR_COMMAND}");
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let mut memory = parse(&raw)?.data;
     memory[0] = 2;
     let mut engine = Processor::new(&memory);
     let mut state = GameState::default();
+
+    // When playing, the player waits for each frame so the screen is drawn as fast as possible
+    let player = options
+        .has("play")
+        .then(|| Player::from_options(options))
+        .transpose()?;
+    let mut renderer = match player {
+        Some(_) => Some(Renderer::new(stdout(), 0, 0)),
+        None => Renderer::from_options(options, 0, 0)?,
+    }
+    .map(|r| r.with_palette(&PALETTE));
+    let mut replay = match options.values("replay").first() {
+        Some(path) => Some(load_joystick(path)?.into_iter()),
+        None => None,
+    };
+    let mut player = player;
+
+    // The joystick comes from the replay, or the player, or follows the ball
+    let mut recorded = Vec::new();
+    let decider = |state: &GameState| {
+        let joystick = match (&mut replay, &mut player) {
+            (Some(replay), _) => replay.next()?,
+            (None, Some(player)) => player.joystick()?,
+            (None, None) => simple_decider(state),
+        };
+        recorded.push(joystick);
+        Some(joystick)
+    };
     let (score, (remaining, total_blocks)) =
-        state.run_with_decider(&mut engine, true, simple_decider);
+        state.run_with_decider(&mut engine, renderer.as_mut(), decider)?;
+    if let Some(renderer) = renderer {
+        renderer.finish()?;
+    }
+    // Restore the terminal before printing anything else
+    drop(player);
 
     println!("Final score: {score} with {remaining}/{total_blocks} blocks remaining.");
+    if let Some(path) = options.values("record").first() {
+        save_joystick(path, &recorded)?;
+        println!("The {} joystick moves were saved in {path}", recorded.len());
+    }
 
     Ok(())
 }
@@ -33,6 +83,84 @@ fn simple_decider(state: &GameState) -> i64 {
     (state.ball.unwrap().0 as i64 - state.player.unwrap().0 as i64).signum()
 }
 
+/// Load the positions of the joystick recorded in a file, one by line (-1, 0 or 1)
+fn load_joystick(path: &str) -> Result<Vec<i64>> {
+    let recorded = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Can't load moves from {path:?}"))?;
+    recorded
+        .lines()
+        .enumerate()
+        .map(|(index, line)| {
+            let joystick = line
+                .trim()
+                .parse()
+                .wrap_err_with(|| format!("Invalid move {line:?} line {}", index + 1))?;
+            ensure!(
+                (-1..=1).contains(&joystick),
+                "Invalid move {joystick} line {}, expected -1, 0 or 1",
+                index + 1
+            );
+            Ok(joystick)
+        })
+        .collect()
+}
+
+/// Save the positions of the joystick in a file, one by line
+fn save_joystick(path: &str, joystick: &[i64]) -> Result<()> {
+    let recorded: String = joystick.iter().map(|j| format!("{j}\n")).collect();
+    std::fs::write(path, recorded).wrap_err_with(|| format!("Can't save moves to {path:?}"))
+}
+
+/// Someone playing with the keyboard: the arrows move the paddle until it is stopped with the
+/// down arrow or space, `q` or escape ends the game
+struct Player {
+    keyboard: Keyboard,
+    /// The current position of the joystick
+    direction: i64,
+    /// The time given to press keys at each frame
+    frame: Duration,
+    /// When the joystick was last moved
+    last: Option<Instant>,
+}
+
+impl Player {
+    /// A player with the frames per second of the `--fps=<FPS>` option
+    fn from_options(options: &Options) -> Result<Self> {
+        let fps: u32 = match options.values("fps").first() {
+            Some(fps) => fps
+                .parse()
+                .wrap_err_with(|| format!("Invalid --fps '{fps}'"))?,
+            None => PLAY_FPS,
+        };
+        ensure!(fps > 0, "The game can't be played at 0 frames per second");
+        let keyboard = Keyboard::open().wrap_err("--play needs a terminal")?;
+        Ok(Self {
+            keyboard,
+            direction: 0,
+            frame: Duration::from_secs(1) / fps,
+            last: None,
+        })
+    }
+
+    /// The position of the joystick after the keys pressed during the frame, None to quit
+    fn joystick(&mut self) -> Option<i64> {
+        if let Some(last) = self.last {
+            sleep(self.frame.saturating_sub(last.elapsed()));
+        }
+        self.last = Some(Instant::now());
+        for key in self.keyboard.keys() {
+            match key {
+                Key::Left => self.direction = -1,
+                Key::Right => self.direction = 1,
+                Key::Down | Key::Char(' ') => self.direction = 0,
+                Key::Escape | Key::Interrupt | Key::Char('q') => return None,
+                _ => {}
+            }
+        }
+        Some(self.direction)
+    }
+}
+
 #[derive(Default)]
 struct GameState {
     score: i64,
@@ -44,66 +172,44 @@ struct GameState {
     blocks: u64,
 }
 
-impl Display for GameState {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let display = self
-            .screen
-            .iter()
-            .map(|row| row.iter().map(|tile| tile.char()).join(""))
-            .join("\n");
-        write!(
-            f,
-            "Blocks: {blocks:04}\nScore: {score:05}\n{display}",
-            score = self.score,
-            blocks = self.blocks,
-            display = display
-        )
-    }
-}
-
 impl GameState {
-    /// Run the game until it halts.
+    /// Run the game until it halts, or until the decider stops giving inputs.
     /// Inputs are provided by the decider closure which has access to the state.
+    /// Each frame is drawn with the renderer, if any.
     /// # Returns
-    /// A tuple of (score, (remaining blocks, total blocks))
+    /// A tuple of (score, (remaining blocks, total blocks)), Err if the game or its drawing failed
     pub fn run_with_decider<F>(
         &mut self,
         engine: &mut Processor,
-        show: bool,
+        renderer: Option<&mut Renderer>,
         decider: F,
-    ) -> (i64, (u64, u64))
+    ) -> Result<(i64, (u64, u64))>
     where
-        F: Fn(&Self) -> i64,
+        F: FnMut(&Self) -> Option<i64>,
     {
-        let mut status = self.run(engine, show);
-        let blocks = self.blocks;
-        loop {
-            match status {
-                Status::RequireInput => engine.write_int(decider(self)),
-                _ => return (self.score, (self.blocks, blocks)),
-            }
-            status = self.run(engine, false);
-            if show {
-                std::thread::sleep(std::time::Duration::from_millis(FRAME_DELAY));
-                println!("{}{}", TO_TOP, &self);
-            }
+        let mut cabinet = Cabinet {
+            state: self,
+            joystick: decider,
+            renderer,
+            tile: Vec::with_capacity(3),
+            total_blocks: None,
+            failure: None,
+        };
+        let status = engine.run_with_io(&mut cabinet);
+        if let Some(failure) = cabinet.failure.take() {
+            return Err(failure).wrap_err("The game could not be drawn");
         }
+        status?;
+        let total_blocks = cabinet.total_blocks.unwrap_or(self.blocks);
+        Ok((self.score, (self.blocks, total_blocks)))
     }
 
-    /// Run the game until it blocks for any reason.
-    pub fn run(&mut self, engine: &mut Processor, show: bool) -> Status {
-        let mut outputs = [0; 3];
-        loop {
-            match engine.read_next_array(&mut outputs, 3) {
-                (_, Some(status)) => return status,
-                (3, _) => self.update((outputs[0], outputs[1], outputs[2])),
-                _ => return Status::Halted,
-            }
-            if show {
-                std::thread::sleep(std::time::Duration::from_millis(FRAME_DELAY));
-                println!("{}{}", TO_TOP, &self);
-            }
-        }
+    /// The tiles of the game screen
+    fn screen(&self) -> Grid<Tile> {
+        Grid::tabulate(self.width, self.height, |(x, y)| {
+            let row = &self.screen[y];
+            row.get(x).copied().unwrap_or(Tile::Empty)
+        })
     }
 
     /// Dispatch the output from the IntCode engine to update the game state.
@@ -159,6 +265,66 @@ impl GameState {
     }
 }
 
+/// The arcade cabinet: draws the outputs of the game and moves the joystick with a decider
+struct Cabinet<'a, F> {
+    /// The state of the game being played
+    state: &'a mut GameState,
+    /// Decides the position of the joystick from the state of the game, None to stop the game
+    joystick: F,
+    /// Draws each frame, if they should be displayed
+    renderer: Option<&'a mut Renderer>,
+    /// The outputs of the tile currently being drawn
+    tile: Vec<i64>,
+    /// The number of blocks when the game first required input
+    total_blocks: Option<u64>,
+    /// The error that stopped the drawing of the game, if any
+    failure: Option<std::io::Error>,
+}
+
+impl<'a, F: FnMut(&GameState) -> Option<i64>> Cabinet<'a, F> {
+    /// Display the game state if needed, halting the game if it can't be drawn
+    fn show(&mut self) -> Result<(), Status> {
+        if let Some(renderer) = self.renderer.as_mut() {
+            let state = &self.state;
+            renderer.set_status(&format!(
+                "Blocks: {:04}\nScore: {:05}",
+                state.blocks, state.score
+            ));
+            renderer.resize(state.width, state.height);
+            let drawn = renderer.draw(&state.screen(), (0, 0), |tile| tile.char());
+            if let Err(failure) = drawn {
+                self.failure = Some(failure);
+                return Err(Status::Halted);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: FnMut(&GameState) -> Option<i64>> IntCodeIo for Cabinet<'a, F> {
+    fn input(&mut self) -> Result<i64, Status> {
+        if self.total_blocks.is_none() {
+            self.total_blocks = Some(self.state.blocks);
+        } else {
+            self.show()?;
+        }
+        (self.joystick)(self.state).ok_or(Status::RequireInput)
+    }
+
+    fn output(&mut self, value: i64) -> Result<(), Status> {
+        self.tile.push(value);
+        if let [x, y, tile] = self.tile[..] {
+            self.tile.clear();
+            self.state.update((x, y, tile));
+            // Only show each drawn tile before the game starts, then show each frame
+            if self.total_blocks.is_none() {
+                self.show()?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum Tile {
     Empty,
@@ -8,8 +8,9 @@ fn solve_test() {
     memory[0] = 2;
     let mut engine = Processor::new(&memory);
     let mut state = GameState::default();
-    let (score, (remaining, total_blocks)) =
-        state.run_with_decider(&mut engine, false, simple_decider);
+    let (score, (remaining, total_blocks)) = state
+        .run_with_decider(&mut engine, None, |state| Some(simple_decider(state)))
+        .unwrap();
 
     assert_eq!(452, total_blocks);
     assert_eq!(
@@ -18,3 +19,40 @@ fn solve_test() {
     );
     assert_eq!(21415, score);
 }
+
+#[test]
+fn replayed_joystick() {
+    let mut memory = parse(DATA).unwrap().data;
+    memory[0] = 2;
+    let mut recorded = Vec::new();
+    let mut state = GameState::default();
+    state
+        .run_with_decider(&mut Processor::new(&memory), None, |state| {
+            let joystick = simple_decider(state);
+            recorded.push(joystick);
+            Some(joystick)
+        })
+        .unwrap();
+
+    // A file for this process only, so that concurrent test runs do not share it
+    let name = format!("aoc_2019_13_joystick_{}.txt", std::process::id());
+    let path = std::env::temp_dir().join(name);
+    let path = path.to_str().unwrap();
+    save_joystick(path, &recorded).unwrap();
+    let mut replay = load_joystick(path).unwrap().into_iter();
+    std::fs::remove_file(path).unwrap();
+
+    let mut state = GameState::default();
+    let (score, (remaining, _)) = state
+        .run_with_decider(&mut Processor::new(&memory), None, |_| replay.next())
+        .unwrap();
+    assert_eq!((21415, 0), (score, remaining));
+
+    // Stopping the replay halfway stops the game
+    let mut replay = recorded[..100].iter().copied();
+    let mut state = GameState::default();
+    let (_, (remaining, _)) = state
+        .run_with_decider(&mut Processor::new(&memory), None, |_| replay.next())
+        .unwrap();
+    assert!(remaining > 0);
+}
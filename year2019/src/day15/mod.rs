@@ -1,20 +1,33 @@
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
 
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
-
-use commons::grid::{Direction, Point};
-use commons::{err, Result};
-use commons::{WrapErr, TO_TOP};
+use commons::arguments::Options;
+use commons::grid::image::Rgb;
+use commons::grid::{Direction, Grid, Point};
+use commons::terminal::Renderer;
+use commons::{err, Result, WrapErr};
 
 use super::int_code::{IntCodeInput, Processor, Status};
 
 pub const TITLE: &str = "Day 15: Oxygen System";
-const FRAME_DELAY: u64 = 0;
 
-pub fn run(raw: String) -> Result<()> {
+/// The size of the part of the map displayed around the robot while it explores
+const VIEWPORT: (usize, usize) = (31, 21);
+
+/// The colors of the tiles of the map
+const PALETTE: [(char, Rgb); 3] = [
+    ('#', [0x90, 0x90, 0x90]),
+    ('O', [0x40, 0xa0, 0xff]),
+    ('@', [0xff, 0x60, 0x40]),
+];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let memory = parse(&raw)?.data;
-    let map = explore_map(&memory, true);
+    let renderer = Renderer::from_options(options, VIEWPORT.0, VIEWPORT.1)?;
+    let mut renderer = renderer.map(|renderer| renderer.with_palette(&PALETTE));
+    let map = explore_map(&memory, renderer.as_mut())?;
+    if let Some(renderer) = renderer {
+        renderer.finish()?;
+    }
 
     // First part
     let (oxygen, path_length) = first_part(&map)?;
@@ -52,8 +65,12 @@ fn second_part(map: &HashMap<Point, Tile>, oxygen: Point) -> Result<usize> {
     Ok(path.len())
 }
 
-/// The robot explores the maze until it finds no unexplored tiles adjacent to explored ones
-fn explore_map(memory: &[i64], show: bool) -> HashMap<Point, Tile> {
+/// The robot explores the maze until it finds no unexplored tiles adjacent to explored ones.
+/// Each move is drawn with the renderer, if any.
+fn explore_map(
+    memory: &[i64],
+    mut renderer: Option<&mut Renderer>,
+) -> std::io::Result<HashMap<Point, Tile>> {
     fn convert_direction(direction: Direction) -> i64 {
         match direction {
             Direction::North => 1,
@@ -65,7 +82,7 @@ fn explore_map(memory: &[i64], show: bool) -> HashMap<Point, Tile> {
 
     let mut robot: Processor = memory.into();
     let mut map: HashMap<Point, Tile> = HashMap::new();
-    let ((mut min_x, mut min_y), (mut max_x, mut max_y)) = ((0, 0), (0, 0));
+    let (mut min, mut max): (Point, Point) = (Point::default(), Point::default());
 
     let mut current = Point::default();
     let mut direction = Direction::North;
@@ -87,14 +104,13 @@ fn explore_map(memory: &[i64], show: bool) -> HashMap<Point, Tile> {
                     current = explored;
                 }
 
-                // This code snippet is used to display the full map during the exploration.
-                if show {
-                    min_x = min_x.min(explored.x);
-                    min_y = min_y.min(explored.y);
-                    max_x = max_x.max(explored.x);
-                    max_y = max_y.max(explored.y);
-                    print_map(current, &map, (min_x, min_y), (max_x, max_y));
-                    std::thread::sleep(std::time::Duration::from_millis(FRAME_DELAY));
+                // Display the known map around the robot during the exploration
+                if let Some(renderer) = renderer.as_mut() {
+                    min = Point::new(min.x.min(explored.x), min.y.min(explored.y));
+                    max = Point::new(max.x.max(explored.x), max.y.max(explored.y));
+                    let known = known_map(current, &map, min, max);
+                    renderer.follow(Some(current.tupled()));
+                    renderer.draw(&known, min.tupled(), |c| *c)?;
                 }
             }
             Ok(Status::RequireInput) => {
@@ -105,7 +121,7 @@ fn explore_map(memory: &[i64], show: bool) -> HashMap<Point, Tile> {
                     // Use a BFS to look for the nearest not explored point
                     match bfs(current, &map, |p, _| !map.contains_key(&p)) {
                         Some(path) => next = path,
-                        None => return map,
+                        None => return Ok(map),
                     }
                 }
             }
@@ -157,30 +173,17 @@ where
     None
 }
 
-/// Prints the map to the console.
-fn print_map(current: Point, map: &HashMap<Point, Tile>, min: (i64, i64), max: (i64, i64)) {
-    let to_x = max.0 + 1;
-    let to_y = max.1 + 1;
-
-    let display = (min.0..to_y)
-        .map(|y| {
-            (min.1..to_x)
-                .map(|x| {
-                    let point = Point::new(x, y);
-                    if point == current {
-                        '@'
-                    } else {
-                        match map.get(&point) {
-                            Some(&tile) => tile.char(),
-                            None => ' ',
-                        }
-                    }
-                })
-                .join("")
-        })
-        .join("\n");
-
-    println!("{TO_TOP}{display}");
+/// The characters of the map known between two corners, with the robot at its position
+fn known_map(current: Point, map: &HashMap<Point, Tile>, min: Point, max: Point) -> Grid<char> {
+    let (width, height) = ((max.x - min.x + 1) as usize, (max.y - min.y + 1) as usize);
+    Grid::tabulate(width, height, |(x, y)| {
+        let point = Point::new(min.x + x as i64, min.y + y as i64);
+        if point == current {
+            '@'
+        } else {
+            map.get(&point).map_or(' ', |tile| tile.char())
+        }
+    })
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
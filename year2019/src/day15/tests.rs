@@ -5,7 +5,7 @@ const DATA: &str = include_str!("data.txt");
 #[test]
 fn solve_test() {
     let memory = parse(DATA).unwrap().data;
-    let map = explore_map(&memory, false);
+    let map = explore_map(&memory, None).unwrap();
     let (oxygen, path_length) = first_part(&map).unwrap();
 
     assert_eq!(Point { x: 16, y: 16 }, oxygen);
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashSet,
     fmt::{Display, Formatter, Result as FmtResult},
     io::{stdout, BufWriter, Write},
     str::FromStr,
@@ -7,14 +7,26 @@ use std::{
 
 use itertools::Itertools;
 
+use commons::arguments::Options;
+use commons::compression::{Compressed, Compressor};
 use commons::grid::{Direction, Point};
-use commons::{Result, WrapErr};
+use commons::{ensure, Result, WrapErr};
 
 use super::int_code::{IntCodeInput, Processor, Status};
 
 pub const TITLE: &str = "Day 17: Set and Forget";
+pub const OPTIONS: &[&str] = &["optimal", "all"];
 
-pub fn run(raw: String) -> Result<()> {
+/// The number of movement functions of the robot
+const FUNCTIONS: usize = 3;
+
+/// The maximum number of characters of the main routine and of each function
+const MAX_LENGTH: usize = 20;
+
+/// The characters between two moves or two calls
+const SEPARATOR: &str = ",";
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let memory = parse(&raw)?.data;
     let scaffold = Scaffold::from_camera_program(&memory, true)
         .wrap_err("The camera program should have worked !")?;
@@ -26,9 +38,25 @@ pub fn run(raw: String) -> Result<()> {
     // Second part
     let path = scaffold.straight_ahead_path();
     println!("The path is {}", path.iter().join(","));
-    let (main, a, b, c) =
-        compression(&path, (5, 20)).wrap_err("The compression should succeed !")?;
-    println!("We can send it as {main} with \nA = {a}\nB = {b} \nC = {c}");
+    let compressor = Compressor::new(FUNCTIONS, MAX_LENGTH)?.with_separator(SEPARATOR);
+    if options.has("all") {
+        let all = compressor.all(&path);
+        println!("The number of ways to compress the path is {}", all.len());
+    }
+    let compressed = movement_functions(&path, &compressor, options.has("optimal"))?;
+    let mut routines = vec![compressed.main_routine(SEPARATOR)];
+    println!(
+        "We can send it as {} with ({} characters)",
+        routines[0],
+        compressed.length(SEPARATOR)
+    );
+    for function in 0..FUNCTIONS {
+        routines.push(compressed.function(function, SEPARATOR));
+        if let Some(name) = Compressed::<Path>::name(function) {
+            println!("{name} = {}", routines[function + 1]);
+        }
+    }
+    routines.push("n".to_owned());
 
     // Run the robot with the path
     let mut robot: Processor = {
@@ -38,8 +66,8 @@ pub fn run(raw: String) -> Result<()> {
     };
 
     let mut stdout = BufWriter::new(stdout());
-    let _ = robot.run_with_ascii_callbacks(
-        [&main, &a, &b, &c, "n"].iter(),
+    let (status, _) = robot.run_with_ascii_callbacks(
+        routines.iter(),
         |iterator| Some(format!("{}\n", iterator.next()?)),
         |_, line| {
             stdout
@@ -48,6 +76,7 @@ pub fn run(raw: String) -> Result<()> {
         },
     );
     stdout.flush()?;
+    status?;
 
     println!("The robot finished working, see above for last output.");
 
@@ -58,99 +87,27 @@ fn parse(s: &str) -> Result<IntCodeInput> {
     Ok(s.parse()?)
 }
 
-/// Try to compress a full path into a combination of 3 smaller paths as a main path
-fn compression(
-    full_path: &[Path],
-    (min_length, max_length): (usize, usize),
-) -> Option<(String, String, String, String)> {
-    // Build the main routine from the used patterns and the full pattern map
-    fn reconstruct_main(
-        used: &[&[Path]], // The slice containing exactly 3 patterns (A, B and C)
-        path: &[&[Path]], // The slice containing all patterns use instance in the use order
-    ) -> Option<(String, String, String, String)> {
-        let mut patterns = used.iter();
-        let a = patterns.next()?.iter().join(",");
-        let b = patterns.next()?.iter().join(",");
-        let c = patterns.next()?.iter().join(",");
-        let main_routine = path
-            .iter()
-            .map(|path| {
-                let converted = path.iter().join(",");
-                if converted == a {
-                    'A'
-                } else if converted == b {
-                    'B'
-                } else {
-                    'C'
-                }
-            })
-            .join(",");
-        Some((main_routine, a, b, c))
-    }
-
-    // The main function here :
-    // Try to find a matching 3 elements pattern for the compression algorithm
-    fn find_matching_patterns<'a>(
-        patterns: &HashMap<usize, Vec<&'a [Path]>>,
-        start: usize,
-        end: usize,
-        used: &[&'a [Path]],
-        path: &[&'a [Path]],
-    ) -> Option<(String, String, String, String)> {
-        if start >= end {
-            // If we have reached the end we are done and can rebuild the path
-            reconstruct_main(used, path)
-        } else if let Some(possible) = patterns.get(&start) {
-            // If some length remains we go in turn through each possibility starting at this pos
-            possible.iter().find_map(|additional| {
-                let length = additional.len();
-                if used.contains(additional) {
-                    // If the possible pattern is already used, perfect !
-                    let mut new_path = path.to_owned();
-                    new_path.push(additional);
-                    find_matching_patterns(patterns, start + length, end, used, &new_path)
-                } else if used.len() < 3 {
-                    // If it is not already used but we have not reached 3, we can try it
-                    let mut new_used = used.to_owned();
-                    new_used.push(additional);
-                    let mut new_path = path.to_owned();
-                    new_path.push(additional);
-                    find_matching_patterns(patterns, start + length, end, &new_used, &new_path)
-                } else {
-                    // But in the other cases this attempt is a miss
-                    None
-                }
-            })
-        } else {
-            // This case should never happen as we have computed all patterns from 0 to end - 1
-            None
-        }
-    }
-
-    let main_length = full_path.len();
-    // Build the pattern map between all positions and the possible patterns starting there
-    let patterns: HashMap<usize, Vec<&[Path]>> = (0..main_length)
-        .map(|start| {
-            let max_sub_length = (start + max_length / 2).min(main_length) + 1;
-            let possibilities: Vec<&[Path]> = ((start + 1)..max_sub_length)
-                .rev()
-                .filter_map(|end| {
-                    let slice = &full_path[start..end];
-                    let sub_length = slice.iter().map(|path| path.len()).sum::<usize>() - 1;
-                    // Evict possibilities that are not in the wanted bounds
-                    if min_length <= sub_length && sub_length <= max_length {
-                        Some(slice)
-                    } else {
-                        None
-                    }
-                })
-                .collect();
-            (start, possibilities)
-        })
-        .collect();
-
-    // Recursively go through each possibility, pruning as early as possible dead-ends
-    find_matching_patterns(&patterns, 0, main_length, &Vec::new(), &Vec::new())
+/// A path as the main routine and the movement functions of the robot
+/// # Arguments
+/// * `path` - The moves of the robot, like the straight ahead path of the scaffold
+/// * `compressor` - The limits of the movement functions
+/// * `optimal` - True for the shortest compression, false for the first one found
+fn movement_functions(
+    path: &[Path],
+    compressor: &Compressor,
+    optimal: bool,
+) -> Result<Compressed<Path>> {
+    let compressed = if optimal {
+        compressor.optimal(path)
+    } else {
+        compressor.first(path)
+    };
+    let compressed = compressed.wrap_err("The compression should succeed !")?;
+    ensure!(
+        compressed.expand() == path,
+        "The compression does not follow the path"
+    );
+    Ok(compressed)
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -291,22 +248,5 @@ impl Display for Path {
     }
 }
 
-impl Path {
-    /// The length of this path as an ascii value (including the following ,)
-    pub fn len(&self) -> usize {
-        fn number_of_decimal_digit(int: usize, acc: usize) -> usize {
-            if int >= 10 {
-                number_of_decimal_digit(int / 10, acc + 1)
-            } else {
-                acc + 1
-            }
-        }
-        match *self {
-            Path::Right | Path::Left => 2,
-            Path::Ahead(size) => number_of_decimal_digit(size, 1),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests;
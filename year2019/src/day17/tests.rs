@@ -99,16 +99,15 @@ fn straight_path_test_b() {
 fn compression_test_a() {
     let scaffold: Scaffold = TEST_TWO.parse().unwrap();
     let path = scaffold.straight_ahead_path();
-    let (main, a, b, c) = compression(&path, (5, 20)).unwrap();
-    let rebuilt = main.replace('A', &a).replace('B', &b).replace('C', &c);
+    let compressed = Compressor::new(3, 20).unwrap().first(&path).unwrap();
 
-    assert!(main.len() < 20);
-    assert!(a.len() < 20);
-    assert!(b.len() < 20);
-    assert!(c.len() < 20);
+    assert!(compressed.main_routine(",").len() <= 20);
+    for function in 0..3 {
+        assert!(compressed.function(function, ",").len() <= 20);
+    }
     assert_eq!(
         "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2",
-        rebuilt
+        compressed.expand().iter().join(",")
     );
 }
 
@@ -117,14 +116,19 @@ fn compression_test_b() {
     let memory = parse(CODE).unwrap().data;
     let scaffold = Scaffold::from_camera_program(&memory, false).unwrap();
     let path = scaffold.straight_ahead_path();
-    let (main, a, b, c) = compression(&path, (5, 20)).unwrap();
+    let compressor = Compressor::new(3, 20).unwrap();
+    let compressed = movement_functions(&path, &compressor, false).unwrap();
 
-    assert_eq!("A,A,B,C,B,C,B,C,A,C", main);
-    assert_eq!("R,6,L,8,R,8", a);
-    assert_eq!("R,4,R,6,R,6,R,4,R,4", b);
-    assert_eq!("L,8,R,6,L,10,L,10", c);
+    assert_eq!("A,A,B,C,B,C,B,C,A,C", compressed.main_routine(","));
+    assert_eq!("R,6,L,8,R,8", compressed.function(0, ","));
+    assert_eq!("R,4,R,6,R,6,R,4,R,4", compressed.function(1, ","));
+    assert_eq!("L,8,R,6,L,10,L,10", compressed.function(2, ","));
     assert_eq!(
         include_str!("data_path.txt"),
-        main.replace('A', &a).replace('B', &b).replace('C', &c)
+        compressed.expand().iter().join(",")
     );
+
+    let optimal = movement_functions(&path, &compressor, true).unwrap();
+    assert!(optimal.length(",") <= compressed.length(","));
+    assert_eq!(optimal.expand(), path);
 }
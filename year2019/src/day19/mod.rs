@@ -77,8 +77,8 @@ fn check_position(drone: &[i64], point: Point) -> bool {
 
     match drone.read_next() {
         Ok(result) => result == 1,
-        Err(status) => {
-            println!("The drone should not stop, but we got status {status:?}");
+        Err(error) => {
+            println!("The drone should not stop, but it did: {error}");
             false
         }
     }
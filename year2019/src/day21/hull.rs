@@ -0,0 +1,109 @@
+//! Simulate the springdroid on a hull, to check a script before sending it to the droid
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use commons::{ensure, err, Report, Result};
+
+use super::springscript::Script;
+
+/// The distance of a jump
+const JUMP: usize = 4;
+
+/// A section of hull, the droid starts on its first tile
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Hull {
+    /// True for the ground, false for the holes
+    tiles: Vec<bool>,
+}
+
+/// The droid fell into a hole
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Fall {
+    /// The position of the hole
+    pub position: usize,
+    /// The position the droid jumped from, if it jumped into the hole
+    pub jumped_from: Option<usize>,
+}
+
+impl Hull {
+    /// Check if there is ground at a position, the hull past its end is all ground
+    pub fn ground(&self, position: usize) -> bool {
+        self.tiles.get(position).copied().unwrap_or(true)
+    }
+
+    /// The value of the sensors of the droid at a position
+    fn sensors(&self, position: usize, count: usize) -> u16 {
+        (0..count)
+            .filter(|distance| self.ground(position + distance + 1))
+            .fold(0, |sensors, distance| sensors | 1 << distance)
+    }
+
+    /// Move the droid along the hull according to a script
+    /// # Returns
+    /// The hole it fell into, if it did not cross the whole hull
+    pub fn simulate(&self, script: &Script) -> Result<(), Fall> {
+        let mut position = 0;
+        while position < self.tiles.len() {
+            let jump = script.eval(self.sensors(position, script.mode.sensors()));
+            let from = position;
+            position += if jump { JUMP } else { 1 };
+            if !self.ground(position) {
+                return Err(Fall {
+                    position,
+                    jumped_from: jump.then_some(from),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check a script against some hulls
+/// # Returns
+/// The first hull where the droid fell, and where it fell
+pub fn check<'a>(script: &Script, hulls: &'a [Hull]) -> Result<(), (&'a Hull, Fall)> {
+    hulls
+        .iter()
+        .try_for_each(|hull| hull.simulate(script).map_err(|fall| (hull, fall)))
+}
+
+impl FromStr for Hull {
+    type Err = Report;
+
+    /// Parse a hull as `#` for the ground and `.` for the holes, like `#####.#..########`
+    fn from_str(s: &str) -> Result<Self> {
+        let tiles = s
+            .trim()
+            .chars()
+            .map(|tile| match tile {
+                '#' => Ok(true),
+                '.' => Ok(false),
+                _ => Err(err!("Unknown tile '{tile}' in '{s}'")),
+            })
+            .collect::<Result<Vec<bool>>>()?;
+        ensure!(
+            tiles.first() == Some(&true),
+            "The droid must start on the ground in '{s}'"
+        );
+        Ok(Self { tiles })
+    }
+}
+
+impl Display for Hull {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for ground in &self.tiles {
+            write!(f, "{}", if *ground { '#' } else { '.' })?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Fall {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self.jumped_from {
+            Some(from) => write!(f, "jumped from {from} into the hole at {}", self.position),
+            None => write!(f, "walked into the hole at {}", self.position),
+        }
+    }
+}
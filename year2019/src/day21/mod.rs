@@ -1,50 +1,54 @@
 //! Day 21
 //!
+//! The springscript programs are compiled from boolean expressions over the sensors of the droid,
+//! then checked on some hulls with a simulation before being sent to the droid.
+//!
 //! ## PART ONE
 //! Jump if any empty tile in 1-3 and 4 is available :
 //! This allow to jump over any hole in sight as soon as it is possible.
 //!
-//! BOOLEAN FORM is : D && (!C || !B || !A)
-//! CODE is :
-//! NOT A T -> Store !A
-//! NOT B J -> Store !B
-//! OR J T  -> Compute x = !A || !C
-//! NOT C J -> Store !C
-//! OR T J  -> Compute y = x || !C
-//! AND D J -> Compute D && y
-//! WALK
-//!
 //! ## PART TWO
 //! Jump if any empty tile in 1-3, 4 and (5 or 8) are available :
 //! This allow to jump over any hole in sight like first part
 //! But it also avoid the bad case when the landing point is a lone part with a hole 4 case away.
-//!
-//! BOOLEAN FORM is : (D && (E || H)) && (!C || !B || !A)
-//! CODE is :
-//! NOT A T -> Store !A
-//! NOT B J -> Store !B
-//! OR J T  -> Compute x = !A || !C
-//! NOT C J -> Store !C
-//! OR T J  -> Compute y = x || !C
-//! OR E T
-//! OR H T  -> Compute z = E || H
-//! AND D T -> Compute a = D && z
-//! AND T J -> Compute D && z
-//! RUN
 
-use std::io::{stdout, BufWriter, Write};
+use commons::{bail, err, Result, WrapErr};
 
-use commons::Result;
-
-use super::int_code::{IntCodeInput, Processor, Status};
+use super::int_code::{IntCodeInput, Processor};
+use hull::Hull;
+use springscript::{compile, Mode, Script};
 
 pub const TITLE: &str = "Day 21: Springdroid Adventure";
 
+/// When to jump while walking
+const WALK: &str = "D & (!A | !B | !C)";
+/// When to jump while running
+const RUN: &str = "D & (E | H) & (!A | !B | !C)";
+
+/// Some hulls the walking droid must cross
+const WALK_HULLS: [&str; 3] = [
+    "#####.###########",
+    "#####..#.########",
+    "#####...#########",
+];
+/// Some hulls the running droid must cross
+const RUN_HULLS: [&str; 5] = [
+    "#####.###########",
+    "#####..#.########",
+    "#####...#########",
+    "#####.#.##..#.###",
+    "#####.##.##.#.###",
+];
+
 pub fn run(raw: String) -> Result<()> {
     let data = parse(&raw)?;
-    first_part(&data.data)?;
-    println!();
-    second_part(&data.data)?;
+    let script = springscript(WALK, Mode::Walk, &WALK_HULLS)?;
+    println!("The walking script:\n{script}");
+    println!("1. The hull damage is {}", survey(&data.data, &script)?);
+
+    let script = springscript(RUN, Mode::Run, &RUN_HULLS)?;
+    println!("The running script:\n{script}");
+    println!("2. The hull damage is {}", survey(&data.data, &script)?);
     Ok(())
 }
 
@@ -52,40 +56,42 @@ fn parse(s: &str) -> Result<IntCodeInput> {
     Ok(s.parse()?)
 }
 
-fn first_part(memory: &[i64]) -> std::io::Result<()> {
-    let mut stdout = BufWriter::new(stdout());
+/// Compile an expression and check it on some hulls
+fn springscript(expression: &str, mode: Mode, hulls: &[&str]) -> Result<Script> {
+    let script = compile(&expression.parse()?, mode)?;
+    let hulls = hulls
+        .iter()
+        .map(|hull| hull.parse())
+        .collect::<Result<Vec<Hull>>>()?;
+    if let Err((hull, fall)) = hull::check(&script, &hulls) {
+        bail!("With '{expression}' on the hull {hull}, the droid {fall}");
+    }
+    Ok(script)
+}
+
+/// Send a script to the droid
+/// # Returns
+/// The hull damage it reported, or an error with its view of the hull if it fell
+fn survey(memory: &[i64], script: &Script) -> Result<i64> {
     let mut robot: Processor = memory.into();
-    let _ = robot.run_with_ascii_callbacks(
-        [
-            "NOT A T", "NOT B J", "OR J T", "NOT C J", "OR T J", "AND D J", "WALK",
-        ]
-        .iter(),
-        |iterator| Some(format!("{}\n", iterator.next()?)),
-        |_, line| {
-            stdout
-                .write_all(line.as_bytes())
-                .map_err(|_| Status::Halted)
+    let lines: Vec<String> = script.to_string().lines().map(str::to_owned).collect();
+    let (status, (_, output)) = robot.run_with_ascii_callbacks(
+        (lines.into_iter(), String::new()),
+        |(lines, _)| Some(format!("{}\n", lines.next()?)),
+        |(_, output), line| {
+            output.push_str(line);
+            Ok(())
         },
     );
-    stdout.flush()
+    status?;
+    let last = output.lines().last().wrap_err("The droid did not answer")?;
+    last.trim()
+        .parse()
+        .map_err(|_| err!("The droid fell:\n{output}"))
 }
 
-fn second_part(memory: &[i64]) -> std::io::Result<()> {
-    let mut stdout = BufWriter::new(stdout());
-    let mut robot: Processor = memory[..].into();
-    let _ = robot.run_with_ascii_callbacks(
-        [
-            "NOT A T", "NOT B J", "OR J T", "NOT C J", "OR T J", "OR E T", "OR H T", "AND D T",
-            "AND T J", "RUN",
-        ]
-        .iter(),
-        |iterator| Some(format!("{}\n", iterator.next()?)),
-        |_, line| {
-            stdout
-                .write_all(line.as_bytes())
-                .map_err(|_| Status::Halted)
-        },
-    );
+mod hull;
+mod springscript;
 
-    stdout.flush()
-}
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,654 @@
+//! Compile boolean expressions over the sensors of the springdroid into springscript
+//!
+//! An expression uses the sensors `A` to `I` (ground is true), `!` for not, `&` for and, `|` for
+//! or and parentheses, like `D & (!A | !B | !C)`. The droid jumps when it is true.
+//!
+//! The script computes the expression in the `J` register with the `T` register as a temporary.
+//! Several equivalent forms are compiled (the expression as written, and its minimized sums of
+//! products), each node being computed either directly or by its negation, and the shortest
+//! script is kept.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use commons::{bail, ensure, err, Report, Result};
+
+/// The maximum number of instructions of a script
+pub const MAX_INSTRUCTIONS: usize = 15;
+
+/// The names of the sensors, from 1 to 9 tiles away
+const SENSORS: [char; 9] = ['A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I'];
+
+/// How the droid moves, which tells how far it can see
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mode {
+    /// Sees up to 4 tiles away (`A` to `D`)
+    Walk,
+    /// Sees up to 9 tiles away (`A` to `I`)
+    Run,
+}
+
+/// A boolean expression over the sensors
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    /// True if there is ground at this distance minus one
+    Sensor(usize),
+    Not(Box<Expr>),
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+}
+
+/// A register of the springdroid
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Register {
+    /// The temporary value
+    T,
+    /// The jump decision
+    J,
+}
+
+/// The first argument of an instruction
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Source {
+    Sensor(usize),
+    Register(Register),
+}
+
+/// A springscript operation
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    And,
+    Or,
+    Not,
+}
+
+/// A springscript instruction, storing in its target the operation on its source and target
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Instruction {
+    pub op: Op,
+    pub source: Source,
+    pub target: Register,
+}
+
+/// A complete springscript program
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Script {
+    pub instructions: Vec<Instruction>,
+    pub mode: Mode,
+}
+
+impl Mode {
+    /// The number of sensors available
+    pub fn sensors(self) -> usize {
+        match self {
+            Self::Walk => 4,
+            Self::Run => 9,
+        }
+    }
+}
+
+impl Expr {
+    /// Evaluate the expression
+    /// # Arguments
+    /// * `sensors` - The value of each sensor, the bit 0 for `A` to the bit 8 for `I`
+    pub fn eval(&self, sensors: u16) -> bool {
+        match self {
+            Self::Sensor(sensor) => sensors & (1 << sensor) != 0,
+            Self::Not(inner) => !inner.eval(sensors),
+            Self::And(all) => all.iter().all(|expr| expr.eval(sensors)),
+            Self::Or(any) => any.iter().any(|expr| expr.eval(sensors)),
+        }
+    }
+
+    /// The sensors used by the expression, as a bit set
+    pub fn sensors(&self) -> u16 {
+        match self {
+            Self::Sensor(sensor) => 1 << sensor,
+            Self::Not(inner) => inner.sensors(),
+            Self::And(all) | Self::Or(all) => all.iter().fold(0, |set, expr| set | expr.sensors()),
+        }
+    }
+}
+
+impl Script {
+    /// Evaluate the script like the droid, with both registers false at the start
+    /// # Arguments
+    /// * `sensors` - The value of each sensor, the bit 0 for `A` to the bit 8 for `I`
+    ///
+    /// # Returns
+    /// True if the droid jumps
+    pub fn eval(&self, sensors: u16) -> bool {
+        let mut registers = [false; 2];
+        for instruction in &self.instructions {
+            let source = match instruction.source {
+                Source::Sensor(sensor) => sensors & (1 << sensor) != 0,
+                Source::Register(register) => registers[register as usize],
+            };
+            let target = &mut registers[instruction.target as usize];
+            *target = match instruction.op {
+                Op::And => source && *target,
+                Op::Or => source || *target,
+                Op::Not => !source,
+            };
+        }
+        registers[Register::J as usize]
+    }
+}
+
+/// Compile an expression into the shortest springscript found
+/// # Errors
+/// If the expression uses sensors the droid cannot see in this mode, or if no script fits in
+/// the 15 instructions
+pub fn compile(expr: &Expr, mode: Mode) -> Result<Script> {
+    let used = expr.sensors();
+    ensure!(
+        used >> mode.sensors() == 0,
+        "The expression '{expr}' uses sensors that cannot be seen in {mode:?} mode"
+    );
+    let variables: Vec<usize> = (0..SENSORS.len())
+        .filter(|s| used & (1 << s) != 0)
+        .collect();
+    let table = |expr: &Expr| -> Vec<bool> {
+        (0..1u16 << variables.len())
+            .map(|index| expr.eval(sensors(&variables, index)))
+            .collect()
+    };
+    let expected = table(expr);
+
+    let forms = [
+        Nnf::new(expr, true),
+        sum_of_products(&variables, &expected, true),
+        sum_of_products(&variables, &expected, false),
+    ];
+    let best = forms
+        .iter()
+        .filter_map(|form| {
+            let mut emitter = Emitter::default();
+            emitter.top(form).ok()?;
+            Some(Script {
+                instructions: emitter.code,
+                mode,
+            })
+        })
+        .filter(|script| {
+            (0..1u16 << variables.len())
+                .all(|index| script.eval(sensors(&variables, index)) == expected[index as usize])
+        })
+        .min_by_key(|script| script.instructions.len())
+        .ok_or_else(|| err!("The expression '{expr}' needs more than two registers"))?;
+    if best.instructions.len() > MAX_INSTRUCTIONS {
+        bail!(
+            "The expression '{expr}' needs {} instructions, more than the {MAX_INSTRUCTIONS} allowed",
+            best.instructions.len()
+        );
+    }
+    Ok(best)
+}
+
+/// The value of the sensors for an index in a truth table over some variables
+fn sensors(variables: &[usize], index: u16) -> u16 {
+    variables
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| index & (1 << bit) != 0)
+        .fold(0, |sensors, (_, variable)| sensors | 1 << variable)
+}
+
+/// An expression where the negations are only on the sensors
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Nnf {
+    Const(bool),
+    /// A sensor, or its negation if false
+    Literal(usize, bool),
+    And(Vec<Nnf>),
+    Or(Vec<Nnf>),
+}
+
+impl Nnf {
+    /// Push the negations of an expression down to the sensors (negating it if not `positive`)
+    fn new(expr: &Expr, positive: bool) -> Self {
+        match expr {
+            Expr::Sensor(sensor) => Self::Literal(*sensor, positive),
+            Expr::Not(inner) => Self::new(inner, !positive),
+            Expr::And(all) => {
+                let all = all.iter().map(|expr| Self::new(expr, positive)).collect();
+                if positive {
+                    Self::and(all)
+                } else {
+                    Self::or(all)
+                }
+            }
+            Expr::Or(any) => {
+                let any = any.iter().map(|expr| Self::new(expr, positive)).collect();
+                if positive {
+                    Self::or(any)
+                } else {
+                    Self::and(any)
+                }
+            }
+        }
+    }
+
+    /// The conjunction of some expressions, simplified
+    fn and(all: Vec<Nnf>) -> Self {
+        Self::simplify(all, true)
+    }
+
+    /// The disjunction of some expressions, simplified
+    fn or(any: Vec<Nnf>) -> Self {
+        Self::simplify(any, false)
+    }
+
+    /// Flatten the nested operations and remove the neutral constants
+    fn simplify(children: Vec<Nnf>, and: bool) -> Self {
+        let mut flat = Vec::new();
+        for child in children {
+            match child {
+                Self::Const(value) if value == and => {}
+                Self::Const(value) => return Self::Const(value),
+                Self::And(inner) if and => flat.extend(inner),
+                Self::Or(inner) if !and => flat.extend(inner),
+                child => flat.push(child),
+            }
+        }
+        match flat.len() {
+            0 => Self::Const(and),
+            1 => flat.pop().unwrap_or(Self::Const(and)),
+            _ if and => Self::And(flat),
+            _ => Self::Or(flat),
+        }
+    }
+
+    /// The negation of this expression
+    fn negate(&self) -> Self {
+        match self {
+            Self::Const(value) => Self::Const(!value),
+            Self::Literal(sensor, positive) => Self::Literal(*sensor, !positive),
+            Self::And(all) => Self::or(all.iter().map(Self::negate).collect()),
+            Self::Or(any) => Self::and(any.iter().map(Self::negate).collect()),
+        }
+    }
+}
+
+/// Minimize a truth table into a sum of products (Quine-McCluskey with a greedy cover)
+/// # Arguments
+/// * `variables` - The sensor of each bit of the indexes of the table
+/// * `table` - The value for each combination of the variables
+/// * `positive` - True to minimize the table, false to minimize its negation and negate it
+fn sum_of_products(variables: &[usize], table: &[bool], positive: bool) -> Nnf {
+    let ones: Vec<u16> = (0..table.len() as u16)
+        .filter(|index| table[*index as usize] == positive)
+        .collect();
+
+    // An implicant is the bits of its literals and the mask of the variables it ignores
+    let mut primes: BTreeSet<(u16, u16)> = BTreeSet::new();
+    let mut current: BTreeSet<(u16, u16)> = ones.iter().map(|one| (*one, 0)).collect();
+    while !current.is_empty() {
+        let mut next = BTreeSet::new();
+        let mut combined = BTreeSet::new();
+        for &(bits, mask) in &current {
+            for variable in 0..variables.len() {
+                let flag = 1 << variable;
+                let other = (bits ^ flag, mask);
+                if mask & flag == 0 && current.contains(&other) {
+                    next.insert((bits & !flag, mask | flag));
+                    combined.insert((bits, mask));
+                    combined.insert(other);
+                }
+            }
+        }
+        primes.extend(current.difference(&combined).copied());
+        current = next;
+    }
+
+    let covers = |(bits, mask): (u16, u16), one: u16| one & !mask == bits;
+    let mut uncovered: BTreeSet<u16> = ones.iter().copied().collect();
+    let mut chosen = Vec::new();
+    while let Some(&one) = uncovered.iter().next() {
+        // Take an essential implicant first, otherwise the one covering the most
+        let best = primes
+            .iter()
+            .copied()
+            .filter(|prime| covers(*prime, one))
+            .max_by_key(|prime| {
+                let essential = primes.iter().filter(|p| covers(**p, one)).count() == 1;
+                let count = uncovered.iter().filter(|o| covers(*prime, **o)).count();
+                (essential, count)
+            })
+            .unwrap_or((one, 0));
+        uncovered.retain(|o| !covers(best, *o));
+        chosen.push(best);
+    }
+
+    let products = chosen
+        .into_iter()
+        .map(|(bits, mask)| {
+            let literals = (0..variables.len())
+                .filter(|variable| mask & (1 << variable) == 0)
+                .map(|variable| Nnf::Literal(variables[variable], bits & (1 << variable) != 0))
+                .collect();
+            Nnf::and(literals)
+        })
+        .collect();
+    let sum = Nnf::or(products);
+    if positive {
+        sum
+    } else {
+        sum.negate()
+    }
+}
+
+/// Generates the instructions, tracking the registers that are still false from the start
+#[derive(Debug, Clone)]
+struct Emitter {
+    code: Vec<Instruction>,
+    /// True for the registers that were never written (they are false)
+    fresh: [bool; 2],
+}
+
+impl Default for Emitter {
+    fn default() -> Self {
+        Self {
+            code: Vec::new(),
+            fresh: [true; 2],
+        }
+    }
+}
+
+impl Emitter {
+    /// Add an instruction
+    fn push(&mut self, op: Op, source: Source, target: Register) {
+        self.fresh[target as usize] = false;
+        self.code.push(Instruction { op, source, target });
+    }
+
+    /// Compute a whole expression in `J`
+    fn top(&mut self, expr: &Nnf) -> Result<()> {
+        match expr {
+            Nnf::Const(false) => Ok(()),
+            Nnf::Const(true) => {
+                self.push(Op::Not, Source::Register(Register::T), Register::J);
+                Ok(())
+            }
+            expr => self.compute(expr, Register::J, true, true),
+        }
+    }
+
+    /// Compute an expression in a register
+    /// # Arguments
+    /// * `expr` - The expression to compute
+    /// * `target` - The register receiving the value
+    /// * `scratch` - True if the other register can be overwritten
+    /// * `negation` - True to also try computing the negation of the expression then negate it
+    fn compute(
+        &mut self,
+        expr: &Nnf,
+        target: Register,
+        scratch: bool,
+        negation: bool,
+    ) -> Result<()> {
+        let mut direct = self.clone();
+        let direct = direct.compute_direct(expr, target, scratch).map(|_| direct);
+        let negated = if negation && matches!(expr, Nnf::And(_) | Nnf::Or(_)) {
+            let mut negated = self.clone();
+            negated
+                .compute(&expr.negate(), target, scratch, false)
+                .map(|_| {
+                    negated.push(Op::Not, Source::Register(target), target);
+                    negated
+                })
+        } else {
+            Err(err!("Not negated"))
+        };
+        *self = match (direct, negated) {
+            (Ok(direct), Ok(negated)) if negated.code.len() < direct.code.len() => negated,
+            (Ok(direct), _) => direct,
+            (Err(_), Ok(negated)) => negated,
+            (Err(error), Err(_)) => return Err(error),
+        };
+        Ok(())
+    }
+
+    /// Compute an expression in a register, without negating it as a whole
+    fn compute_direct(&mut self, expr: &Nnf, target: Register, scratch: bool) -> Result<()> {
+        let other = match target {
+            Register::T => Register::J,
+            Register::J => Register::T,
+        };
+        let (children, op) = match expr {
+            Nnf::Literal(sensor, true) => {
+                if self.fresh[target as usize] {
+                    self.push(Op::Or, Source::Sensor(*sensor), target);
+                } else {
+                    self.push(Op::Not, Source::Sensor(*sensor), target);
+                    self.push(Op::Not, Source::Register(target), target);
+                }
+                return Ok(());
+            }
+            Nnf::Literal(sensor, false) => {
+                self.push(Op::Not, Source::Sensor(*sensor), target);
+                return Ok(());
+            }
+            Nnf::Const(_) => bail!("Constants are only supported as the whole expression"),
+            Nnf::And(all) => (all, Op::And),
+            Nnf::Or(any) => (any, Op::Or),
+        };
+
+        // The operation with the most complex child first, as it is the only one with a scratch
+        // register, each complex child is tried in first
+        let complex: Vec<&Nnf> = children
+            .iter()
+            .filter(|child| !matches!(child, Nnf::Literal(..)))
+            .collect();
+        let literals: Vec<&Nnf> = children
+            .iter()
+            .filter(|child| matches!(child, Nnf::Literal(..)))
+            .collect();
+        let orders: Vec<Vec<&Nnf>> = if complex.is_empty() {
+            let mut order = literals.clone();
+            // A positive literal loads in one instruction in a fresh register, a negative one
+            // always does
+            order.sort_by_key(|child| matches!(child, Nnf::Literal(_, true)));
+            vec![order]
+        } else {
+            (0..complex.len())
+                .map(|first| {
+                    let mut order = vec![complex[first]];
+                    order.extend(
+                        complex
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| *i != first)
+                            .map(|(_, c)| *c),
+                    );
+                    order.extend(literals.iter().copied());
+                    order
+                })
+                .collect()
+        };
+
+        let mut best: Option<Emitter> = None;
+        for order in orders {
+            let mut attempt = self.clone();
+            if attempt.combine(&order, op, target, other, scratch).is_ok()
+                && best
+                    .as_ref()
+                    .is_none_or(|best| attempt.code.len() < best.code.len())
+            {
+                best = Some(attempt);
+            }
+        }
+        *self = best.ok_or_else(|| err!("'{expr:?}' needs more than two registers"))?;
+        Ok(())
+    }
+
+    /// Compute the operation on some children, in this order
+    fn combine(
+        &mut self,
+        children: &[&Nnf],
+        op: Op,
+        target: Register,
+        other: Register,
+        scratch: bool,
+    ) -> Result<()> {
+        let (first, rest) = children.split_first().ok_or_else(|| err!("No operand"))?;
+        self.compute(first, target, scratch, true)?;
+        for child in rest {
+            match child {
+                Nnf::Literal(sensor, true) => self.push(op, Source::Sensor(*sensor), target),
+                Nnf::Literal(sensor, false) if scratch => {
+                    self.push(Op::Not, Source::Sensor(*sensor), other);
+                    self.push(op, Source::Register(other), target);
+                }
+                Nnf::Literal(sensor, false) => {
+                    // x & !s == !(!x | s) and x | !s == !(!x & s)
+                    let inverse = match op {
+                        Op::And => Op::Or,
+                        _ => Op::And,
+                    };
+                    self.push(Op::Not, Source::Register(target), target);
+                    self.push(inverse, Source::Sensor(*sensor), target);
+                    self.push(Op::Not, Source::Register(target), target);
+                }
+                child if scratch => {
+                    self.compute(child, other, false, true)?;
+                    self.push(op, Source::Register(other), target);
+                }
+                _ => bail!("Two complex operands need a third register"),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Expr {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens: Vec<char> = s.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut parser = Parser { tokens, next: 0 };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(token) => Err(err!("Unexpected '{token}' in '{s}'")),
+        }
+    }
+}
+
+/// A recursive descent parser of expressions, `&` binding tighter than `|`
+struct Parser {
+    tokens: Vec<char>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.next).copied()
+    }
+
+    fn or(&mut self) -> Result<Expr> {
+        let mut any = vec![self.and()?];
+        while self.peek() == Some('|') {
+            self.next += 1;
+            any.push(self.and()?);
+        }
+        Ok(if any.len() == 1 {
+            any.remove(0)
+        } else {
+            Expr::Or(any)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr> {
+        let mut all = vec![self.unary()?];
+        while self.peek() == Some('&') {
+            self.next += 1;
+            all.push(self.unary()?);
+        }
+        Ok(if all.len() == 1 {
+            all.remove(0)
+        } else {
+            Expr::And(all)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        let token = self
+            .peek()
+            .ok_or_else(|| err!("Unexpected end of the expression"))?;
+        self.next += 1;
+        match token {
+            '!' => Ok(Expr::Not(Box::new(self.unary()?))),
+            '(' => {
+                let inner = self.or()?;
+                ensure!(self.peek() == Some(')'), "Missing a closing parenthesis");
+                self.next += 1;
+                Ok(inner)
+            }
+            sensor => SENSORS
+                .iter()
+                .position(|name| *name == sensor)
+                .map(Expr::Sensor)
+                .ok_or_else(|| err!("Unknown sensor '{sensor}'")),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let write_all = |f: &mut Formatter<'_>, all: &[Expr], separator: &str| -> FmtResult {
+            for (i, expr) in all.iter().enumerate() {
+                if i > 0 {
+                    write!(f, "{separator}")?;
+                }
+                match expr {
+                    Self::And(_) | Self::Or(_) => write!(f, "({expr})")?,
+                    expr => write!(f, "{expr}")?,
+                }
+            }
+            Ok(())
+        };
+        match self {
+            Self::Sensor(sensor) => write!(f, "{}", SENSORS[*sensor]),
+            Self::Not(inner) => match **inner {
+                Self::And(_) | Self::Or(_) => write!(f, "!({inner})"),
+                _ => write!(f, "!{inner}"),
+            },
+            Self::And(all) => write_all(f, all, " & "),
+            Self::Or(any) => write_all(f, any, " | "),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        let source = match self.source {
+            Source::Sensor(sensor) => SENSORS[sensor],
+            Source::Register(Register::T) => 'T',
+            Source::Register(Register::J) => 'J',
+        };
+        let target = match self.target {
+            Register::T => 'T',
+            Register::J => 'J',
+        };
+        write!(f, "{op} {source} {target}")
+    }
+}
+
+impl Display for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for instruction in &self.instructions {
+            writeln!(f, "{instruction}")?;
+        }
+        match self.mode {
+            Mode::Walk => writeln!(f, "WALK"),
+            Mode::Run => writeln!(f, "RUN"),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::*;
+
+const DATA: &str = include_str!("data.txt");
+
+#[test]
+fn first_part() {
+    let memory = parse(DATA).unwrap().data;
+    let script = springscript(WALK, Mode::Walk, &WALK_HULLS).unwrap();
+    assert_eq!(survey(&memory, &script).unwrap(), 19_352_864);
+}
+
+#[test]
+fn second_part() {
+    let memory = parse(DATA).unwrap().data;
+    let script = springscript(RUN, Mode::Run, &RUN_HULLS).unwrap();
+    assert_eq!(survey(&memory, &script).unwrap(), 1_142_488_337);
+}
+
+#[test]
+fn compile_expressions() {
+    let compiled = |expression: &str, mode| {
+        let expr: springscript::Expr = expression.parse().unwrap();
+        let script = compile(&expr, mode).unwrap();
+        for sensors in 0..1 << mode.sensors() {
+            assert_eq!(script.eval(sensors), expr.eval(sensors), "{expression}");
+        }
+        script.to_string()
+    };
+
+    assert_eq!(
+        compiled(WALK, Mode::Walk),
+        "OR A J\nAND B J\nAND C J\nNOT J J\nAND D J\nWALK\n"
+    );
+    assert_eq!(compiled("!A", Mode::Walk), "NOT A J\nWALK\n");
+    assert_eq!(compiled("A | !A", Mode::Walk), "NOT T J\nWALK\n");
+    assert_eq!(compiled("A & !A", Mode::Run), "RUN\n");
+    assert_eq!(
+        compiled("(A & !B) | (!A & B)", Mode::Walk),
+        "NOT B J\nAND A J\nNOT A T\nAND B T\nOR T J\nWALK\n"
+    );
+    assert_eq!(compiled(RUN, Mode::Run).lines().count(), 9);
+    assert_eq!(
+        "D & !(A & B & C)"
+            .parse::<springscript::Expr>()
+            .unwrap()
+            .to_string(),
+        "D & !(A & B & C)"
+    );
+
+    let parity: springscript::Expr =
+        "(A & !B | !A & B) & (C & !D | !C & D) | (A & B | !A & !B) & (C & D | !C & !D)"
+            .parse()
+            .unwrap();
+    let error = compile(&parity, Mode::Walk).unwrap_err();
+    assert!(
+        error.to_string().contains("more than the 15 allowed"),
+        "{error}"
+    );
+    let error = compile(&"E".parse().unwrap(), Mode::Walk).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The expression 'E' uses sensors that cannot be seen in Walk mode"
+    );
+    assert!("A & (B".parse::<springscript::Expr>().is_err());
+    assert!("A & Z".parse::<springscript::Expr>().is_err());
+}
+
+#[test]
+fn simulate_hulls() {
+    let walk = compile(&WALK.parse().unwrap(), Mode::Walk).unwrap();
+    let run = compile(&RUN.parse().unwrap(), Mode::Run).unwrap();
+    let hull: Hull = "#####.#.##..#.###".parse().unwrap();
+    // After the first jump, the droid lands at 6 and cannot jump over 7 to a hole at 10
+    let fall = hull.simulate(&walk).unwrap_err();
+    assert_eq!(fall.to_string(), "walked into the hole at 7");
+    assert_eq!(hull.simulate(&run), Ok(()));
+
+    let hulls: Vec<Hull> = ["#####...#########", "##.##.###"]
+        .iter()
+        .map(|hull| hull.parse().unwrap())
+        .collect();
+    let jump = compile(&"!A".parse().unwrap(), Mode::Walk).unwrap();
+    assert_eq!(
+        hull::check(&jump, &hulls),
+        Err((
+            &hulls[1],
+            hull::Fall {
+                position: 5,
+                jumped_from: Some(1)
+            }
+        ))
+    );
+    assert_eq!(
+        hulls[1].simulate(&jump).unwrap_err().to_string(),
+        "jumped from 1 into the hole at 5"
+    );
+    assert!("..##".parse::<Hull>().is_err());
+}
@@ -2,10 +2,12 @@ use std::fmt::{Display, Formatter};
 
 use commons::{Result, WrapErr};
 
-use super::int_code::{IntCodeInput, Processor, Status};
+use super::int_code::network::{self, Network, NetworkError, Route, Router};
+use super::int_code::{IntCodeInput, Processor};
 
 pub const TITLE: &str = "Day 23: Category Six";
 const NETWORK_SIZE: usize = 50;
+const NAT_ADDRESS: i64 = 255;
 
 pub fn run(raw: String) -> Result<()> {
     let memory = parse(&raw)?.data;
@@ -25,113 +27,85 @@ fn parse(s: &str) -> Result<IntCodeInput> {
     Ok(s.parse()?)
 }
 
+/// Build the network of computers, each one starting with its address as input
+fn build_network(memory: &[i64]) -> Network<Packet> {
+    Network::new(
+        (0..NETWORK_SIZE).map(|port| Processor::with_initial_inputs(memory, &[port as i64])),
+    )
+}
+
 /// Runs the network for first part.
 /// There is no NAT to handle packets to 255, and the first packet for it is returned.
-fn run_until_nat_packet(memory: &[i64]) -> Option<Packet> {
-    let mut computers: Vec<Processor> = (0..NETWORK_SIZE)
-        .map(|port| Processor::with_initial_inputs(memory, &[port as i64]))
-        .collect();
-
-    loop {
-        for n in 0..NETWORK_SIZE {
-            match collect_packet(&mut computers[n]) {
-                Ok(packet) => {
-                    if let Err(not_sent) = send_packet(&mut computers, packet) {
-                        return if not_sent.destination == 255 {
-                            Some(not_sent)
-                        } else {
-                            println!("Error : unhandled packet {not_sent}");
-                            None
-                        };
-                    }
-                }
-                Err(Status::RequireInput) => computers[n].write_int(-1),
-                Err(_) => return None,
-            }
-        }
-    }
+fn run_until_nat_packet(memory: &[i64]) -> Result<Packet, NetworkError> {
+    build_network(memory).run(&mut FirstNatPacket)
 }
 
 /// Runs the network for the second part.
 /// There is a NAT receiving packets for 255, and it will wake up 0 when network is idle.
-fn run_until_duplicate_wakeup(memory: &[i64]) -> Option<Packet> {
-    // We add 1 to a computer idle count when it asks for a packet but does not receive any.
-    // When the computer produces a packet, its idle count is reset.
-    // If the idle count is 2 or higher we take it to mean the computer is blocking on IO
-    let mut idle_cycles: [u16; NETWORK_SIZE] = [0; NETWORK_SIZE];
-    // The NAT sends the last packet it received to 0 when all states are idle
-    let mut nat_buffer: Option<Packet> = None;
-    // We want to collect the first Y value sent twice in a row by the NAT
-    let mut last_wakeup: Option<Packet> = None;
-
-    let mut computers: Vec<Processor> = (0..NETWORK_SIZE)
-        .map(|port| Processor::with_initial_inputs(memory, &[port as i64]))
-        .collect();
-
-    loop {
-        for n in 0..NETWORK_SIZE {
-            match collect_packet(&mut computers[n]) {
-                Ok(packet) => {
-                    idle_cycles[n] = 0; // The computer sent a packet, so it is no longer idle.
-                    if let Err(mut not_sent) = send_packet(&mut computers, packet) {
-                        if not_sent.destination == 255 {
-                            not_sent.destination = 0;
-                            nat_buffer = Some(not_sent);
-                        } else {
-                            println!("Error : unhandled packet {not_sent}");
-                            return None;
-                        }
-                    };
-                }
-                Err(Status::RequireInput) => {
-                    idle_cycles[n] += 1; // This computer is considered idle for one cycle.
-                    computers[n].write_int(-1)
-                }
-                Err(_) => return None,
-            }
-        }
+fn run_until_duplicate_wakeup(memory: &[i64]) -> Result<Packet, NetworkError> {
+    build_network(memory).run(&mut Nat {
+        buffer: None,
+        last_wakeup: None,
+    })
+}
 
-        // Enter the NAT if the network is idle, i.e. all idles count are higher than 2
-        if idle_cycles.iter().all(|&x| x >= 2) {
-            // Send the last buffered packet to the computer 0
-            let packet = nat_buffer.take()?;
-            let recipient = computers.first_mut()?;
-            recipient.write_int(packet.x);
-            recipient.write_int(packet.y);
-            println!("Wake-up ! {}", &packet);
-
-            // Check if the previous one sent had the same Y, if it is we can return
-            if let Some(previous) = last_wakeup.take() {
-                if packet.y == previous.y {
-                    return Some(packet);
-                }
-            }
+/// Route packets to their destination, stopping on the first one sent to the NAT
+struct FirstNatPacket;
 
-            // Set the last wake-up to be the packet we just sent
-            last_wakeup = Some(packet);
+impl Router for FirstNatPacket {
+    type Packet = Packet;
+    type Output = Packet;
+
+    fn route(&mut self, _: usize, packet: Packet) -> Route<Packet, Packet> {
+        match packet.destination {
+            NAT_ADDRESS => Route::Stop(packet),
+            port => Route::Deliver(port as usize, packet),
         }
     }
+
+    fn empty_input(&self) -> Option<i64> {
+        Some(-1)
+    }
 }
 
-/// Collect one packet from a Processor as an Ok, or a blocking Status as an Err
-fn collect_packet(computer: &mut Processor) -> Result<Packet, Status> {
-    let destination = computer.read_next()?;
-    let x = computer.read_next()?;
-    let y = computer.read_next()?;
-    Ok(Packet::new((destination, x, y)))
+/// Route packets to their destination, buffering the ones for the NAT to wake up 0 when idle
+struct Nat {
+    /// The NAT sends the last packet it received to 0 when all computers are idle
+    buffer: Option<Packet>,
+    /// We want to collect the first Y value sent twice in a row by the NAT
+    last_wakeup: Option<Packet>,
 }
 
-/// Send a packet to one of the computers as an Ok(()) or return it in an Err if it can't be sent
-fn send_packet(computers: &mut [Processor], packet: Packet) -> Result<(), Packet> {
-    let port = packet.destination;
-    if 0 <= port && port < NETWORK_SIZE as i64 {
-        let port = port as usize;
-        let recipient = &mut computers[port];
-        recipient.write_int(packet.x);
-        recipient.write_int(packet.y);
-        Ok(())
-    } else {
-        Err(packet)
+impl Router for Nat {
+    type Packet = Packet;
+    type Output = Packet;
+
+    fn route(&mut self, _: usize, mut packet: Packet) -> Route<Packet, Packet> {
+        match packet.destination {
+            NAT_ADDRESS => {
+                packet.destination = 0;
+                self.buffer = Some(packet);
+                Route::Discard
+            }
+            port => Route::Deliver(port as usize, packet),
+        }
+    }
+
+    fn on_idle(&mut self) -> Route<Packet, Packet> {
+        let Some(packet) = self.buffer.take() else {
+            return Route::Discard;
+        };
+        println!("Wake-up ! {}", &packet);
+
+        // Check if the previous one sent had the same Y, if it is we can return
+        match self.last_wakeup.replace(packet.clone()) {
+            Some(previous) if previous.y == packet.y => Route::Stop(packet),
+            _ => Route::Deliver(0, packet),
+        }
+    }
+
+    fn empty_input(&self) -> Option<i64> {
+        Some(-1)
     }
 }
 
@@ -159,5 +133,18 @@ impl Packet {
     }
 }
 
+impl network::Packet for Packet {
+    const SIZE: usize = 3;
+
+    fn from_outputs(outputs: &[i64]) -> Self {
+        Self::new((outputs[0], outputs[1], outputs[2]))
+    }
+
+    fn write_to(&self, processor: &mut Processor) {
+        processor.write_int(self.x);
+        processor.write_int(self.y);
+    }
+}
+
 #[cfg(test)]
 mod tests;
@@ -5,6 +5,8 @@ use itertools::Itertools;
 use commons::CLEAR_COMMAND;
 use commons::{err, Result};
 
+use super::int_code::console::Console;
+use super::int_code::io::Ascii;
 use super::int_code::{IntCodeInput, Processor};
 
 mod ship;
@@ -62,37 +64,20 @@ struct GameState {
     to_checkpoint: bool,
 }
 
-/// You can play the game by typing in the console.
+/// You can play the game by typing in the console (type :help for the console commands).
 fn play_manually(memory: &[i64]) {
-    fn normalize_new_line(line: &mut String) {
-        if line.ends_with("\r\n") {
-            line.truncate(line.len() - 2);
-            line.push('\n');
-        }
-    }
-
     println!("{CLEAR_COMMAND}");
     let mut processor: Processor = memory.into();
-    processor.run_with_ascii_callbacks(
-        0,
-        |_| {
-            let mut line: String = String::new();
-            std::io::stdin().read_line(&mut line).unwrap();
-            normalize_new_line(&mut line);
-            Some(line)
-        },
-        |_, line| {
-            print!("{line}");
-            Ok(())
-        },
-    );
+    if let Err(err) = processor.run_with_io(&mut Ascii::new(Console::stdio())) {
+        println!("Error : {err}");
+    }
 }
 
 /// Play the game until we reach the end
 ///
 /// This is overly complex but it should work
 fn auto_play(memory: &[i64]) -> Option<u64> {
-    let (_, (_, last_state)) = Processor::new(memory).run_with_ascii_callbacks(
+    let (status, (_, last_state)) = Processor::new(memory).run_with_ascii_callbacks(
         (
             false,
             GameState {
@@ -111,6 +96,10 @@ fn auto_play(memory: &[i64]) -> Option<u64> {
             Ok(())
         },
     );
+    if let Err(error) = status {
+        println!("Error : {error}");
+        return None;
+    }
 
     if let Some(message) = last_state.buffer.split("\n\n").last() {
         println!("{message}");
@@ -0,0 +1,227 @@
+//! An interactive console to play with any ascii IntCode program
+//!
+//! Lines typed in the console are sent to the program, except for the console commands:
+//! - `:help` - Display the available commands
+//! - `:history` - Display all the lines sent to the program
+//! - `:redo [N]` - Send again the line N of the history (by default the last one)
+//! - `:save <FILE>` - Save the history in a file, it can then be replayed as a script
+//! - `:transcript <FILE>` - Save everything that happened in the console in a file
+//! - `:quit` - Stop the program
+//!
+//! A script is a file of lines to send before reading the console input.
+//! Empty lines and lines starting with `#` are ignored, console commands can be used.
+//! ```
+//! use std::io::Cursor;
+//! use advent_of_code_2019::int_code::console::Console;
+//! use advent_of_code_2019::int_code::io::Ascii;
+//! use advent_of_code_2019::int_code::{Processor, Status};
+//!
+//! // This program echoes every input as output
+//! let mut cpu = Processor::new(&[3, 3, 104, -1, 1106, 0, 0, 99]);
+//! let mut console = Console::new(Cursor::new("Hello\n:redo\n"), Vec::new());
+//! assert_eq!(cpu.run_with_io(&mut Ascii::new(&mut console)), Ok(Status::RequireInput));
+//! assert_eq!(console.transcript(), "Hello\nHello\n:redo\nHello\nHello\n");
+//! ```
+
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Stdin, Stdout, Write};
+use std::path::Path;
+
+use commons::{Result, WrapErr};
+
+use super::io::AsciiIo;
+use super::Status;
+
+const HELP: &str = "Console commands:
+  :help               Display this help
+  :history            Display all the lines sent to the program
+  :redo [N]           Send again the line N of the history (by default the last one)
+  :save <FILE>        Save the history in a file, it can then be replayed as a script
+  :transcript <FILE>  Save everything that happened in the console in a file
+  :quit               Stop the program
+";
+
+/// An ascii device reading lines from a script then from an input, keeping a history of all
+#[derive(Debug)]
+pub struct Console<R, W> {
+    /// The interactive input of the console
+    input: R,
+    /// The output of the console
+    output: W,
+    /// The lines of the script that were not replayed yet
+    script: VecDeque<String>,
+    /// All the lines sent to the program
+    history: Vec<String>,
+    /// Everything that happened in the console
+    transcript: String,
+}
+
+impl Console<BufReader<Stdin>, Stdout> {
+    /// A console reading from the standard input and writing to the standard output
+    pub fn stdio() -> Self {
+        Self::new(BufReader::new(std::io::stdin()), std::io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> Console<R, W> {
+    /// Build a new console
+    /// # Arguments
+    /// * `input` - The interactive input of the console
+    /// * `output` - The output of the console
+    pub fn new(input: R, output: W) -> Self {
+        Self {
+            input,
+            output,
+            script: VecDeque::new(),
+            history: Vec::new(),
+            transcript: String::new(),
+        }
+    }
+
+    /// Add a script to replay before reading the interactive input
+    /// # Arguments
+    /// * `script` - The lines of the script (empty and `#` lines are ignored)
+    pub fn with_script(mut self, script: &str) -> Self {
+        self.script.extend(
+            script
+                .lines()
+                .map(str::trim_end)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(String::from),
+        );
+        self
+    }
+
+    /// Add the script in this file to replay before reading the interactive input
+    pub fn with_script_file(self, path: &Path) -> Result<Self> {
+        let script = commons::problem::load(path.to_path_buf())?;
+        Ok(self.with_script(&script))
+    }
+
+    /// All the lines sent to the program so far
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Everything that happened in the console so far
+    pub fn transcript(&self) -> &str {
+        &self.transcript
+    }
+
+    /// Save the history in a file, so that it can be replayed as a script
+    pub fn save_history(&self, path: &Path) -> Result<()> {
+        let mut script = self.history.join("\n");
+        script.push('\n');
+        std::fs::write(path, script).wrap_err_with(|| format!("Can't save history to {path:?}"))
+    }
+
+    /// Save the transcript in a file
+    pub fn save_transcript(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, &self.transcript)
+            .wrap_err_with(|| format!("Can't save transcript to {path:?}"))
+    }
+
+    /// Read the next line, from the script first and then from the interactive input
+    /// # Returns
+    /// The line without its terminating new line, None if there is nothing left to read
+    fn next_line(&mut self) -> Option<String> {
+        if let Some(line) = self.script.pop_front() {
+            self.print(&format!("{line}\n"));
+            return Some(line);
+        }
+
+        let mut line = String::new();
+        match self.input.read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                let line = line.trim_end_matches(['\n', '\r']).to_owned();
+                self.transcript.push_str(&line);
+                self.transcript.push('\n');
+                Some(line)
+            }
+        }
+    }
+
+    /// Execute a console command
+    /// # Returns
+    /// Ok(Some(line)) for a line to send to the program, Ok(None) if nothing has to be sent.
+    /// Err(status) if the program should be stopped.
+    fn command(&mut self, command: &str) -> Result<Option<String>, Status> {
+        let (name, argument) = match command.split_once(' ') {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+
+        let message = match name {
+            "help" => HELP.to_owned(),
+            "history" => self
+                .history
+                .iter()
+                .enumerate()
+                .map(|(i, line)| format!("{:>4}  {line}\n", i + 1))
+                .collect(),
+            "redo" => {
+                let index = match argument {
+                    "" => Some(self.history.len()),
+                    n => n.parse::<usize>().ok(),
+                };
+                match index.and_then(|i| self.history.get(i.checked_sub(1)?)) {
+                    Some(line) => return Ok(Some(line.clone())),
+                    None => format!("No line '{argument}' in the history\n"),
+                }
+            }
+            "save" => match self.save_history(Path::new(argument)) {
+                Ok(()) => format!("History saved to {argument}\n"),
+                Err(err) => format!("{err}\n"),
+            },
+            "transcript" => match self.save_transcript(Path::new(argument)) {
+                Ok(()) => format!("Transcript saved to {argument}\n"),
+                Err(err) => format!("{err}\n"),
+            },
+            "quit" => return Err(Status::Halted),
+            _ => format!("Unknown command ':{name}', type :help for the list of commands\n"),
+        };
+
+        self.print(&message);
+        Ok(None)
+    }
+
+    /// Print a message in the console output and the transcript
+    fn print(&mut self, message: &str) {
+        self.transcript.push_str(message);
+        // The console output is best effort: a broken output should not stop the program
+        let _ = self
+            .output
+            .write_all(message.as_bytes())
+            .and_then(|_| self.output.flush());
+    }
+}
+
+impl<R: BufRead, W: Write> AsciiIo for Console<R, W> {
+    fn input_line(&mut self) -> Result<String, Status> {
+        loop {
+            let line = self.next_line().ok_or(Status::RequireInput)?;
+            let line = match line.strip_prefix(':') {
+                Some(command) => match self.command(command) {
+                    Ok(Some(line)) => {
+                        self.print(&format!("{line}\n"));
+                        line
+                    }
+                    Ok(None) => continue,
+                    Err(status) => return Err(status),
+                },
+                None => line,
+            };
+
+            let mut input = line.clone();
+            input.push('\n');
+            self.history.push(line);
+            return Ok(input);
+        }
+    }
+
+    fn output_line(&mut self, line: &str) -> Result<(), Status> {
+        self.print(line);
+        Ok(())
+    }
+}
@@ -0,0 +1,290 @@
+//! Devices that can be plugged in the inputs and outputs of an IntCode processor
+//! # Basic usage
+//! - Iterate over the outputs of a processor fed from an input iterator
+//! ```
+//! use advent_of_code_2019::int_code::Processor;
+//!
+//! // This program echoes every input as output
+//! let mut cpu = Processor::new(&[3, 3, 104, -1, 1106, 0, 0, 99]);
+//! let outputs: Vec<i64> = cpu.outputs([1, 2, 3]).collect();
+//! assert_eq!(outputs, vec![1, 2, 3]);
+//! ```
+//! - Plug a device implementing `IntCodeIo` (or `AsciiIo` through the `Ascii` adapter)
+//! ```
+//! use advent_of_code_2019::int_code::io::{Ascii, AsciiIo};
+//! use advent_of_code_2019::int_code::{Processor, Status};
+//!
+//! /// Sends a single line, and collects all the lines written back
+//! struct Parrot(Option<String>, Vec<String>);
+//!
+//! impl AsciiIo for Parrot {
+//!     fn input_line(&mut self) -> Result<String, Status> {
+//!         self.0.take().ok_or(Status::RequireInput)
+//!     }
+//!
+//!     fn output_line(&mut self, line: &str) -> Result<(), Status> {
+//!         self.1.push(line.to_owned());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! let mut cpu = Processor::new(&[3, 3, 104, -1, 1106, 0, 0, 99]);
+//! let mut terminal = Ascii::new(Parrot(Some("Hello\n".into()), vec![]));
+//! assert_eq!(cpu.run_with_io(&mut terminal), Ok(Status::RequireInput));
+//! assert_eq!(terminal.into_inner().1, vec!["Hello\n"]);
+//! ```
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write as _};
+
+use super::{IntCodeError, Processor, Status};
+
+/// A device plugged in the inputs and outputs of a processor
+pub trait IntCodeIo {
+    /// The next input of the processor, or an Err to stop it with that status
+    /// (`Status::RequireInput` to stop it until more input is available)
+    fn input(&mut self) -> Result<i64, Status>;
+
+    /// Receive the next output of the processor, or return an Err to stop it with that status
+    fn output(&mut self, value: i64) -> Result<(), Status>;
+
+    /// Called once the processor halted, to finish any pending output
+    fn halted(&mut self) {}
+}
+
+/// A device exchanging lines of ascii text with a processor, see `Ascii` to plug it
+pub trait AsciiIo {
+    /// The next line of input for the processor, or an Err to stop it with that status
+    /// (`Status::RequireInput` to stop it until more input is available)
+    fn input_line(&mut self) -> Result<String, Status>;
+
+    /// Receive the next line of output of the processor (with its terminating character).
+    /// Return an Err to stop the processor with that status.
+    fn output_line(&mut self, line: &str) -> Result<(), Status>;
+}
+
+impl<D: IntCodeIo> IntCodeIo for &mut D {
+    fn input(&mut self) -> Result<i64, Status> {
+        (**self).input()
+    }
+
+    fn output(&mut self, value: i64) -> Result<(), Status> {
+        (**self).output(value)
+    }
+
+    fn halted(&mut self) {
+        (**self).halted()
+    }
+}
+
+impl<D: AsciiIo> AsciiIo for &mut D {
+    fn input_line(&mut self) -> Result<String, Status> {
+        (**self).input_line()
+    }
+
+    fn output_line(&mut self, line: &str) -> Result<(), Status> {
+        (**self).output_line(line)
+    }
+}
+
+/// Adapts an ascii device into an int device
+/// # Behaviour
+/// Output characters are buffered until:
+/// - An ascii control (new line in most cases) is seen
+/// - A non ascii i64 is read (in that case the i64 is concatenated to the line)
+/// - The processor requires input (to flush any prompt)
+/// - The processor halts (to flush a last line without new line)
+#[derive(Debug, Clone, Default)]
+pub struct Ascii<D> {
+    /// The device receiving the lines of text
+    device: D,
+    /// The output line currently being built
+    line: String,
+    /// The input bytes not yet read by the processor
+    pending: VecDeque<u8>,
+}
+
+impl<D: AsciiIo> Ascii<D> {
+    /// Wrap an ascii device
+    pub fn new(device: D) -> Self {
+        Self {
+            device,
+            line: String::with_capacity(10), // 10 is a good starting capacity.
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Get back the device
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Send the current line to the device if it is not empty
+    fn flush(&mut self) -> Result<(), Status> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        let result = self.device.output_line(&self.line);
+        self.line.clear();
+        result
+    }
+}
+
+impl<D: AsciiIo> IntCodeIo for Ascii<D> {
+    fn input(&mut self) -> Result<i64, Status> {
+        if self.pending.is_empty() {
+            self.flush()?;
+            self.pending.extend(self.device.input_line()?.bytes());
+        }
+        self.pending
+            .pop_front()
+            .map(i64::from)
+            .ok_or(Status::RequireInput)
+    }
+
+    fn output(&mut self, value: i64) -> Result<(), Status> {
+        match u8::try_from(value) {
+            Ok(code) => {
+                let c = char::from(code);
+                self.line.push(c);
+                if c.is_ascii_control() {
+                    self.flush()?;
+                }
+            }
+            Err(_) => {
+                writeln!(self.line, "\n{value}").expect("written to string");
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn halted(&mut self) {
+        // The processor is already halted, the status requested by the device does not matter
+        let _ = self.flush();
+    }
+}
+
+/// An ascii device that reads lines from the standard input and prints to the standard output
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Terminal;
+
+impl AsciiIo for Terminal {
+    fn input_line(&mut self) -> Result<String, Status> {
+        let mut line = String::new();
+        let read = std::io::stdin().lock().read_line(&mut line);
+        if read.is_err() || line.is_empty() {
+            return Err(Status::RequireInput); // End of the input
+        }
+        if line.ends_with("\r\n") {
+            line.truncate(line.len() - 2);
+            line.push('\n');
+        }
+        Ok(line)
+    }
+
+    fn output_line(&mut self, line: &str) -> Result<(), Status> {
+        let mut stdout = std::io::stdout().lock();
+        stdout
+            .write_all(line.as_bytes())
+            .and_then(|_| stdout.flush())
+            .map_err(|_| Status::Halted)
+    }
+}
+
+/// An int device built from two callbacks sharing a state
+pub(super) struct Callbacks<State, I, O> {
+    pub(super) state: State,
+    pub(super) when_input: I,
+    pub(super) on_output: O,
+}
+
+impl<State, I, O> IntCodeIo for Callbacks<State, I, O>
+where
+    I: FnMut(&mut State) -> Option<i64>,
+    O: FnMut(&mut State, i64) -> Result<(), Status>,
+{
+    fn input(&mut self) -> Result<i64, Status> {
+        (self.when_input)(&mut self.state).ok_or(Status::RequireInput)
+    }
+
+    fn output(&mut self, value: i64) -> Result<(), Status> {
+        (self.on_output)(&mut self.state, value)
+    }
+}
+
+/// An ascii device built from two callbacks sharing a state
+pub(super) struct AsciiCallbacks<State, I, O> {
+    pub(super) state: State,
+    pub(super) when_input: I,
+    pub(super) on_output: O,
+}
+
+impl<State, I, O> AsciiIo for AsciiCallbacks<State, I, O>
+where
+    I: FnMut(&mut State) -> Option<String>,
+    O: FnMut(&mut State, &str) -> Result<(), Status>,
+{
+    fn input_line(&mut self) -> Result<String, Status> {
+        (self.when_input)(&mut self.state).ok_or(Status::RequireInput)
+    }
+
+    fn output_line(&mut self, line: &str) -> Result<(), Status> {
+        (self.on_output)(&mut self.state, line)
+    }
+}
+
+/// An iterator over the outputs of a processor, fed from an iterator of inputs
+///
+/// It ends when the processor halts, fails or requires input after the inputs are exhausted.
+/// The reason it ended is then available with `stopped`.
+#[derive(Debug)]
+pub struct Outputs<'a, I> {
+    /// The processor producing the outputs
+    processor: &'a mut Processor,
+    /// The inputs to feed the processor with
+    inputs: I,
+    /// The reason the processor stopped, if it did
+    stopped: Option<Result<Status, IntCodeError>>,
+}
+
+impl<'a, I: Iterator<Item = i64>> Outputs<'a, I> {
+    pub(super) fn new(processor: &'a mut Processor, inputs: I) -> Self {
+        Self {
+            processor,
+            inputs,
+            stopped: None,
+        }
+    }
+
+    /// The reason the processor stopped producing outputs, None if it has not stopped yet
+    pub fn stopped(&self) -> Option<&Result<Status, IntCodeError>> {
+        self.stopped.as_ref()
+    }
+}
+
+impl<'a, I: Iterator<Item = i64>> Iterator for Outputs<'a, I> {
+    type Item = i64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stopped.is_some() {
+            return None;
+        }
+        loop {
+            match self.processor.run() {
+                Ok(Status::WithOutput(out)) => return Some(out),
+                Ok(Status::RequireInput) => match self.inputs.next() {
+                    Some(input) => self.processor.write_int(input),
+                    None => break,
+                },
+                stopped => {
+                    self.stopped = Some(stopped);
+                    return None;
+                }
+            }
+        }
+        self.stopped = Some(Ok(Status::RequireInput));
+        None
+    }
+}
@@ -14,7 +14,8 @@
 //! cpu.write_int(5);
 //!
 //! // Run the Processor and collect its next output as the Ok variant of its output
-//! // Return Err(status) if no output was produced before it blocked on status.
+//! // Return Err(ReadError::Blocked(status)) if no output was produced before it blocked on status
+//! // or Err(ReadError::Failed(error)) if it failed.
 //! let out = cpu.read_next().expect("This program should output something");
 //! println!("{}", out); // 5, as this specific program echoes every input as output
 //!
@@ -27,6 +28,16 @@
 //! println!("{:?}", out); // [4, 3]
 //! ```
 //!
+//! # Engines
+//! By default instructions are decoded once and cached until the program writes over them.
+//! The original interpreter, that decodes each instruction on every cycle, is still available:
+//! ```
+//! use advent_of_code_2019::int_code::{Engine, Processor, Status};
+//!
+//! let mut cpu = Processor::new(&[104, 5, 99]).with_engine(Engine::Interpreted);
+//! assert_eq!(cpu.run(), Ok(Status::WithOutput(5)));
+//! ```
+//!
 
 use std::convert::TryInto;
 use std::{
@@ -34,8 +45,16 @@ use std::{
     convert::TryFrom,
     error::Error,
     fmt::{Display, Formatter},
+    rc::Rc,
 };
 
+use commons::machine::{Machine, Step};
+use io::{Ascii, AsciiCallbacks, Callbacks, IntCodeIo, Outputs};
+
+pub mod console;
+pub mod io;
+pub mod network;
+
 /// The type to use when parsing int code inputs
 pub type IntCodeInput = commons::parse::CommaSep<i64>;
 
@@ -50,28 +69,66 @@ pub enum Status {
     WithOutput(i64),
 }
 
+/// The strategy used by a Processor to execute its instructions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Decode the opcode and modes of an instruction each time it is executed.
+    Interpreted,
+    /// Decode each instruction once and cache it until the program writes over it.
+    #[default]
+    PreDecoded,
+}
+
 /// Represents the state of an int_code processor commonly used in many problems of AOC2019
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Processor {
     /// The underlying memory of the processor
     memory: Vec<i64>,
+    /// The memory the processor started with, restored when it is reset
+    initial: Rc<[i64]>,
     /// The instruction pointer
     current: i64,
     /// The offset for the Relative writes/read
     relative_offset: i64,
     /// The queue containing all non-yet-read inputs for the program
     input_queue: VecDeque<i64>,
+    /// The strategy used to execute instructions
+    engine: Engine,
+    /// The instructions already decoded by the PreDecoded engine, indexed by address
+    decoded: Vec<Option<Instruction>>,
 }
 
 /// An error for the whole IntCode processor.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum IntCodeError {
-    InvalidIndexRead,
-    InvalidIndexWrite,
-    InvalidInstruction,
+    /// An instruction read from a negative index
+    InvalidIndexRead {
+        index: i64,
+        fault: Fault,
+    },
+    /// An instruction wrote to a negative index
+    InvalidIndexWrite {
+        index: i64,
+        fault: Fault,
+    },
+    /// The opcode or one of the modes of an instruction is unknown
+    InvalidInstruction {
+        fault: Fault,
+    },
     Other(String),
 }
 
+/// The state of the processor when an instruction failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fault {
+    /// The address of the failing instruction (the instruction pointer)
+    pub address: i64,
+    /// The raw value of the failing instruction
+    pub instruction: i64,
+    /// The offset for the Relative writes/read
+    pub relative_base: i64,
+}
+
 impl IntCodeError {
     pub fn new(msg: &str) -> Self {
         IntCodeError::Other(msg.into())
@@ -82,13 +139,52 @@ impl Error for IntCodeError {}
 
 impl Display for IntCodeError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let error = match self {
-            IntCodeError::InvalidIndexRead => "Cannot read from an invalid index",
-            IntCodeError::InvalidIndexWrite => "Cannot write to an invalid index",
-            IntCodeError::InvalidInstruction => "Current instruction is invalid",
-            IntCodeError::Other(msg) => msg.as_str(),
-        };
-        error.fmt(f)
+        match self {
+            IntCodeError::InvalidIndexRead { index, fault } => {
+                write!(f, "Cannot read from the invalid index {index} {fault}")
+            }
+            IntCodeError::InvalidIndexWrite { index, fault } => {
+                write!(f, "Cannot write to the invalid index {index} {fault}")
+            }
+            IntCodeError::InvalidInstruction { fault } => write!(f, "Invalid instruction {fault}"),
+            IntCodeError::Other(msg) => msg.fmt(f),
+        }
+    }
+}
+
+impl Display for Fault {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "(instruction {} at address {}, relative base {})",
+            self.instruction, self.address, self.relative_base
+        )
+    }
+}
+
+/// The reason a processor did not produce the output that was read from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadError {
+    /// The processor blocked (halted or requires input) before producing an output
+    Blocked(Status),
+    /// The processor failed before producing an output
+    Failed(IntCodeError),
+}
+
+impl From<IntCodeError> for ReadError {
+    fn from(error: IntCodeError) -> Self {
+        ReadError::Failed(error)
+    }
+}
+
+impl Error for ReadError {}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReadError::Blocked(status) => write!(f, "Blocked before any output: {status:?}"),
+            ReadError::Failed(error) => error.fmt(f),
+        }
     }
 }
 
@@ -99,12 +195,24 @@ impl Processor {
     pub fn new(initial_state: &[i64]) -> Self {
         Self {
             memory: initial_state.into(),
+            initial: initial_state.into(),
             current: 0,
             relative_offset: 0,
             input_queue: VecDeque::new(),
+            engine: Engine::default(),
+            decoded: Vec::new(),
         }
     }
 
+    /// Change the strategy used by this Processor to execute its instructions.
+    /// # Arguments
+    /// * `engine` - The engine to use from now on
+    pub fn with_engine(mut self, engine: Engine) -> Self {
+        self.engine = engine;
+        self.decoded.clear();
+        self
+    }
+
     /// Build a new Processor from an initial state and some starting data.
     /// # Arguments
     /// * `initial_state` - A slice of ints to be used as the starting state of the program
@@ -120,68 +228,87 @@ impl Processor {
     /// Run the Processor until it hits a block (halt, no input left, or produced output)
     pub fn run(&mut self) -> Result<Status, IntCodeError> {
         loop {
-            let instruction = self.parse_inst()?;
-            let (a, b, c) = instruction.modes;
-            match instruction.code {
-                OpCode::Add | OpCode::Mul | OpCode::Less | OpCode::Equals => {
-                    let first = self.get(1, a)?;
-                    let second = self.get(2, b)?;
-                    let result = match instruction.code {
-                        OpCode::Add => first + second,
-                        OpCode::Mul => first * second,
-                        OpCode::Less => i64::from(first < second),
-                        OpCode::Equals => i64::from(first == second),
-                        _ => unreachable!(),
-                    };
-                    self.set(3, result, c)?;
-                    self.current += 4;
-                }
-                OpCode::TrueJump | OpCode::FalseJump => {
-                    let first = self.get(1, a)?;
-                    let second = self.get(2, b)?;
-                    let is_jump = match instruction.code {
-                        OpCode::TrueJump => first != 0,
-                        OpCode::FalseJump => first == 0,
-                        _ => unreachable!(),
-                    };
-                    if is_jump {
-                        self.current = second;
-                    } else {
-                        self.current += 3;
-                    }
-                }
-                OpCode::Offset => {
-                    let first = self.get(1, a)?;
-                    self.relative_offset += first;
-                    self.current += 2;
+            if let Some(status) = self.execute()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Execute the next instruction of the Processor
+    /// # Returns
+    /// The status if the instruction blocked (halt, no input left, or produced output)
+    fn execute(&mut self) -> Result<Option<Status>, IntCodeError> {
+        let instruction = match self.engine {
+            Engine::Interpreted => self.parse_inst()?,
+            Engine::PreDecoded => self.decode()?,
+        };
+        let (a, b, c) = instruction.modes;
+        match instruction.code {
+            OpCode::Add | OpCode::Mul | OpCode::Less | OpCode::Equals => {
+                let first = self.get(1, a)?;
+                let second = self.get(2, b)?;
+                let result = match instruction.code {
+                    OpCode::Add => first + second,
+                    OpCode::Mul => first * second,
+                    OpCode::Less => i64::from(first < second),
+                    OpCode::Equals => i64::from(first == second),
+                    _ => unreachable!(),
+                };
+                self.set(3, result, c)?;
+                self.current += 4;
+            }
+            OpCode::TrueJump | OpCode::FalseJump => {
+                let first = self.get(1, a)?;
+                let second = self.get(2, b)?;
+                let is_jump = match instruction.code {
+                    OpCode::TrueJump => first != 0,
+                    OpCode::FalseJump => first == 0,
+                    _ => unreachable!(),
+                };
+                if is_jump {
+                    self.current = second;
+                } else {
+                    self.current += 3;
                 }
-                OpCode::Input => match self.input_queue.pop_front() {
-                    Some(input) => {
-                        self.set(1, input, a)?;
-                        self.current += 2;
-                    }
-                    None => return Ok(Status::RequireInput),
-                },
-                OpCode::Output => {
-                    let output = self.get(1, a)?;
+            }
+            OpCode::Offset => {
+                let first = self.get(1, a)?;
+                self.relative_offset += first;
+                self.current += 2;
+            }
+            OpCode::Input => match self.input_queue.pop_front() {
+                Some(input) => {
+                    self.set(1, input, a)?;
                     self.current += 2;
-                    return Ok(Status::WithOutput(output));
                 }
-                OpCode::Halt => return Ok(Status::Halted),
+                None => return Ok(Some(Status::RequireInput)),
+            },
+            OpCode::Output => {
+                let output = self.get(1, a)?;
+                self.current += 2;
+                return Ok(Some(Status::WithOutput(output)));
             }
+            OpCode::Halt => return Ok(Some(Status::Halted)),
         }
+        Ok(None)
     }
 
-    /// Run the processor, collecting the next output or returning the first block
-    pub fn read_next(&mut self) -> Result<i64, Status> {
-        match self.run().unwrap_or(Status::Halted) {
+    /// Run the processor, collecting the next output or returning the first block or error
+    pub fn read_next(&mut self) -> Result<i64, ReadError> {
+        match self.run()? {
             Status::WithOutput(out) => Ok(out),
-            blocked => Err(blocked),
+            blocked => Err(ReadError::Blocked(blocked)),
         }
     }
 
     /// Read multiple values from the processor into the destination slice.
-    pub fn read_next_array(&mut self, dest: &mut [i64], limit: usize) -> (usize, Option<Status>) {
+    /// # Returns
+    /// The number of values read, and the reason it stopped reading before the limit if any
+    pub fn read_next_array(
+        &mut self,
+        dest: &mut [i64],
+        limit: usize,
+    ) -> (usize, Option<ReadError>) {
         let true_limit = limit.min(dest.len());
         let mut current = 0;
         while current < true_limit {
@@ -201,8 +328,8 @@ impl Processor {
     /// Read characters until :
     /// - An ascii control (new line in most cases) is seen
     /// - A non ascii i64 is read (in that case the i64 is concatenated to the String)
-    /// - The processor blocks (in that case the second return value contains the block)
-    pub fn read_next_line(&mut self) -> (String, Option<Status>) {
+    /// - The processor blocks or fails (in that case the second return value contains the reason)
+    pub fn read_next_line(&mut self) -> (String, Option<ReadError>) {
         let mut acc = String::with_capacity(10); // 10 is a good starting capacity.
         loop {
             match self.read_next() {
@@ -237,6 +364,38 @@ impl Processor {
         }
     }
 
+    /// Iterate over the outputs of the processor, feeding it from the inputs when it requires some.
+    /// # Arguments
+    /// * `inputs` - The inputs to feed the processor with, after its current input queue
+    pub fn outputs<I: IntoIterator<Item = i64>>(&mut self, inputs: I) -> Outputs<'_, I::IntoIter> {
+        Outputs::new(self, inputs.into_iter())
+    }
+
+    /// Run the processor, sending outputs and asking inputs from the device given.
+    /// # Arguments
+    /// * `io` - The device plugged to the inputs and outputs of the processor
+    /// # Returns
+    /// The status that stopped the processor (RequireInput if the device had no input left)
+    pub fn run_with_io<Io: IntCodeIo>(&mut self, io: &mut Io) -> Result<Status, IntCodeError> {
+        loop {
+            match self.run()? {
+                Status::WithOutput(out) => {
+                    if let Err(status) = io.output(out) {
+                        return Ok(status);
+                    }
+                }
+                Status::RequireInput => match io.input() {
+                    Ok(input) => self.write_int(input),
+                    Err(status) => return Ok(status),
+                },
+                Status::Halted => {
+                    io.halted();
+                    return Ok(Status::Halted);
+                }
+            }
+        }
+    }
+
     /// Run the processor, sending outputs and asking inputs from the callbacks given.
     /// This version uses the raw i64 as the input and outputs.
     /// # Arguments
@@ -245,35 +404,20 @@ impl Processor {
     /// * `on_output` A callback to run on the state and an output whenever some is produced
     pub fn run_with_callbacks<State, I, O>(
         &mut self,
-        mut state: State,
-        mut when_input: I,
-        mut on_output: O,
-    ) -> Status
+        state: State,
+        when_input: I,
+        on_output: O,
+    ) -> Result<Status, IntCodeError>
     where
         I: FnMut(&mut State) -> Option<i64>,
         O: FnMut(&mut State, i64) -> Result<(), Status>,
     {
-        loop {
-            match self.run() {
-                Ok(Status::WithOutput(out)) => {
-                    if let Err(status) = on_output(&mut state, out) {
-                        return status;
-                    }
-                }
-                Ok(Status::RequireInput) => {
-                    let input: i64 = match when_input(&mut state) {
-                        Some(int) => int,
-                        None => return Status::RequireInput,
-                    };
-                    self.write_int(input);
-                }
-                Ok(Status::Halted) => return Status::Halted,
-                Err(err) => {
-                    println!("Error : {err}");
-                    return Status::Halted;
-                }
-            }
-        }
+        let mut io = Callbacks {
+            state,
+            when_input,
+            on_output,
+        };
+        self.run_with_io(&mut io)
     }
 
     /// Run the processor, sending outputs and asking inputs from the callbacks given.
@@ -284,33 +428,21 @@ impl Processor {
     /// * `on_output` A callback to run on the state and an output whenever some is produced
     pub fn run_with_ascii_callbacks<State, I, O>(
         &mut self,
-        mut state: State,
-        mut when_input: I,
-        mut on_output: O,
-    ) -> (Status, State)
+        state: State,
+        when_input: I,
+        on_output: O,
+    ) -> (Result<Status, IntCodeError>, State)
     where
         I: FnMut(&mut State) -> Option<String>,
         O: FnMut(&mut State, &str) -> Result<(), Status>,
     {
-        loop {
-            let (out, status_opt) = self.read_next_line();
-            // Read the status of the ascii line read
-            match status_opt {
-                Some(Status::RequireInput) => {
-                    let input: String = match when_input(&mut state) {
-                        Some(str) => str,
-                        None => return (Status::RequireInput, state),
-                    };
-                    self.write_text(&input);
-                }
-                Some(status) => return (status, state),
-                None => {}
-            }
-            // Feed the output to the function
-            if let Err(status) = on_output(&mut state, &out) {
-                return (status, state);
-            }
-        }
+        let mut io = Ascii::new(AsciiCallbacks {
+            state,
+            when_input,
+            on_output,
+        });
+        let status = self.run_with_io(&mut io);
+        (status, io.into_inner().state)
     }
 
     /// Returns the ownership of the current memory
@@ -321,16 +453,50 @@ impl Processor {
     /// Read an instruction from the current position in memory.
     fn parse_inst(&self) -> Result<Instruction, IntCodeError> {
         let inst = self.read_memory(self.current)?;
-        let code = OpCode::try_from(inst)?;
-        let mode1 = Mode::try_from(inst / 100)?;
-        let mode2 = Mode::try_from(inst / 1000)?;
-        let mode3 = Mode::try_from(inst / 10000)?;
+        let invalid = |_| IntCodeError::InvalidInstruction {
+            fault: self.fault(),
+        };
+        let code = OpCode::try_from(inst).map_err(invalid)?;
+        let mode1 = Mode::try_from(inst / 100).map_err(invalid)?;
+        let mode2 = Mode::try_from(inst / 1000).map_err(invalid)?;
+        let mode3 = Mode::try_from(inst / 10000).map_err(invalid)?;
         Ok(Instruction {
             code,
             modes: (mode1, mode2, mode3),
         })
     }
 
+    /// The state of the processor for an error in the current instruction
+    fn fault(&self) -> Fault {
+        Fault {
+            address: self.current,
+            instruction: usize::try_from(self.current)
+                .ok()
+                .and_then(|pos| self.memory.get(pos))
+                .copied()
+                .unwrap_or(0),
+            relative_base: self.relative_offset,
+        }
+    }
+
+    /// Decode the instruction at the current position, or fetch it from the cache.
+    fn decode(&mut self) -> Result<Instruction, IntCodeError> {
+        let index = usize::try_from(self.current).map_err(|_| IntCodeError::InvalidIndexRead {
+            index: self.current,
+            fault: self.fault(),
+        })?;
+        if let Some(Some(instruction)) = self.decoded.get(index) {
+            return Ok(*instruction);
+        }
+
+        let instruction = self.parse_inst()?;
+        if index >= self.decoded.len() {
+            self.decoded.resize(index + 1, None);
+        }
+        self.decoded[index] = Some(instruction);
+        Ok(instruction)
+    }
+
     /// Get an input from memory according to its mode.
     fn get(&self, offset: i64, mode: Mode) -> Result<i64, IntCodeError> {
         let pos = self.read_memory(self.current + offset)?;
@@ -352,21 +518,27 @@ impl Processor {
     }
 
     /// Direct read from the program memory
-    /// # Panics
+    /// # Errors
     /// If pos is negative
     fn read_memory(&self, pos: i64) -> Result<i64, IntCodeError> {
         if pos < 0 {
-            return Err(IntCodeError::InvalidIndexRead);
+            return Err(IntCodeError::InvalidIndexRead {
+                index: pos,
+                fault: self.fault(),
+            });
         }
         Ok(*self.memory.get(pos as usize).unwrap_or(&0))
     }
 
     /// Direct write to the program memory
-    /// # Panics
+    /// # Errors
     /// If pos is negative
     fn write_memory(&mut self, pos: i64, value: i64) -> Result<(), IntCodeError> {
         if pos < 0 {
-            return Err(IntCodeError::InvalidIndexWrite);
+            return Err(IntCodeError::InvalidIndexWrite {
+                index: pos,
+                fault: self.fault(),
+            });
         }
 
         let pos = pos as usize;
@@ -375,12 +547,62 @@ impl Processor {
             self.memory.resize(pos + 1, 0);
         }
         *self.memory.get_mut(pos).unwrap() = value;
+
+        // invalidates the cached instruction, parameters are always read from memory.
+        if let Some(decoded) = self.decoded.get_mut(pos) {
+            *decoded = None;
+        }
         Ok(())
     }
 }
 
+impl Machine for Processor {
+    /// The instruction pointer, the relative offset and the memory
+    type State = (i64, i64, Vec<i64>);
+    type Output = i64;
+    type Error = IntCodeError;
+
+    fn step(&mut self) -> Result<Step<i64>, IntCodeError> {
+        Ok(match self.execute()? {
+            None => Step::Running,
+            Some(Status::WithOutput(output)) => Step::Output(output),
+            Some(Status::RequireInput) => Step::Blocked,
+            Some(Status::Halted) => Step::Halted,
+        })
+    }
+
+    /// Restore the starting memory, the pending inputs are dropped
+    fn reset(&mut self) {
+        self.memory = self.initial.to_vec();
+        self.current = 0;
+        self.relative_offset = 0;
+        self.input_queue.clear();
+        self.decoded.clear();
+    }
+
+    fn position(&self) -> usize {
+        usize::try_from(self.current).unwrap_or(usize::MAX)
+    }
+
+    fn state(&self) -> Self::State {
+        (self.current, self.relative_offset, self.memory.clone())
+    }
+}
+
+impl PartialEq for Processor {
+    /// Processors are equal if their state is equal, whatever their engine and cache
+    fn eq(&self, other: &Self) -> bool {
+        self.memory == other.memory
+            && self.current == other.current
+            && self.relative_offset == other.relative_offset
+            && self.input_queue == other.input_queue
+    }
+}
+
+impl Eq for Processor {}
+
 /// A single instruction for an int_code processor
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 struct Instruction {
     /// The OpCode defining the instruction
     code: OpCode,
@@ -389,7 +611,7 @@ struct Instruction {
 }
 
 /// All the different instructions for an int_code processor
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 enum OpCode {
     Add,
     Mul,
@@ -404,7 +626,7 @@ enum OpCode {
 }
 
 impl TryFrom<i64> for OpCode {
-    type Error = IntCodeError;
+    type Error = i64;
     fn try_from(value: i64) -> Result<Self, Self::Error> {
         match value % 100 {
             1 => Ok(OpCode::Add),
@@ -417,13 +639,13 @@ impl TryFrom<i64> for OpCode {
             8 => Ok(OpCode::Equals),
             9 => Ok(OpCode::Offset),
             99 => Ok(OpCode::Halt),
-            _ => Err(IntCodeError::InvalidInstruction),
+            invalid => Err(invalid),
         }
     }
 }
 
 /// The different modes of accessing the memory in an instruction
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy)]
 enum Mode {
     Absolute,
     Immediate,
@@ -431,13 +653,13 @@ enum Mode {
 }
 
 impl TryFrom<i64> for Mode {
-    type Error = IntCodeError;
+    type Error = i64;
     fn try_from(value: i64) -> Result<Self, Self::Error> {
         match value % 10 {
             0 => Ok(Mode::Absolute),
             1 => Ok(Mode::Immediate),
             2 => Ok(Mode::Relative),
-            _ => Err(IntCodeError::InvalidInstruction),
+            invalid => Err(invalid),
         }
     }
 }
@@ -0,0 +1,284 @@
+//! A scheduler running many IntCode processors connected together
+//! # Basic usage
+//! - Describe the packets exchanged by the processors with the `Packet` trait
+//! - Describe where the packets should go with the `Router` trait
+//! - Run the network until the router stops it
+//! ```
+//! use advent_of_code_2019::int_code::Processor;
+//! use advent_of_code_2019::int_code::network::{Network, Route, Router};
+//!
+//! /// Each node adds one to its input and sends it to the next one, until it reaches 10
+//! struct Ring;
+//!
+//! impl Router for Ring {
+//!     type Packet = i64;
+//!     type Output = i64;
+//!
+//!     fn route(&mut self, from: usize, value: i64) -> Route<i64, i64> {
+//!         if value >= 10 {
+//!             Route::Stop(value)
+//!         } else {
+//!             Route::Deliver((from + 1) % 3, value)
+//!         }
+//!     }
+//! }
+//!
+//! // Reads an input, outputs it incremented by one and loops
+//! let increment = [3, 11, 101, 1, 11, 11, 4, 11, 1105, 1, 0];
+//! let mut network: Network<i64> = Network::new((0..3).map(|_| Processor::new(&increment)));
+//! network.send(0, &0).unwrap();
+//! assert_eq!(network.run(&mut Ring), Ok(10));
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+
+use super::{IntCodeError, Processor, Status};
+
+/// The number of consecutive empty reads after which a node is considered idle
+const IDLE_READS: u32 = 2;
+
+/// A message exchanged between the processors of a network
+pub trait Packet: Sized {
+    /// The number of outputs of a processor that make up one packet
+    const SIZE: usize;
+
+    /// Build a packet from the `SIZE` outputs of a processor
+    fn from_outputs(outputs: &[i64]) -> Self;
+
+    /// Write this packet in the inputs of the receiving processor
+    fn write_to(&self, processor: &mut Processor);
+}
+
+/// A single int as a packet, for networks that exchange raw values
+impl Packet for i64 {
+    const SIZE: usize = 1;
+
+    fn from_outputs(outputs: &[i64]) -> Self {
+        outputs[0]
+    }
+
+    fn write_to(&self, processor: &mut Processor) {
+        processor.write_int(*self);
+    }
+}
+
+/// What to do with a packet, or with the network once it is idle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route<P, O> {
+    /// Deliver the packet to the node at this address
+    Deliver(usize, P),
+    /// Drop the packet (when idle, this means the network is dead-locked)
+    Discard,
+    /// Stop the whole network with this result
+    Stop(O),
+}
+
+/// The routing rules of a network, that may also observe it (like a NAT)
+pub trait Router {
+    /// The packets exchanged in the network
+    type Packet: Packet;
+    /// The result of the network once stopped
+    type Output;
+
+    /// Decide what to do with a packet sent by a node.
+    /// # Arguments
+    /// * `from` - The address of the node that sent the packet
+    /// * `packet` - The packet that was sent
+    fn route(&mut self, from: usize, packet: Self::Packet) -> Route<Self::Packet, Self::Output>;
+
+    /// Decide what to do when no node of the network can make progress.
+    /// By default the network is considered dead-locked.
+    fn on_idle(&mut self) -> Route<Self::Packet, Self::Output> {
+        Route::Discard
+    }
+
+    /// The value to give to a node that requires input when it has none (None to block it)
+    fn empty_input(&self) -> Option<i64> {
+        None
+    }
+}
+
+/// An error that stopped a network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkError {
+    /// One of the processors failed
+    Processor(usize, IntCodeError),
+    /// A packet was delivered to an address that does not exist
+    Unreachable(usize),
+    /// No node can make progress and the router did not wake the network up
+    Deadlock,
+}
+
+impl Error for NetworkError {}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Processor(node, error) => write!(f, "Node {node} failed: {error}"),
+            NetworkError::Unreachable(node) => write!(f, "Node {node} does not exist"),
+            NetworkError::Deadlock => write!(f, "The network is dead-locked"),
+        }
+    }
+}
+
+/// Many processors exchanging packets of type P, scheduled in a round-robin fashion
+#[derive(Debug, Clone)]
+pub struct Network<P> {
+    nodes: Vec<Node>,
+    packet: PhantomData<P>,
+}
+
+/// A processor in a network, along with its scheduling state
+#[derive(Debug, Clone)]
+struct Node {
+    /// The processor running on this node
+    processor: Processor,
+    /// The outputs of the packet being produced
+    outputs: Vec<i64>,
+    /// The number of times the node asked for input without receiving any
+    empty_reads: u32,
+    /// True if the processor has halted
+    halted: bool,
+}
+
+impl<P: Packet> Network<P> {
+    /// Build a new network, each processor having its index as address.
+    /// # Arguments
+    /// * `processors` - The processors to schedule
+    pub fn new(processors: impl IntoIterator<Item = Processor>) -> Self {
+        let nodes = processors
+            .into_iter()
+            .map(|processor| Node {
+                processor,
+                outputs: Vec::with_capacity(P::SIZE),
+                empty_reads: 0,
+                halted: false,
+            })
+            .collect();
+
+        Self {
+            nodes,
+            packet: PhantomData,
+        }
+    }
+
+    /// The number of nodes in this network
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// True if this network has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// True if no node can make progress: they all halted or are waiting for a packet
+    pub fn is_idle(&self) -> bool {
+        self.nodes.iter().all(|node| {
+            node.halted || (node.empty_reads >= IDLE_READS && node.processor.input_queue.is_empty())
+        })
+    }
+
+    /// Send a packet to one of the nodes of the network from outside of it
+    pub fn send(&mut self, to: usize, packet: &P) -> Result<(), NetworkError> {
+        let node = self
+            .nodes
+            .get_mut(to)
+            .ok_or(NetworkError::Unreachable(to))?;
+        packet.write_to(&mut node.processor);
+        node.empty_reads = 0;
+        Ok(())
+    }
+
+    /// Run the network until the router stops it.
+    /// # Returns
+    /// The result given by the router, or the error that stopped the network
+    pub fn run<R>(&mut self, router: &mut R) -> Result<R::Output, NetworkError>
+    where
+        R: Router<Packet = P>,
+    {
+        loop {
+            if let Some(output) = self.step(router)? {
+                return Ok(output);
+            }
+        }
+    }
+
+    /// Run one round: each node in turn runs until it sends a packet or blocks.
+    /// The router is then asked to wake up the network if it is idle.
+    /// # Returns
+    /// Some result if the router stopped the network, None if it can keep running
+    pub fn step<R>(&mut self, router: &mut R) -> Result<Option<R::Output>, NetworkError>
+    where
+        R: Router<Packet = P>,
+    {
+        for from in 0..self.nodes.len() {
+            if let Some(packet) = self.poll(from, router.empty_input())? {
+                if let Some(output) = self.dispatch(router.route(from, packet))? {
+                    return Ok(Some(output));
+                }
+            }
+        }
+
+        if self.is_idle() {
+            return match router.on_idle() {
+                Route::Discard => Err(NetworkError::Deadlock),
+                route => self.dispatch(route),
+            };
+        }
+
+        Ok(None)
+    }
+
+    /// Run a node until it produces a packet or blocks.
+    /// # Arguments
+    /// * `at` - The address of the node to run
+    /// * `empty_input` - The value to give to the node if it has no input to read
+    fn poll(&mut self, at: usize, empty_input: Option<i64>) -> Result<Option<P>, NetworkError> {
+        let node = &mut self.nodes[at];
+        if node.halted {
+            return Ok(None);
+        }
+        if node.empty_reads > 0 && node.processor.input_queue.is_empty() {
+            if let Some(input) = empty_input {
+                node.processor.write_int(input);
+            }
+        }
+
+        loop {
+            match node.processor.run() {
+                Ok(Status::WithOutput(out)) => {
+                    node.outputs.push(out);
+                    if node.outputs.len() >= P::SIZE {
+                        let packet = P::from_outputs(&node.outputs);
+                        node.outputs.clear();
+                        node.empty_reads = 0;
+                        return Ok(Some(packet));
+                    }
+                }
+                Ok(Status::RequireInput) => {
+                    node.empty_reads += 1;
+                    return Ok(None);
+                }
+                Ok(Status::Halted) => {
+                    node.halted = true;
+                    return Ok(None);
+                }
+                Err(error) => return Err(NetworkError::Processor(at, error)),
+            }
+        }
+    }
+
+    /// Apply the decision of the router
+    /// # Returns
+    /// Some result if the router stopped the network, None if it can keep running
+    fn dispatch<O>(&mut self, route: Route<P, O>) -> Result<Option<O>, NetworkError> {
+        match route {
+            Route::Deliver(to, packet) => self.send(to, &packet).map(|_| None),
+            Route::Discard => Ok(None),
+            Route::Stop(output) => Ok(Some(output)),
+        }
+    }
+}
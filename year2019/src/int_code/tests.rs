@@ -1,4 +1,9 @@
-use super::{Processor, Status};
+use commons::machine::{Detection, Machine, Runner, Stop};
+
+use super::console::Console;
+use super::io::{Ascii, AsciiIo, IntCodeIo};
+use super::network::{Network, NetworkError, Route, Router};
+use super::{Engine, Fault, IntCodeError, IntCodeInput, Processor, ReadError, Status};
 
 /// An IntCode binary Program that will ask for input and output it back. Perfect to test IO.
 const ECHO_PROGRAM: [i64; 8] = [3, 3, 104, -1, 1106, 0, 0, 99];
@@ -120,7 +125,7 @@ fn int_callback() {
 
     let mut data = input.iter().copied();
     let mut acc: Vec<i64> = Vec::with_capacity(input.len());
-    program.run_with_callbacks(
+    let status = program.run_with_callbacks(
         0,
         |_| data.next(),
         |_, int| {
@@ -129,6 +134,7 @@ fn int_callback() {
         },
     );
 
+    assert_eq!(status, Ok(Status::RequireInput));
     assert_eq!(input.as_ref(), acc.as_slice());
 }
 
@@ -139,7 +145,7 @@ fn ascii_callback() {
 
     let mut data = std::iter::once(hello.to_string());
     let mut acc: String = String::with_capacity(hello.len());
-    program.run_with_ascii_callbacks(
+    let (status, _) = program.run_with_ascii_callbacks(
         0,
         |_| data.next(),
         |_, line| {
@@ -148,5 +154,311 @@ fn ascii_callback() {
         },
     );
 
+    assert_eq!(status, Ok(Status::RequireInput));
     assert_eq!(&acc, hello);
 }
+
+#[test]
+fn errors() {
+    fn fault(address: i64, instruction: i64, relative_base: i64) -> Fault {
+        Fault {
+            address,
+            instruction,
+            relative_base,
+        }
+    }
+
+    for engine in [Engine::Interpreted, Engine::PreDecoded] {
+        let mut program = Processor::new(&[109, 2, 204, -5, 99]).with_engine(engine);
+        let error = IntCodeError::InvalidIndexRead {
+            index: -3,
+            fault: fault(2, 204, 2),
+        };
+        assert_eq!(program.run(), Err(error.clone()), "{engine:?}");
+        assert_eq!(
+            error.to_string(),
+            "Cannot read from the invalid index -3 (instruction 204 at address 2, relative base 2)"
+        );
+
+        let mut program = Processor::new(&[109, 3, 21101, 1, 1, -10, 99]).with_engine(engine);
+        let error = IntCodeError::InvalidIndexWrite {
+            index: -7,
+            fault: fault(2, 21101, 3),
+        };
+        assert_eq!(program.run(), Err(error), "{engine:?}");
+
+        let mut program = Processor::new(&[1101, 1, 1, 0, 42]).with_engine(engine);
+        let error = IntCodeError::InvalidInstruction {
+            fault: fault(4, 42, 0),
+        };
+        assert_eq!(
+            program.read_next(),
+            Err(ReadError::Failed(error)),
+            "{engine:?}"
+        );
+
+        let mut program = Processor::new(&[104, 1, 304, 0]).with_engine(engine);
+        let error = IntCodeError::InvalidInstruction {
+            fault: fault(2, 304, 0),
+        };
+        let mut outputs = [0; 2];
+        let (read, stopped) = program.read_next_array(&mut outputs, 2);
+        assert_eq!(
+            (read, stopped),
+            (1, Some(ReadError::Failed(error))),
+            "{engine:?}"
+        );
+
+        let mut program = Processor::new(&[1105, 1, -1]).with_engine(engine);
+        let error = IntCodeError::InvalidIndexRead {
+            index: -1,
+            fault: fault(-1, 0, 0),
+        };
+        assert_eq!(program.run(), Err(error), "{engine:?}");
+    }
+
+    let mut program: Processor = ECHO_PROGRAM.as_ref().into();
+    assert_eq!(
+        program.read_next(),
+        Err(ReadError::Blocked(Status::RequireInput))
+    );
+}
+
+#[test]
+fn self_modifying_code() {
+    // Outputs 1, then replaces that output parameter by 5 and jumps back once
+    let parameter_rewrite: [i64; 18] = [
+        104, 1, 1005, 17, 16, 1101, 5, 0, 1, 1101, 1, 0, 17, 1106, 0, 0, 99, 0,
+    ];
+    // Outputs 7, then replaces that output instruction by a halt and jumps back
+    let opcode_rewrite: [i64; 9] = [104, 7, 1101, 99, 0, 0, 1105, 1, 0];
+
+    for engine in [Engine::Interpreted, Engine::PreDecoded] {
+        let mut program = Processor::new(&parameter_rewrite).with_engine(engine);
+        assert_eq!(program.run(), Ok(Status::WithOutput(1)), "{engine:?}");
+        assert_eq!(program.run(), Ok(Status::WithOutput(5)), "{engine:?}");
+        assert_eq!(program.run(), Ok(Status::Halted), "{engine:?}");
+
+        let mut program = Processor::new(&opcode_rewrite).with_engine(engine);
+        assert_eq!(program.run(), Ok(Status::WithOutput(7)), "{engine:?}");
+        assert_eq!(program.run(), Ok(Status::Halted), "{engine:?}");
+    }
+}
+
+#[test]
+fn engines_agree() {
+    let memory = include_str!("../day09/data.txt")
+        .parse::<IntCodeInput>()
+        .unwrap()
+        .data;
+
+    let [interpreted, decoded] = [Engine::Interpreted, Engine::PreDecoded].map(|engine| {
+        let mut program = Processor::with_initial_inputs(&memory, &[2]).with_engine(engine);
+        let output = program.read_next();
+        (output, program)
+    });
+
+    assert_eq!(interpreted.0, Ok(87_571));
+    assert_eq!(interpreted, decoded);
+}
+
+/// Routes each value to the next node of a ring, stopping when the limit is reached
+struct Ring {
+    size: usize,
+    limit: i64,
+}
+
+impl Router for Ring {
+    type Packet = i64;
+    type Output = (usize, i64);
+
+    fn route(&mut self, from: usize, value: i64) -> Route<i64, (usize, i64)> {
+        if value >= self.limit {
+            Route::Stop((from, value))
+        } else {
+            Route::Deliver((from + 1) % self.size, value)
+        }
+    }
+}
+
+#[test]
+fn network_routing() {
+    // Reads an input, outputs it incremented by one and loops
+    let increment: [i64; 11] = [3, 11, 101, 1, 11, 11, 4, 11, 1105, 1, 0];
+    let build = || Network::new((0..4).map(|_| Processor::new(&increment)));
+
+    let mut network: Network<i64> = build();
+    network.send(0, &0).unwrap();
+    assert_eq!(network.run(&mut Ring { size: 4, limit: 10 }), Ok((1, 10)));
+
+    let mut network: Network<i64> = build();
+    network.send(0, &0).unwrap();
+    assert_eq!(
+        network.run(&mut Ring { size: 5, limit: 10 }),
+        Err(NetworkError::Unreachable(4))
+    );
+    assert_eq!(network.send(7, &0), Err(NetworkError::Unreachable(7)));
+}
+
+#[test]
+fn network_idleness() {
+    let mut network: Network<i64> = Network::new((0..3).map(|_| ECHO_PROGRAM.as_ref().into()));
+    let mut ring = Ring { size: 3, limit: 10 };
+    assert!(!network.is_idle());
+
+    // Nobody has anything to send: the network is dead-locked once all nodes waited twice
+    assert_eq!(network.step(&mut ring), Ok(None));
+    assert!(!network.is_idle());
+    assert_eq!(network.step(&mut ring), Err(NetworkError::Deadlock));
+    assert!(network.is_idle());
+
+    // A packet wakes the network up
+    network.send(1, &3).unwrap();
+    assert!(!network.is_idle());
+    assert_eq!(network.step(&mut ring), Ok(None));
+}
+
+#[test]
+fn outputs_iterator() {
+    let mut program: Processor = ECHO_PROGRAM.as_ref().into();
+    let mut outputs = program.outputs([3, 2, 1]);
+    assert_eq!(outputs.by_ref().collect::<Vec<_>>(), vec![3, 2, 1]);
+    assert_eq!(outputs.stopped(), Some(&Ok(Status::RequireInput)));
+
+    let mut program = Processor::new(&[104, 1, 104, 2, 99]);
+    let mut outputs = program.outputs(std::iter::empty());
+    assert_eq!(outputs.stopped(), None);
+    assert_eq!(outputs.by_ref().sum::<i64>(), 3);
+    assert_eq!(outputs.stopped(), Some(&Ok(Status::Halted)));
+}
+
+#[test]
+fn io_device() {
+    /// Sends its inputs then stops the processor as soon as it outputs a negative number
+    struct Device(Vec<i64>, Vec<i64>);
+
+    impl IntCodeIo for Device {
+        fn input(&mut self) -> Result<i64, Status> {
+            self.0.pop().ok_or(Status::RequireInput)
+        }
+
+        fn output(&mut self, value: i64) -> Result<(), Status> {
+            self.1.push(value);
+            if value < 0 {
+                Err(Status::WithOutput(value))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    let mut program: Processor = ECHO_PROGRAM.as_ref().into();
+    let mut device = Device(vec![-4, 3, 2, 1], vec![]);
+    assert_eq!(program.run_with_io(&mut device), Ok(Status::WithOutput(-4)));
+    assert_eq!(device.1, vec![1, 2, 3, -4]);
+    assert_eq!(program.run_with_io(&mut device), Ok(Status::RequireInput));
+}
+
+#[test]
+fn ascii_device() {
+    /// Answers each prompt with the next line, collecting all the output lines
+    struct Console(Vec<&'static str>, Vec<String>);
+
+    impl AsciiIo for Console {
+        fn input_line(&mut self) -> Result<String, Status> {
+            self.0.pop().map(String::from).ok_or(Status::RequireInput)
+        }
+
+        fn output_line(&mut self, line: &str) -> Result<(), Status> {
+            self.1.push(line.to_owned());
+            Ok(())
+        }
+    }
+
+    let mut program: Processor = ECHO_PROGRAM.as_ref().into();
+    let mut console = Ascii::new(Console(vec!["there\n", "Hello "], vec![]));
+    assert_eq!(program.run_with_io(&mut console), Ok(Status::RequireInput));
+    // The prompt "Hello " is flushed before asking for the next line
+    assert_eq!(console.into_inner().1, vec!["Hello ", "there\n"]);
+
+    let mut program = Processor::new(&[104, 72, 104, 105, 104, 1000, 99]);
+    let mut console = Ascii::new(Console(vec![], vec![]));
+    assert_eq!(program.run_with_io(&mut console), Ok(Status::Halted));
+    assert_eq!(console.into_inner().1, vec!["Hi\n1000\n"]);
+
+    // The last line is flushed when the processor halts, even without a new line
+    let mut program = Processor::new(&[104, 72, 104, 105, 99]);
+    let mut console = Ascii::new(Console(vec![], vec![]));
+    assert_eq!(program.run_with_io(&mut console), Ok(Status::Halted));
+    assert_eq!(console.into_inner().1, vec!["Hi"]);
+}
+
+#[test]
+fn ascii_device_status() {
+    /// Stops the processor with its status as soon as it receives a line
+    struct Stopper(Status);
+
+    impl AsciiIo for Stopper {
+        fn input_line(&mut self) -> Result<String, Status> {
+            Ok("\n".to_owned())
+        }
+
+        fn output_line(&mut self, _: &str) -> Result<(), Status> {
+            Err(self.0.clone())
+        }
+    }
+
+    // The prompt is flushed when the processor requires input, the device stops it there
+    let mut program = Processor::new(&[104, 62, 3, 0, 99]);
+    let status = program.run_with_io(&mut Ascii::new(Stopper(Status::Halted)));
+    assert_eq!(status, Ok(Status::Halted));
+}
+
+#[test]
+fn console_script_and_history() {
+    let script = "# A comment\nfirst\n\n:redo\n";
+    let input = std::io::Cursor::new(":history\n:redo 3\n:redo 1\nlast\n:quit\nignored\n");
+    let mut console = Console::new(input, Vec::new()).with_script(script);
+    let mut program: Processor = ECHO_PROGRAM.as_ref().into();
+
+    // The program is halted by :quit, the lines after it are not read
+    let status = program.run_with_io(&mut Ascii::new(&mut console));
+    assert_eq!(status, Ok(Status::Halted));
+    assert_eq!(console.history(), ["first", "first", "first", "last"]);
+    assert_eq!(
+        console.transcript(),
+        "first\nfirst\n:redo\nfirst\nfirst\n\
+         :history\n   1  first\n   2  first\n\
+         :redo 3\nNo line '3' in the history\n\
+         :redo 1\nfirst\nfirst\n\
+         last\nlast\n\
+         :quit\n"
+    );
+}
+
+#[test]
+fn processor_as_machine() {
+    let mut program = Processor::with_initial_inputs(&ECHO_PROGRAM, &[1, 2]);
+    let mut outputs = Vec::new();
+    let outcome = Runner::new()
+        .with_output(|output| outputs.push(output))
+        .run(&mut program)
+        .unwrap();
+    assert_eq!((outcome.stop, outcome.last), (Stop::Blocked, Some(4)));
+    assert_eq!(outputs, [1, 2]);
+    assert_eq!(program.state().2[3], 2);
+
+    program.reset();
+    assert_eq!(program.state(), (0, 0, ECHO_PROGRAM.to_vec()));
+    assert_eq!(program.run(), Ok(Status::RequireInput));
+
+    // Jumps back to itself forever
+    let mut program = Processor::new(&[1105, 1, 0]);
+    let outcome = Runner::new()
+        .with_detection(Detection::State)
+        .run(&mut program)
+        .unwrap();
+    assert_eq!((outcome.stop, outcome.steps), (Stop::Looped(0), 1));
+    let outcome = Runner::new().with_limit(50).run(&mut program).unwrap();
+    assert_eq!(outcome.stop, Stop::Limit);
+}
@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 
-use commons::arguments::Day;
+use commons::arguments::{Day, Options};
 use commons::problem::solve_verbose;
+use commons::terminal::Renderer;
 use commons::{err, error::Result};
 
 mod day01;
@@ -31,26 +32,35 @@ mod day24;
 mod day25;
 pub mod int_code;
 
+/// The options of the solutions of this year, the other options are rejected
+pub const OPTIONS: &[&[&str]] = &[
+    Renderer::OPTIONS,
+    day03::OPTIONS,
+    day06::OPTIONS,
+    day13::OPTIONS,
+    day17::OPTIONS,
+];
+
 /// Dispatch to the correct problem and solve it
-pub fn solve_problem(day: Day, input: PathBuf) -> Result<()> {
+pub fn solve_problem(day: Day, input: PathBuf, options: &Options) -> Result<()> {
     match day {
         Day::Day1 => solve_verbose(day01::TITLE, input, day01::run),
         Day::Day2 => solve_verbose(day02::TITLE, input, day02::run),
-        Day::Day3 => solve_verbose(day03::TITLE, input, day03::run),
+        Day::Day3 => solve_verbose(day03::TITLE, input, |raw| day03::run(raw, options)),
         Day::Day4 => solve_verbose(day04::TITLE, input, day04::run),
         Day::Day5 => solve_verbose(day05::TITLE, input, day05::run),
-        Day::Day6 => solve_verbose(day06::TITLE, input, day06::run),
+        Day::Day6 => solve_verbose(day06::TITLE, input, |raw| day06::run(raw, options)),
         Day::Day7 => solve_verbose(day07::TITLE, input, day07::run),
         Day::Day8 => solve_verbose(day08::TITLE, input, day08::run),
         Day::Day9 => solve_verbose(day09::TITLE, input, day09::run),
         Day::Day10 => solve_verbose(day10::TITLE, input, day10::run),
         Day::Day11 => solve_verbose(day11::TITLE, input, day11::run),
         Day::Day12 => solve_verbose(day12::TITLE, input, day12::run),
-        Day::Day13 => solve_verbose(day13::TITLE, input, day13::run),
+        Day::Day13 => solve_verbose(day13::TITLE, input, |raw| day13::run(raw, options)),
         Day::Day14 => solve_verbose(day14::TITLE, input, day14::run),
-        Day::Day15 => solve_verbose(day15::TITLE, input, day15::run),
+        Day::Day15 => solve_verbose(day15::TITLE, input, |raw| day15::run(raw, options)),
         Day::Day16 => solve_verbose(day16::TITLE, input, day16::run),
-        Day::Day17 => solve_verbose(day17::TITLE, input, day17::run),
+        Day::Day17 => solve_verbose(day17::TITLE, input, |raw| day17::run(raw, options)),
         Day::Day18 => solve_verbose(day18::TITLE, input, day18::run),
         Day::Day19 => solve_verbose(day19::TITLE, input, day19::run),
         Day::Day20 => solve_verbose(day20::TITLE, input, day20::run),
@@ -1,14 +1,21 @@
 use std::collections::HashMap;
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::{ensure, Result, WrapErr};
 
 pub const TITLE: &str = "Day 7: Handy Haversacks";
-pub fn run(raw: String) -> Result<()> {
+pub const OPTIONS: &[&str] = &["dot"];
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let rules = parse(&raw)?;
     let containing_bags = first_part(&rules);
     println!("{containing_bags} bags contains a {WANTED_BAG} bag");
     let contained_bags = second_part(&rules);
     println!("A {WANTED_BAG} will contain {contained_bags} bags");
+    if let Some(path) = options.values("dot").first() {
+        rules_graph(&rules).save(path)?;
+        println!("The bag rules were described in {path}");
+    }
     Ok(())
 }
 
@@ -18,6 +25,8 @@ const WANTED_BAG: &str = "shiny gold";
 struct Rules {
     /// Each element is a bag content
     by_index: Vec<Vec<BagContent>>,
+    /// The name of each bag, by index
+    names: Vec<String>,
     /// The index of the wanted bag in the Vec
     wanted: usize,
 }
@@ -80,13 +89,22 @@ fn parse(raw: &str) -> Result<Rules> {
         };
     }
 
+    let mut names = vec![String::new(); by_name.len()];
+    for (bag, (index, _)) in &by_name {
+        names[*index] = (*bag).to_owned();
+    }
+
     // Find the position of the wanted bag
     let wanted = by_name
         .get(WANTED_BAG)
         .wrap_err_with(|| format!("missing target bag {WANTED_BAG} line"))
         .map(|t| t.0)?;
 
-    Ok(Rules { by_index, wanted })
+    Ok(Rules {
+        by_index,
+        names,
+        wanted,
+    })
 }
 
 /// Find the number of bags that can contain the wanted one (recursively)
@@ -143,5 +161,24 @@ fn count_bags_inside(all_rules: &Rules, bag: usize, cache: &mut [u32]) -> u32 {
     sum
 }
 
+/// The rules as a graph from each bag to the bags it contains, labelled with their count
+fn rules_graph(rules: &Rules) -> Dot {
+    let mut dot = Dot::directed("bags").with_attribute("rankdir", "LR");
+    for (index, name) in rules.names.iter().enumerate() {
+        if index == rules.wanted {
+            dot.node(name, &[("style", "filled"), ("fillcolor", "gold")]);
+        } else {
+            dot.node(name, &[]);
+        }
+    }
+    for (name, contents) in rules.names.iter().zip(&rules.by_index) {
+        for content in contents {
+            let contained = &rules.names[content.contained as usize];
+            dot.edge(name, contained, &[("label", &content.count.to_string())]);
+        }
+    }
+    dot
+}
+
 #[cfg(test)]
 mod tests;
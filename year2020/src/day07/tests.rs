@@ -30,3 +30,13 @@ fn second_part_test_b() {
     let contains = second_part(&rules);
     assert_eq!(158493, contains);
 }
+
+#[test]
+fn rules_graph_test() {
+    let rules = parse(A).unwrap();
+    let dot = rules_graph(&rules);
+    assert_eq!((9, 13), (dot.nodes(), dot.edges()));
+    assert!(dot
+        .to_string()
+        .contains("\"muted yellow\" -> \"faded blue\" [label=\"9\"];"));
+}
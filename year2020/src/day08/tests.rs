@@ -1,3 +1,5 @@
+use crate::handheld::Operation;
+
 use super::*;
 
 const A: &str = include_str!("example.txt");
@@ -6,9 +8,9 @@ const B: &str = include_str!("data.txt");
 #[test]
 fn first_part_a() {
     let data = parse(A).unwrap();
-    let mut state = ProgramState::new(data.data);
+    let mut state = Handheld::new(data);
 
-    let (pos, acc) = run_until_duplicate_execution(&mut state);
+    let (pos, acc) = run_until_duplicate_execution(&mut state).unwrap();
     assert_eq!(5, acc);
     assert_eq!(4, pos);
 }
@@ -16,9 +18,9 @@ fn first_part_a() {
 #[test]
 fn first_part_b() {
     let data = parse(B).unwrap();
-    let mut state = ProgramState::new(data.data);
+    let mut state = Handheld::new(data);
 
-    let (pos, acc) = run_until_duplicate_execution(&mut state);
+    let (pos, acc) = run_until_duplicate_execution(&mut state).unwrap();
     assert_eq!(1586, acc);
     assert_eq!(463, pos);
 }
@@ -26,19 +28,24 @@ fn first_part_b() {
 #[test]
 fn second_part_a() {
     let data = parse(A).unwrap();
-    let mut state = ProgramState::new(data.data);
-
-    let (pos, acc) = replace_and_run(&mut state);
-    assert_eq!(9, pos, "did not reach the end of the instructions");
-    assert_eq!(8, acc, "bad accumulator value");
+    let fix = repair(&data).unwrap();
+    assert_eq!(7, fix.line, "did not patch the right line");
+    assert_eq!(Operation::Nop(-4), fix.patched);
+    assert_eq!(8, fix.accumulator, "bad accumulator value");
+    assert_eq!(
+        fix.to_string(),
+        "Patch the line 7 from 'jmp -4' to 'nop -4':\n\
+         - Without the patch, the program loops back to the line 1\n\
+         - Once patched it continues to the line 8, which can reach the end in 1 steps\n\
+         - The patched program terminates with the accumulator at 8"
+    );
 }
 
 #[test]
 fn second_part_b() {
     let data = parse(B).unwrap();
-    let mut state = ProgramState::new(data.data);
-
-    let (pos, acc) = replace_and_run(&mut state);
-    assert_eq!(641, pos, "did not reach the end of the instructions");
-    assert_eq!(703, acc, "bad accumulator value");
+    let fix = repair(&data).unwrap();
+    assert_eq!(174, fix.line, "did not patch the right line");
+    assert_eq!(Operation::Nop(166), fix.patched);
+    assert_eq!(703, fix.accumulator, "bad accumulator value");
 }
@@ -1,11 +1,17 @@
 use itertools::Itertools;
 
+use commons::arguments::Options;
+use commons::grid::gif::Recorder;
+use commons::grid::image::Rgb;
 use commons::grid::Grid;
 use commons::Result;
 
 pub const TITLE: &str = "Day 11: Seating System";
 
-pub fn run(raw: String) -> Result<()> {
+/// The colors of the animation: the floor, the empty seats and the full seats
+const PALETTE: [Rgb; 3] = [[0x30, 0x30, 0x30], [0x40, 0xa0, 0x40], [0xd0, 0x30, 0x30]];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(&raw);
     let first = first_part(data.clone());
 
@@ -14,12 +20,21 @@ pub fn run(raw: String) -> Result<()> {
         first.occupied_seats()
     );
 
-    let second = second_part(data);
+    let second = second_part(data.clone());
     println!(
         "When seeing further seats the number of occupied seats at the end is {}",
         second.occupied_seats()
     );
 
+    if let Some((mut recorder, path)) = Recorder::from_options(options, &PALETTE)? {
+        animate(data, &mut recorder);
+        recorder.save(&path)?;
+        println!(
+            "The {} states of the seats were saved in {path}",
+            recorder.frames()
+        );
+    }
+
     Ok(())
 }
 
@@ -59,48 +74,70 @@ fn parse(s: &str) -> Ferry {
 
 /// Compute the floor next state until the changes count drops to 0
 /// Only directly adjacent seats are taken into account
-fn first_part(mut floor: Ferry) -> Ferry {
-    fn full_seat(grid: &Grid<Tile>, point: (isize, isize), direction: (isize, isize)) -> bool {
-        grid.get((point.0 + direction.0, point.1 + direction.1))
-            .map_or(false, |t| t.is_occupied())
-    }
-
-    let mut swap = floor.0.clone();
-    let mut changes = 1;
-    while changes != 0 {
-        changes = floor.compute_next_state(4, &mut swap, full_seat);
-        std::mem::swap(&mut floor.0, &mut swap);
-    }
-
-    floor
+fn first_part(floor: Ferry) -> Ferry {
+    settle(floor, 4, adjacent_full_seat, |_| {})
 }
 
 /// Compute the floor next state until the changes count drops to 0
 /// The first seat in each direction is taken into account
-fn second_part(mut floor: Ferry) -> Ferry {
-    fn full_seat(grid: &Grid<Tile>, point: (isize, isize), direction: (isize, isize)) -> bool {
-        grid.half_line(point, direction)
-            .skip(1)
-            .find_map(|(_, t)| {
-                if t.is_nothing() {
-                    None
-                } else {
-                    Some(t.is_occupied())
-                }
-            })
-            .unwrap_or_default()
-    }
+fn second_part(floor: Ferry) -> Ferry {
+    settle(floor, 5, visible_full_seat, |_| {})
+}
 
+/// Record the floor settling with the rules of the first part, then with the rules of the second
+/// part from the initial floor again
+fn animate(floor: Ferry, recorder: &mut Recorder) {
+    let mut capture = |floor: &Ferry| recorder.capture(&floor.0, |tile| tile.color());
+    settle(floor.clone(), 4, adjacent_full_seat, &mut capture);
+    settle(floor, 5, visible_full_seat, &mut capture);
+}
+
+/// Compute the floor next state until the changes count drops to 0
+///
+/// ### Arguments
+/// * `max_around` - The maximum number of full seats in sight before emptying a seat
+/// * `full_seat` - (Grid, Current point, Direction) -> True if full seat in sight
+/// * `on_state` - Called with the initial floor, then after each state computed
+fn settle(
+    mut floor: Ferry,
+    max_around: usize,
+    full_seat: impl Fn(&Grid<Tile>, (isize, isize), (isize, isize)) -> bool,
+    mut on_state: impl FnMut(&Ferry),
+) -> Ferry {
     let mut swap = floor.0.clone();
     let mut changes = 1;
+    on_state(&floor);
     while changes != 0 {
-        changes = floor.compute_next_state(5, &mut swap, full_seat);
-        std::mem::swap(&mut floor.0, &mut swap)
+        changes = floor.compute_next_state(max_around, &mut swap, &full_seat);
+        std::mem::swap(&mut floor.0, &mut swap);
+        if changes != 0 {
+            on_state(&floor);
+        }
     }
 
     floor
 }
 
+/// True if the directly adjacent seat in the direction is full
+fn adjacent_full_seat(grid: &Grid<Tile>, point: (isize, isize), direction: (isize, isize)) -> bool {
+    grid.get((point.0 + direction.0, point.1 + direction.1))
+        .map_or(false, |t| t.is_occupied())
+}
+
+/// True if the first seat seen in the direction is full
+fn visible_full_seat(grid: &Grid<Tile>, point: (isize, isize), direction: (isize, isize)) -> bool {
+    grid.half_line(point, direction)
+        .skip(1)
+        .find_map(|(_, t)| {
+            if t.is_nothing() {
+                None
+            } else {
+                Some(t.is_occupied())
+            }
+        })
+        .unwrap_or_default()
+}
+
 /// The floor plan of the ferry
 #[derive(Clone)]
 struct Ferry(Grid<Tile>);
@@ -121,6 +158,15 @@ impl Tile {
     fn is_nothing(self) -> bool {
         matches!(self, Tile::Nothing)
     }
+
+    /// The index of the color of this tile in the palette of the animation
+    fn color(self) -> u8 {
+        match self {
+            Tile::Nothing => 0,
+            Tile::EmptySeat => 1,
+            Tile::FullSeat => 2,
+        }
+    }
 }
 
 impl Ferry {
@@ -26,3 +26,11 @@ fn second_part_test_b() {
     let floor = second_part(parse(B));
     assert_eq!(2002, floor.occupied_seats());
 }
+
+#[test]
+fn animation_test_a() {
+    let mut recorder = Recorder::new(&PALETTE);
+    animate(parse(A), &mut recorder);
+    // The initial floor and its 5 changing rounds with each rule, one more with the second
+    assert_eq!(recorder.frames(), 13);
+}
@@ -0,0 +1,73 @@
+//! The control flow graph of a handheld program
+
+use std::collections::VecDeque;
+
+use super::Program;
+
+/// The lines that can follow each line of a program.
+/// The node after the last line (the length of the program) is its end.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ControlFlow {
+    /// The lines that can be executed after each line
+    successors: Vec<Vec<usize>>,
+    /// The lines that can be executed before each line, and before the end
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl ControlFlow {
+    /// Build the control flow graph of a program
+    pub fn new(program: &Program) -> Self {
+        let len = program.operations.len();
+        let successors: Vec<Vec<usize>> = program
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(line, operation)| operation.successors(line, len))
+            .collect();
+        let mut predecessors = vec![Vec::new(); len + 1];
+        for (line, next) in successors.iter().enumerate() {
+            for &target in next {
+                predecessors[target].push(line);
+            }
+        }
+        Self {
+            successors,
+            predecessors,
+        }
+    }
+
+    /// The index of the end of the program
+    pub fn end(&self) -> usize {
+        self.successors.len()
+    }
+
+    /// The lines that can be executed after a line
+    pub fn successors(&self, line: usize) -> &[usize] {
+        self.successors.get(line).map_or(&[], Vec::as_slice)
+    }
+
+    /// The lines that can be executed right before a line (or the end)
+    pub fn predecessors(&self, line: usize) -> &[usize] {
+        self.predecessors.get(line).map_or(&[], Vec::as_slice)
+    }
+
+    /// Find the lines from which the end of the program can be reached, going backward from it
+    /// # Returns
+    /// For each line and the end, the number of lines left to execute before the end if it
+    /// can be reached
+    pub fn reaching_end(&self) -> Vec<Option<usize>> {
+        let mut distances = vec![None; self.end() + 1];
+        distances[self.end()] = Some(0);
+        let mut queue = VecDeque::from([self.end()]);
+        while let Some(line) = queue.pop_front() {
+            let distance = distances[line].unwrap_or_default();
+            for &previous in self.predecessors(line) {
+                if distances[previous].is_none() {
+                    distances[previous] = Some(distance + 1);
+                    queue.push_back(previous);
+                }
+            }
+        }
+        distances
+    }
+}
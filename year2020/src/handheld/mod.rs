@@ -0,0 +1,268 @@
+//! The handheld game console of the day 8, with an extended instruction set:
+//! - `acc n` - Increase the accumulator by n, then go to the next instruction
+//! - `mul n` - Multiply the accumulator by n, then go to the next instruction
+//! - `jmp n` - Jump to the instruction n lines away (+n after, -n before)
+//! - `jz n` - Jump to the instruction n lines away if the accumulator is zero
+//! - `jnz n` - Jump to the instruction n lines away if the accumulator is not zero
+//! - `out` - Output the accumulator, then go to the next instruction
+//! - `hlt` - Terminate the program (by jumping right after its last instruction)
+//! - `nop n` - Do nothing, then go to the next instruction
+//!
+//! Empty lines and comments (after a `#`) are ignored.
+//! ```
+//! use commons::machine::Machine;
+//! use advent_of_code_2020::handheld::{repair, Handheld};
+//!
+//! let program = "acc +2\njz +2\njmp -1\nout".parse().unwrap();
+//! let mut handheld = Handheld::new(program);
+//! assert_eq!(handheld.run_until_loop().unwrap().last, Some(2));
+//!
+//! let fix = repair::repair(handheld.program()).unwrap();
+//! assert_eq!((fix.line, fix.accumulator), (1, 2));
+//! ```
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use commons::machine::{Machine, Step};
+use commons::{bail, err, Report, Result, WrapErr};
+
+pub mod graph;
+pub mod repair;
+
+/// The type of the accumulator of the console
+pub type Int = i64;
+
+/// An operation of a handheld program
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Operation {
+    Acc(Int),
+    Mul(Int),
+    Jmp(Int),
+    Jz(Int),
+    Jnz(Int),
+    Out,
+    Hlt,
+    Nop(Int),
+}
+
+/// A program for the handheld console
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Program {
+    pub operations: Vec<Operation>,
+}
+
+/// The handheld console, executing a program
+#[derive(Debug, Clone)]
+pub struct Handheld {
+    program: Program,
+    /// The line of the next operation to execute
+    instruction_pointer: usize,
+    /// The current accumulator
+    accumulator: Int,
+}
+
+impl Operation {
+    /// The lines that can be executed after this operation (the length of the program for its end)
+    /// # Arguments
+    /// * `line` - The line of this operation
+    /// * `len` - The number of lines of the program
+    ///
+    /// # Returns
+    /// The next lines, a jump out of the program has none
+    pub fn successors(self, line: usize, len: usize) -> Vec<usize> {
+        let next = vec![line + 1];
+        let jump = |offset: Int| {
+            usize::try_from(line as Int + offset)
+                .ok()
+                .filter(|target| *target <= len)
+        };
+        match self {
+            Self::Acc(_) | Self::Mul(_) | Self::Out | Self::Nop(_) => next,
+            Self::Jmp(offset) => jump(offset).into_iter().collect(),
+            Self::Jz(offset) | Self::Jnz(offset) => next.into_iter().chain(jump(offset)).collect(),
+            Self::Hlt => vec![len],
+        }
+    }
+
+    /// The operation to use instead of this one if it is corrupted (none for the others)
+    pub fn patched(self) -> Option<Operation> {
+        match self {
+            Self::Jmp(offset) => Some(Self::Nop(offset)),
+            Self::Nop(offset) => Some(Self::Jmp(offset)),
+            Self::Jz(offset) => Some(Self::Jnz(offset)),
+            Self::Jnz(offset) => Some(Self::Jz(offset)),
+            _ => None,
+        }
+    }
+}
+
+impl Handheld {
+    /// Create a new console ready to execute a program from its start
+    pub fn new(program: Program) -> Self {
+        Self {
+            program,
+            instruction_pointer: 0,
+            accumulator: 0,
+        }
+    }
+
+    /// The program executed by this console
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// The current value of the accumulator
+    pub fn accumulator(&self) -> Int {
+        self.accumulator
+    }
+
+    /// True if the program terminated by executing past its last instruction
+    pub fn terminated(&self) -> bool {
+        self.instruction_pointer == self.program.operations.len()
+    }
+
+    /// Change the operation at a line of the program
+    pub fn patch(&mut self, line: usize, operation: Operation) -> Result<()> {
+        let current = self
+            .program
+            .operations
+            .get_mut(line)
+            .wrap_err_with(|| format!("No line {line} to patch"))?;
+        *current = operation;
+        Ok(())
+    }
+
+    /// Move the instruction pointer by an offset
+    fn jump(&mut self, offset: Int) -> Result<()> {
+        let line = self.instruction_pointer;
+        let target = line as Int + offset;
+        match usize::try_from(target) {
+            Ok(target) if target <= self.program.operations.len() => {
+                self.instruction_pointer = target;
+                Ok(())
+            }
+            _ => bail!("The jump at line {line} goes out of the program to {target}"),
+        }
+    }
+}
+
+impl Machine for Handheld {
+    /// The instruction pointer and the accumulator
+    type State = (usize, Int);
+    type Output = Int;
+    type Error = Report;
+
+    /// Execute the next operation in this program, halting past the end of the operations
+    fn step(&mut self) -> Result<Step<Int>> {
+        let Some(operation) = self.program.operations.get(self.instruction_pointer) else {
+            return Ok(Step::Halted);
+        };
+        let mut output = None;
+        match *operation {
+            Operation::Acc(value) => self.accumulator += value,
+            Operation::Mul(value) => self.accumulator *= value,
+            Operation::Jmp(offset) => return self.jump(offset).map(|_| Step::Running),
+            Operation::Jz(offset) if self.accumulator == 0 => {
+                return self.jump(offset).map(|_| Step::Running)
+            }
+            Operation::Jnz(offset) if self.accumulator != 0 => {
+                return self.jump(offset).map(|_| Step::Running)
+            }
+            Operation::Hlt => {
+                self.instruction_pointer = self.program.operations.len();
+                return Ok(Step::Running);
+            }
+            Operation::Out => output = Some(self.accumulator),
+            Operation::Jz(_) | Operation::Jnz(_) | Operation::Nop(_) => {}
+        }
+        self.instruction_pointer += 1;
+        Ok(output.map_or(Step::Running, Step::Output))
+    }
+
+    fn reset(&mut self) {
+        self.instruction_pointer = 0;
+        self.accumulator = 0;
+    }
+
+    fn position(&self) -> usize {
+        self.instruction_pointer
+    }
+
+    fn state(&self) -> Self::State {
+        (self.instruction_pointer, self.accumulator)
+    }
+}
+
+impl FromStr for Operation {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (op, arg) = match s.trim().split_once(' ') {
+            Some((op, arg)) => (op, Some(arg.trim())),
+            None => (s.trim(), None),
+        };
+        let arg = || -> Result<Int> {
+            let arg = arg.wrap_err_with(|| format!("Missing the argument of '{s}'"))?;
+            arg.parse()
+                .wrap_err_with(|| format!("Could not parse the argument in '{arg}'"))
+        };
+        match op {
+            "acc" => Ok(Operation::Acc(arg()?)),
+            "mul" => Ok(Operation::Mul(arg()?)),
+            "jmp" => Ok(Operation::Jmp(arg()?)),
+            "jz" => Ok(Operation::Jz(arg()?)),
+            "jnz" => Ok(Operation::Jnz(arg()?)),
+            "nop" => Ok(Operation::Nop(arg()?)),
+            "out" => Ok(Operation::Out),
+            "hlt" => Ok(Operation::Hlt),
+            _ => Err(err!("Unknown operation name '{op}'")),
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Acc(value) => write!(f, "acc {value:+}"),
+            Self::Mul(value) => write!(f, "mul {value:+}"),
+            Self::Jmp(offset) => write!(f, "jmp {offset:+}"),
+            Self::Jz(offset) => write!(f, "jz {offset:+}"),
+            Self::Jnz(offset) => write!(f, "jnz {offset:+}"),
+            Self::Out => write!(f, "out"),
+            Self::Hlt => write!(f, "hlt"),
+            Self::Nop(value) => write!(f, "nop {value:+}"),
+        }
+    }
+}
+
+impl FromStr for Program {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let operations = s
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let code = line.split('#').next().unwrap_or_default().trim();
+                (!code.is_empty()).then(|| {
+                    code.parse()
+                        .wrap_err_with(|| format!("Invalid operation on line {}", index + 1))
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { operations })
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for operation in &self.operations {
+            writeln!(f, "{operation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,130 @@
+//! Find the corrupted operation that prevents a handheld program from terminating
+//!
+//! The lines that can reach the end of the program are found once by going backward from the end
+//! in the control flow graph. The program is then executed until it loops, and the first executed
+//! line whose patched operation leads to one of those lines is the one to fix.
+//! With conditional jumps the graph only tells which lines *may* reach the end, each candidate is
+//! then checked by executing the patched program.
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use commons::machine::{Detection, Machine, Outcome, Runner, Stop};
+use commons::{bail, Result};
+
+use super::graph::ControlFlow;
+use super::{Handheld, Int, Operation, Program};
+
+/// The maximum number of steps of a program with conditional jumps, whose loops are only found
+/// when the console returns to the same state
+const STEP_LIMIT: usize = 1_000_000;
+
+/// The patch of a single operation that makes a program terminate
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Repair {
+    /// The line of the corrupted operation
+    pub line: usize,
+    /// The corrupted operation
+    pub original: Operation,
+    /// The operation replacing it
+    pub patched: Operation,
+    /// The line the original program looped back to
+    pub looped_at: usize,
+    /// The line executed after the patched operation
+    pub next: usize,
+    /// The number of lines between the next line and the end, following the graph
+    pub distance: usize,
+    /// The accumulator when the patched program terminates
+    pub accumulator: Int,
+}
+
+/// Find the single operation to patch for a program to terminate
+/// # Errors
+/// If the program already terminates, or if no single patch makes it terminate
+pub fn repair(program: &Program) -> Result<Repair> {
+    let len = program.operations.len();
+    let reaching = ControlFlow::new(program).reaching_end();
+
+    let mut looping = Handheld::new(program.clone());
+    let looped_at = match run_checked(program, &mut looping)?.stop {
+        Stop::Looped(line) => line,
+        Stop::Limit => bail!("The program did not terminate after {STEP_LIMIT} steps"),
+        _ => bail!("The program already terminates"),
+    };
+
+    let mut handheld = Handheld::new(program.clone());
+    let mut executed = vec![false; len];
+    while let Some(&original) = program.operations.get(handheld.position()) {
+        let line = handheld.position();
+        if std::mem::replace(&mut executed[line], true) {
+            break;
+        }
+        if let Some(patched) = original.patched() {
+            let mut attempt = handheld.clone();
+            attempt.patch(line, patched)?;
+            // A jump out of the program cannot be the fix
+            if attempt.step().is_ok() {
+                let next = attempt.position();
+                if let Some(distance) = reaching.get(next).copied().flatten() {
+                    let terminated = run_checked(program, &mut attempt)
+                        .is_ok_and(|outcome| outcome.stop == Stop::Halted);
+                    if terminated && attempt.terminated() {
+                        return Ok(Repair {
+                            line,
+                            original,
+                            patched,
+                            looped_at,
+                            next,
+                            distance,
+                            accumulator: attempt.accumulator(),
+                        });
+                    }
+                }
+            }
+        }
+        handheld.step()?;
+    }
+    bail!("No single patch of a jmp, nop, jz or jnz makes the program terminate")
+}
+
+/// Run a console until it stops or loops.
+/// Without conditional jumps the execution does not depend on the accumulator, reaching the
+/// same line twice is then a loop. Otherwise the whole state must repeat.
+fn run_checked(program: &Program, handheld: &mut Handheld) -> Result<Outcome> {
+    let conditional = program
+        .operations
+        .iter()
+        .any(|operation| matches!(operation, Operation::Jz(_) | Operation::Jnz(_)));
+    if conditional {
+        Runner::new()
+            .with_detection(Detection::State)
+            .with_limit(STEP_LIMIT)
+            .run(handheld)
+    } else {
+        handheld.run_until_loop()
+    }
+}
+
+impl Display for Repair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "Patch the line {} from '{}' to '{}':",
+            self.line, self.original, self.patched
+        )?;
+        writeln!(
+            f,
+            "- Without the patch, the program loops back to the line {}",
+            self.looped_at
+        )?;
+        writeln!(
+            f,
+            "- Once patched it continues to the line {}, which can reach the end in {} steps",
+            self.next, self.distance
+        )?;
+        write!(
+            f,
+            "- The patched program terminates with the accumulator at {}",
+            self.accumulator
+        )
+    }
+}
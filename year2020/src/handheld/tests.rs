@@ -0,0 +1,86 @@
+use commons::machine::{Runner, Stop};
+
+use super::graph::ControlFlow;
+use super::repair::repair;
+use super::*;
+
+#[test]
+fn parse_and_display() {
+    let program: Program = "# Count down from 3
+acc +3
+out     # show it
+acc -1
+jnz -2
+
+mul +10
+jz +2
+nop +0
+hlt"
+    .parse()
+    .unwrap();
+    assert_eq!(program.operations.len(), 8);
+    assert_eq!(program.operations[3], Operation::Jnz(-2));
+    assert_eq!(
+        program.to_string(),
+        "acc +3\nout\nacc -1\njnz -2\nmul +10\njz +2\nnop +0\nhlt\n"
+    );
+
+    let mut handheld = Handheld::new(program);
+    let mut outputs = Vec::new();
+    let outcome = Runner::new()
+        .with_output(|value| outputs.push(value))
+        .run(&mut handheld)
+        .unwrap();
+    assert_eq!(outcome.stop, Stop::Halted);
+    assert_eq!(outputs, [3, 2, 1]);
+    assert!(handheld.terminated());
+    assert_eq!(handheld.accumulator(), 0);
+
+    assert!("jmp".parse::<Program>().is_err());
+    assert!("acc +1\nadd +2".parse::<Program>().is_err());
+}
+
+#[test]
+fn jump_out_of_program() {
+    let mut handheld = Handheld::new("nop +0\njmp -2".parse().unwrap());
+    let error = Runner::new().run(&mut handheld).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "The jump at line 1 goes out of the program to -1"
+    );
+}
+
+#[test]
+fn control_flow() {
+    let program: Program = "acc +1\njz +3\njmp -2\njmp +8\nhlt".parse().unwrap();
+    let graph = ControlFlow::new(&program);
+    assert_eq!(graph.end(), 5);
+    assert_eq!(graph.successors(1), [2, 4]);
+    // The jump out of the program has no successor
+    assert!(graph.successors(3).is_empty());
+    assert_eq!(graph.predecessors(0), [2]);
+    assert_eq!(graph.predecessors(5), [4]);
+    assert_eq!(
+        graph.reaching_end(),
+        [Some(3), Some(2), Some(4), None, Some(1), Some(0)]
+    );
+}
+
+#[test]
+fn repair_conditional() {
+    // The jz never jumps and stays in the loop forever: it should be a jnz
+    let program: Program = "acc +2\njz +2\njmp -1\nout".parse().unwrap();
+    let fix = repair(&program).unwrap();
+    assert_eq!((fix.line, fix.looped_at), (1, 1));
+    assert_eq!(fix.patched, Operation::Jnz(2));
+    assert_eq!((fix.next, fix.distance), (3, 1));
+    assert_eq!(fix.accumulator, 2);
+
+    let error = repair(&"acc +1\nout".parse().unwrap()).unwrap_err();
+    assert_eq!(error.to_string(), "The program already terminates");
+    let error = repair(&"acc +1\njmp -1\njmp -1".parse().unwrap()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "No single patch of a jmp, nop, jz or jnz makes the program terminate"
+    );
+}
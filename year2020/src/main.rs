@@ -1,4 +1,5 @@
 fn main() -> commons::error::Result<()> {
-    let args = commons::arguments::parse_arguments("Advent of Code 2020");
-    advent_of_code_2020::solve_problem(args.day, args.input)
+    let args =
+        commons::arguments::parse_arguments("Advent of Code 2020", advent_of_code_2020::OPTIONS);
+    advent_of_code_2020::solve_problem(args.day, args.input, &args.options)
 }
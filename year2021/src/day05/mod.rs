@@ -1,16 +1,24 @@
 use std::collections::{hash_map::Entry, HashMap};
 
+use commons::arguments::Options;
 use commons::grid::Point;
 use commons::math::{gcd, SignedInteger};
 use commons::parse::LineSep;
+use commons::svg::{Style, Svg};
 use commons::{Report, Result, WrapErr};
 
 pub const TITLE: &str = "Day 5: Hydrothermal Venture";
+pub const OPTIONS: &[&str] = &["svg"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(&raw)?;
     println!("1. Overlapping (no diagonals) {}", first_part(&data.data));
     println!("2. Overlapping (diagonals) {}", second_part(&data.data));
+
+    if let Some(path) = options.values("svg").first() {
+        drawing(&data.data).save(path)?;
+        println!("The vents were drawn in {path}");
+    }
     Ok(())
 }
 
@@ -19,22 +27,22 @@ fn parse(s: &str) -> Result<LineSep<Segment>> {
 }
 
 fn first_part(segments: &[Segment]) -> usize {
-    overlapping_points(segments, false)
+    overlapping_points(segments, false).len()
 }
 
 fn second_part(segments: &[Segment]) -> usize {
-    overlapping_points(segments, true)
+    overlapping_points(segments, true).len()
 }
 
-/// Count the overlapping points of those segments
+/// Find the overlapping points of those segments
 ///
 /// ### Params
-/// * `segments` - The segments to count the points for
-/// * `use_diagonals` - False to omit diagonal segments during the counting
+/// * `segments` - The segments to find the points for
+/// * `use_diagonals` - False to omit diagonal segments
 ///
 /// ### Returns
-/// The number of points covered by two segments or more
-fn overlapping_points(segments: &[Segment], use_diagonals: bool) -> usize {
+/// The points covered by two segments or more
+fn overlapping_points(segments: &[Segment], use_diagonals: bool) -> Vec<Point<i16>> {
     // Map points to: false -> one segment, true -> overlapping
     let mut points = HashMap::with_capacity(segments.len());
     for s in segments {
@@ -50,7 +58,32 @@ fn overlapping_points(segments: &[Segment], use_diagonals: bool) -> usize {
         }
     }
 
-    points.into_iter().filter(|(_, overlap)| *overlap).count()
+    points
+        .into_iter()
+        .filter_map(|(point, overlap)| overlap.then_some(point))
+        .collect()
+}
+
+/// Draw the segments, the diagonal ones in another color, and the points where they overlap
+fn drawing(segments: &[Segment]) -> Svg {
+    let position = |point: Point<i16>| (point.x as f64, point.y as f64);
+    let straight = Style::stroke([0x30, 0x60, 0xd0]).with_opacity(0.6);
+    let diagonal = Style::stroke([0xe0, 0x90, 0x20]).with_opacity(0.6);
+
+    let mut svg = Svg::new().with_background([0xff; 3]);
+    for segment in segments {
+        let style = if segment.is_diagonal() {
+            &diagonal
+        } else {
+            &straight
+        };
+        svg.line(position(segment.from), position(segment.to), style);
+    }
+    let overlap = Style::fill([0xd0, 0x20, 0x20]).with_width(1.0);
+    for point in overlapping_points(segments, true) {
+        svg.dot(position(point), &overlap);
+    }
+    svg
 }
 
 /// A segment for the puzzle
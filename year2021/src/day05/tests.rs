@@ -26,3 +26,13 @@ fn second_part_main() {
     let segments = parse(MAIN).unwrap();
     assert_eq!(second_part(&segments.data), 22_335);
 }
+
+#[test]
+fn drawing_example() {
+    let segments = parse(EXAMPLE).unwrap();
+    let mut svg = Vec::new();
+    drawing(&segments.data).write(&mut svg).unwrap();
+    let svg = String::from_utf8(svg).unwrap();
+    assert_eq!(svg.matches("<line").count(), 10);
+    assert_eq!(svg.matches("<circle").count(), 12);
+}
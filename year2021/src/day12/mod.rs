@@ -1,11 +1,18 @@
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::{ensure, err, Result, WrapErr};
 
 pub const TITLE: &str = "Day 12: Passage Pathing";
+pub const OPTIONS: &[&str] = &["dot"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(&raw)?;
     println!("1. Paths count is {}", first_part(&data)?);
     println!("2. Paths count is {}", second_part(&data)?);
+    if let Some(path) = options.values("dot").first() {
+        caves_graph(&data).save(path)?;
+        println!("The caves were described in {path}");
+    }
     Ok(())
 }
 
@@ -101,6 +108,28 @@ fn count_paths(paths: &Paths, can_visit_small_twice: bool) -> Result<usize> {
     Ok(finished)
 }
 
+/// The caves as an undirected graph: the big caves are boxes, the start and the end are doubled
+fn caves_graph(paths: &Paths) -> Dot {
+    let mut dot = Dot::undirected("caves");
+    for (index, cave) in paths.caves.iter().enumerate() {
+        let shape = if index == paths.start || index == paths.end {
+            "doublecircle"
+        } else if cave.small {
+            "circle"
+        } else {
+            "box"
+        };
+        dot.node(&cave.id, &[("shape", shape)]);
+    }
+    for (index, cave) in paths.caves.iter().enumerate() {
+        // Each path is in both caves, only keep it once
+        for next in cave.paths.iter().filter(|&&next| next > index) {
+            dot.edge(&cave.id, &paths.caves[*next].id, &[]);
+        }
+    }
+    dot
+}
+
 /// The paths for the puzzle
 struct Paths {
     /// The index of the entry
@@ -39,3 +39,13 @@ fn second_part_main() {
     let paths = parse(MAIN).unwrap();
     assert_eq!(second_part(&paths).unwrap(), 147_784);
 }
+
+#[test]
+fn caves_graph_example_1() {
+    let paths = parse(EXAMPLE_ONE).unwrap();
+    let dot = caves_graph(&paths);
+    assert_eq!((dot.nodes(), dot.edges()), (6, 7));
+    let dot = dot.to_string();
+    assert!(dot.contains("\"A\" [shape=\"box\"];"));
+    assert!(dot.contains("\"start\" -- \"A\";"));
+}
@@ -4,6 +4,7 @@ use itertools::Itertools;
 use std::collections::HashSet;
 
 use commons::grid::Point;
+use commons::ocr;
 use commons::parse::sep_by_empty_lines;
 use commons::{err, Result, WrapErr};
 
@@ -15,6 +16,7 @@ pub fn run(raw: String) -> Result<()> {
     println!("1. Dots after first fold: {}", origami.count());
     origami.fold_all();
     println!("2. Final origami:\n{origami}");
+    println!("It reads {}", origami.text()?);
     Ok(())
 }
 
@@ -77,6 +79,16 @@ impl Origami {
         self.dots.len()
     }
 
+    /// Read the letters formed by the dots
+    fn text(&self) -> Result<String> {
+        let dots: Vec<Point> = self
+            .dots
+            .iter()
+            .map(|dot| Point::new(dot.x.into(), dot.y.into()))
+            .collect();
+        ocr::read_points(&dots)
+    }
+
     /// Resolve all remaining folds of the origami
     fn fold_all(&mut self) {
         loop {
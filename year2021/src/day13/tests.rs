@@ -21,6 +21,6 @@ fn main() {
     origami.fold_once();
     assert_eq!(origami.count(), 745);
     origami.fold_all();
-    // This spells 'ABKJFBGC'
     itertools::assert_equal(format!("{}", origami).lines(), MAIN_RESULT.lines());
+    assert_eq!(origami.text().unwrap(), "ABKJFBGC");
 }
@@ -0,0 +1,216 @@
+//! The Arithmetic Logic Unit of the submarine, with its 4 registers `w`, `x`, `y` and `z`
+
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
+
+use commons::machine::{Machine, Step};
+use commons::{ensure, err, Report, Result, WrapErr};
+
+/// The names of the registers
+const NAMES: [char; 4] = ['w', 'x', 'y', 'z'];
+
+/// The index of the `z` register
+pub const Z: usize = 3;
+
+/// The second operand of an instruction
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operand {
+    Register(usize),
+    Value(i64),
+}
+
+/// An operation of the ALU
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    Add,
+    Mul,
+    Div,
+    Mod,
+    Eql,
+}
+
+/// An instruction of the ALU
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Instruction {
+    /// Read the next input into a register
+    Inp(usize),
+    /// Store in a register the result of an operation on it and an operand
+    Binary(Op, usize, Operand),
+}
+
+/// The ALU executing a program with some inputs
+#[derive(Debug, Clone)]
+pub struct Alu<'a> {
+    program: &'a [Instruction],
+    /// The next instruction to execute
+    line: usize,
+    registers: [i64; 4],
+    /// The inputs not read yet
+    inputs: VecDeque<i64>,
+}
+
+impl Op {
+    /// Apply this operation, failing like the ALU for a division by zero or an invalid modulo
+    pub fn apply(self, a: i64, b: i64) -> Result<i64> {
+        Ok(match self {
+            Self::Add => a + b,
+            Self::Mul => a * b,
+            Self::Div => {
+                ensure!(b != 0, "Division of {a} by zero");
+                a / b
+            }
+            Self::Mod => {
+                ensure!(a >= 0 && b > 0, "Invalid modulo {a} % {b}");
+                a % b
+            }
+            Self::Eql => i64::from(a == b),
+        })
+    }
+}
+
+impl<'a> Alu<'a> {
+    /// Create an ALU ready to execute a program with these inputs
+    pub fn new(program: &'a [Instruction], inputs: impl IntoIterator<Item = i64>) -> Self {
+        Self {
+            program,
+            line: 0,
+            registers: [0; 4],
+            inputs: inputs.into_iter().collect(),
+        }
+    }
+
+    /// Start with some values in the registers instead of 0
+    pub fn with_registers(mut self, registers: [i64; 4]) -> Self {
+        self.registers = registers;
+        self
+    }
+
+    /// Execute the whole program
+    /// # Returns
+    /// The registers at the end
+    /// # Errors
+    /// If an instruction failed or if the program needed more inputs
+    pub fn execute(mut self) -> Result<[i64; 4]> {
+        loop {
+            match self.step()? {
+                Step::Halted => return Ok(self.registers),
+                Step::Blocked => return Err(err!("Missing an input on line {}", self.line)),
+                Step::Running | Step::Output(_) => {}
+            }
+        }
+    }
+}
+
+impl Machine for Alu<'_> {
+    /// The next instruction and the registers
+    type State = (usize, [i64; 4]);
+    type Output = ();
+    type Error = Report;
+
+    fn step(&mut self) -> Result<Step<()>> {
+        let Some(instruction) = self.program.get(self.line) else {
+            return Ok(Step::Halted);
+        };
+        match *instruction {
+            Instruction::Inp(register) => match self.inputs.pop_front() {
+                Some(input) => self.registers[register] = input,
+                None => return Ok(Step::Blocked),
+            },
+            Instruction::Binary(op, register, operand) => {
+                let b = match operand {
+                    Operand::Register(other) => self.registers[other],
+                    Operand::Value(value) => value,
+                };
+                self.registers[register] = op
+                    .apply(self.registers[register], b)
+                    .wrap_err_with(|| format!("On line {} ({instruction})", self.line))?;
+            }
+        }
+        self.line += 1;
+        Ok(Step::Running)
+    }
+
+    /// Restart the program, the inputs already read are lost
+    fn reset(&mut self) {
+        self.line = 0;
+        self.registers = [0; 4];
+    }
+
+    fn position(&self) -> usize {
+        self.line
+    }
+
+    fn state(&self) -> Self::State {
+        (self.line, self.registers)
+    }
+}
+
+/// Parse the name of a register
+fn register(s: &str) -> Result<usize> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(name), None) => NAMES
+            .iter()
+            .position(|n| *n == name)
+            .wrap_err_with(|| format!("Unknown register '{s}'")),
+        _ => Err(err!("Unknown register '{s}'")),
+    }
+}
+
+impl FromStr for Instruction {
+    type Err = Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts[..] {
+            ["inp", a] => Ok(Self::Inp(register(a)?)),
+            [op, a, b] => {
+                let op = match op {
+                    "add" => Op::Add,
+                    "mul" => Op::Mul,
+                    "div" => Op::Div,
+                    "mod" => Op::Mod,
+                    "eql" => Op::Eql,
+                    _ => return Err(err!("Unknown operation '{op}'")),
+                };
+                let b = match b.parse() {
+                    Ok(value) => Operand::Value(value),
+                    Err(_) => Operand::Register(register(b)?),
+                };
+                Ok(Self::Binary(op, register(a)?, b))
+            }
+            _ => Err(err!("Bad instruction format '{s}'")),
+        }
+    }
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Inp(a) => write!(f, "inp {}", NAMES[*a]),
+            Self::Binary(op, a, b) => {
+                let op = match op {
+                    Op::Add => "add",
+                    Op::Mul => "mul",
+                    Op::Div => "div",
+                    Op::Mod => "mod",
+                    Op::Eql => "eql",
+                };
+                write!(f, "{op} {}", NAMES[*a])?;
+                match b {
+                    Operand::Register(b) => write!(f, " {}", NAMES[*b]),
+                    Operand::Value(b) => write!(f, " {b}"),
+                }
+            }
+        }
+    }
+}
+
+/// Parse a program, one instruction per line
+pub fn parse(s: &str) -> Result<Vec<Instruction>> {
+    s.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
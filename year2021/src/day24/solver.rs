@@ -0,0 +1,296 @@
+//! Find the model numbers accepted by any ALU program
+//!
+//! The program is split before each `inp` instruction, the digits are then chosen one by one
+//! (depth first, from the preferred digit). Before going deeper, the remaining instructions are
+//! evaluated on intervals of values, with each input between 1 and 9: if `z` cannot end at 0 the
+//! whole branch is dropped. The states already known to fail are also remembered, ignoring the
+//! registers that are written before being read by the remaining instructions.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use commons::{bail, ensure, Result};
+
+use super::alu::{Alu, Instruction, Op, Operand, Z};
+
+/// All the values an ALU register can have, from `min` to `max` (included)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Interval {
+    pub min: i64,
+    pub max: i64,
+}
+
+/// The range of the digits of a model number
+const DIGITS: Interval = Interval { min: 1, max: 9 };
+
+/// Searches the model numbers accepted by a program
+#[derive(Debug, Clone)]
+pub struct Solver<'a> {
+    program: &'a [Instruction],
+    /// The instructions executed after each input (starting by that input)
+    segments: Vec<Range<usize>>,
+    /// The registers before the first input
+    start: [i64; 4],
+    /// The registers whose value is read by the remaining instructions, at the start of each segment
+    live: Vec<[bool; 4]>,
+    /// The segments and registers from which no accepted number can be found
+    failed: HashSet<(usize, [i64; 4])>,
+}
+
+impl Interval {
+    /// The interval of a single value
+    pub fn exact(value: i64) -> Self {
+        Self {
+            min: value,
+            max: value,
+        }
+    }
+
+    /// Check if a value is in this interval
+    pub fn contains(self, value: i64) -> bool {
+        self.min <= value && value <= self.max
+    }
+
+    /// Build the smallest interval containing some values
+    fn hull(values: impl IntoIterator<Item = i64>) -> Option<Self> {
+        values.into_iter().fold(None, |hull: Option<Self>, value| {
+            Some(match hull {
+                Some(hull) => Self {
+                    min: hull.min.min(value),
+                    max: hull.max.max(value),
+                },
+                None => Self::exact(value),
+            })
+        })
+    }
+
+    /// The values that an operation can produce, when its operands are in these intervals
+    /// # Returns
+    /// None if the operation always fails
+    pub fn apply(op: Op, a: Self, b: Self) -> Option<Self> {
+        match op {
+            Op::Add => Some(Self {
+                min: a.min.saturating_add(b.min),
+                max: a.max.saturating_add(b.max),
+            }),
+            Op::Mul => Self::hull(
+                [
+                    (a.min, b.min),
+                    (a.min, b.max),
+                    (a.max, b.min),
+                    (a.max, b.max),
+                ]
+                .map(|(a, b)| a.saturating_mul(b)),
+            ),
+            Op::Div => {
+                // The division is monotonic on each side of 0, which is not a valid divisor
+                let divisors = [b.min, b.max, -1, 1].into_iter().filter(|d| *d != 0);
+                let divisors: Vec<i64> = divisors.filter(|d| b.contains(*d)).collect();
+                Self::hull(
+                    divisors
+                        .iter()
+                        .flat_map(|d| [a.min / d, a.max / d].into_iter()),
+                )
+            }
+            Op::Mod => {
+                // Only a positive value modulo a strictly positive one is valid
+                let a = Self {
+                    min: a.min.max(0),
+                    max: a.max,
+                };
+                let b = Self {
+                    min: b.min.max(1),
+                    max: b.max,
+                };
+                if a.min > a.max || b.min > b.max {
+                    None
+                } else if b.min == b.max && a.min / b.min == a.max / b.min {
+                    Some(Self {
+                        min: a.min % b.min,
+                        max: a.max % b.min,
+                    })
+                } else {
+                    Some(Self {
+                        min: 0,
+                        max: a.max.min(b.max - 1),
+                    })
+                }
+            }
+            Op::Eql => Some(if a.max < b.min || b.max < a.min {
+                Self::exact(0)
+            } else if a.min == a.max && a == b {
+                Self::exact(1)
+            } else {
+                Self { min: 0, max: 1 }
+            }),
+        }
+    }
+}
+
+/// Evaluate instructions on intervals
+/// # Arguments
+/// * `instructions` - The instructions to evaluate
+/// * `registers` - The values each register can have before the instructions
+/// * `inputs` - The values each input can have
+///
+/// # Returns
+/// The values each register can have after the instructions, None if they always fail
+pub fn bounds(
+    instructions: &[Instruction],
+    mut registers: [Interval; 4],
+    inputs: Interval,
+) -> Option<[Interval; 4]> {
+    for instruction in instructions {
+        match *instruction {
+            Instruction::Inp(register) => registers[register] = inputs,
+            Instruction::Binary(op, register, operand) => {
+                let b = match operand {
+                    Operand::Register(other) => registers[other],
+                    Operand::Value(value) => Interval::exact(value),
+                };
+                registers[register] = Interval::apply(op, registers[register], b)?;
+            }
+        }
+    }
+    Some(registers)
+}
+
+impl<'a> Solver<'a> {
+    /// Prepare the search of the model numbers accepted by a program
+    /// # Errors
+    /// If the instructions before the first input fail
+    pub fn new(program: &'a [Instruction]) -> Result<Self> {
+        let inputs: Vec<usize> = program
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| matches!(instruction, Instruction::Inp(_)))
+            .map(|(line, _)| line)
+            .collect();
+        let first = inputs.first().copied().unwrap_or(program.len());
+        let segments: Vec<Range<usize>> = inputs
+            .iter()
+            .enumerate()
+            .map(|(i, start)| *start..inputs.get(i + 1).copied().unwrap_or(program.len()))
+            .collect();
+        let live = segments
+            .iter()
+            .map(|segment| live(&program[segment.start..]))
+            .collect();
+        let start = Alu::new(&program[..first], []).execute()?;
+        Ok(Self {
+            program,
+            segments,
+            start,
+            live,
+            failed: HashSet::new(),
+        })
+    }
+
+    /// The number of digits of the model numbers
+    pub fn digits(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Find the largest model number accepted by the program
+    pub fn largest(&mut self) -> Result<i64> {
+        self.find(&[9, 8, 7, 6, 5, 4, 3, 2, 1])
+    }
+
+    /// Find the smallest model number accepted by the program
+    pub fn smallest(&mut self) -> Result<i64> {
+        self.find(&[1, 2, 3, 4, 5, 6, 7, 8, 9])
+    }
+
+    /// Find the first model number accepted by the program, trying the digits in this order
+    /// # Errors
+    /// If no model number is accepted, or if the one found is not accepted when executed
+    fn find(&mut self, order: &[i64]) -> Result<i64> {
+        let mut digits = Vec::with_capacity(self.digits());
+        ensure!(
+            self.search(0, self.start, order, &mut digits),
+            "No model number of {} digits is accepted",
+            self.digits()
+        );
+
+        let registers = Alu::new(self.program, digits.iter().copied()).execute()?;
+        if registers[Z] != 0 {
+            bail!("The model number {digits:?} was not accepted: {registers:?}");
+        }
+        Ok(digits.iter().fold(0, |number, digit| number * 10 + digit))
+    }
+
+    /// Search the digits accepted from a segment with some registers
+    /// # Returns
+    /// True if they were found (they are then in `digits`), false otherwise
+    fn search(
+        &mut self,
+        segment: usize,
+        registers: [i64; 4],
+        order: &[i64],
+        digits: &mut Vec<i64>,
+    ) -> bool {
+        let Some(range) = self.segments.get(segment).cloned() else {
+            return registers[Z] == 0;
+        };
+        // The registers that are not read have no effect, they can be ignored
+        let mut registers = registers;
+        for (register, live) in registers.iter_mut().zip(self.live[segment]) {
+            if !live {
+                *register = 0;
+            }
+        }
+        if self.failed.contains(&(segment, registers)) || !self.may_accept(segment, registers) {
+            return false;
+        }
+
+        for digit in order {
+            let alu = Alu::new(&self.program[range.clone()], [*digit]).with_registers(registers);
+            // An instruction failing stops the program, the number is then not accepted
+            let Ok(next) = alu.execute() else {
+                continue;
+            };
+            digits.push(*digit);
+            if self.search(segment + 1, next, order, digits) {
+                return true;
+            }
+            digits.pop();
+        }
+        self.failed.insert((segment, registers));
+        false
+    }
+
+    /// Check if `z` can end at 0 from a segment with some registers
+    fn may_accept(&self, segment: usize, registers: [i64; 4]) -> bool {
+        let start = self.segments[segment].start;
+        bounds(
+            &self.program[start..],
+            registers.map(Interval::exact),
+            DIGITS,
+        )
+        .is_some_and(|registers| registers[Z].contains(0))
+    }
+}
+
+/// Find the registers whose initial value is read by some instructions
+fn live(instructions: &[Instruction]) -> [bool; 4] {
+    let mut live = [false; 4];
+    let mut written = [false; 4];
+    for instruction in instructions {
+        let (register, read) = match *instruction {
+            Instruction::Inp(register) => (register, None),
+            // The result does not depend on the register
+            Instruction::Binary(Op::Mul, register, Operand::Value(0)) => (register, None),
+            Instruction::Binary(_, register, Operand::Register(other)) => (register, Some(other)),
+            Instruction::Binary(_, register, Operand::Value(_)) => (register, None),
+        };
+        let reads_register = !matches!(
+            instruction,
+            Instruction::Inp(_) | Instruction::Binary(Op::Mul, _, Operand::Value(0))
+        );
+        for read in read.into_iter().chain(reads_register.then_some(register)) {
+            live[read] |= !written[read];
+        }
+        written[register] = true;
+    }
+    live
+}
@@ -8,3 +8,65 @@ fn main() {
     assert_eq!(min, 18_116_121_134_117);
     assert_eq!(max, 39_999_698_799_429);
 }
+
+#[test]
+fn alu_examples() {
+    use alu::{parse, Alu};
+
+    let negate = parse("inp x\nmul x -1").unwrap();
+    assert_eq!(Alu::new(&negate, [7]).execute().unwrap(), [0, -7, 0, 0]);
+
+    let binary = parse(
+        "inp w\nadd z w\nmod z 2\ndiv w 2\nadd y w\nmod y 2\ndiv w 2\nadd x w\nmod x 2\ndiv w 2\nmod w 2",
+    )
+    .unwrap();
+    assert_eq!(Alu::new(&binary, [13]).execute().unwrap(), [1, 1, 0, 1]);
+    assert_eq!(binary[2].to_string(), "mod z 2");
+
+    let error = Alu::new(&negate, []).execute().unwrap_err();
+    assert_eq!(error.to_string(), "Missing an input on line 0");
+    let error = Alu::new(&parse("inp w\ndiv z w").unwrap(), [0])
+        .execute()
+        .unwrap_err();
+    assert!(format!("{error:?}").contains("Division of 0 by zero"));
+    assert!(parse("inp a").is_err());
+    assert!(parse("sub x 1").is_err());
+}
+
+#[test]
+fn intervals() {
+    use solver::{bounds, Interval};
+
+    let digit = Interval { min: 1, max: 9 };
+    let registers = [Interval::exact(0); 4];
+    let program = alu::parse("inp w\nadd x w\nmul x -3\nmod w 5\neql y 12\ndiv x 2").unwrap();
+    assert_eq!(
+        bounds(&program, registers, digit),
+        Some([
+            Interval { min: 0, max: 4 },
+            Interval { min: -13, max: -1 },
+            Interval::exact(0),
+            Interval::exact(0),
+        ])
+    );
+    // A modulo of a negative value always fails
+    let program = alu::parse("inp w\nmul w -1\nmod w 3").unwrap();
+    assert_eq!(bounds(&program, registers, digit), None);
+}
+
+#[test]
+fn any_program() {
+    // Accepts the numbers whose second digit is twice the first, and the third one is odd
+    let program = alu::parse(
+        "inp w\nmul w 2\ninp x\neql w x\neql w 0\nadd z w\ninp y\nmod y 2\nadd y -1\nmul y y\nadd z y",
+    )
+    .unwrap();
+    let mut solver = Solver::new(&program).unwrap();
+    assert_eq!(solver.digits(), 3);
+    assert_eq!(solver.largest().unwrap(), 489);
+    assert_eq!(solver.smallest().unwrap(), 121);
+
+    let never = alu::parse("inp w\nadd z w").unwrap();
+    let error = Solver::new(&never).unwrap().largest().unwrap_err();
+    assert_eq!(error.to_string(), "No model number of 1 digits is accepted");
+}
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use commons::arguments::Day;
+use commons::arguments::{Day, Options};
 use commons::problem::{solve_quiet, solve_verbose};
 use commons::Result;
 
@@ -30,21 +30,24 @@ mod day23;
 mod day24;
 mod day25;
 
+/// The options of the solutions of this year, the other options are rejected
+pub const OPTIONS: &[&[&str]] = &[day05::OPTIONS, day12::OPTIONS];
+
 /// Dispatch to the correct problem and solve it
-pub fn solve_problem(day: Day, input: PathBuf) -> Result<()> {
+pub fn solve_problem(day: Day, input: PathBuf, options: &Options) -> Result<()> {
     match day {
         Day::Day1 => solve_verbose(day01::TITLE, input, day01::run),
         Day::Day2 => solve_verbose(day02::TITLE, input, day02::run),
         Day::Day3 => solve_verbose(day03::TITLE, input, day03::run),
         Day::Day4 => solve_verbose(day04::TITLE, input, day04::run),
-        Day::Day5 => solve_verbose(day05::TITLE, input, day05::run),
+        Day::Day5 => solve_verbose(day05::TITLE, input, |raw| day05::run(raw, options)),
         Day::Day6 => solve_verbose(day06::TITLE, input, day06::run),
         Day::Day7 => solve_verbose(day07::TITLE, input, day07::run),
         Day::Day8 => solve_verbose(day08::TITLE, input, day08::run),
         Day::Day9 => solve_verbose(day09::TITLE, input, day09::run),
         Day::Day10 => solve_verbose(day10::TITLE, input, day10::run),
         Day::Day11 => solve_verbose(day11::TITLE, input, day11::run),
-        Day::Day12 => solve_verbose(day12::TITLE, input, day12::run),
+        Day::Day12 => solve_verbose(day12::TITLE, input, |raw| day12::run(raw, options)),
         Day::Day13 => solve_verbose(day13::TITLE, input, day13::run),
         Day::Day14 => solve_verbose(day14::TITLE, input, day14::run),
         Day::Day15 => solve_verbose(day15::TITLE, input, day15::run),
@@ -58,25 +61,25 @@ pub fn solve_problem(day: Day, input: PathBuf) -> Result<()> {
         Day::Day23 => solve_verbose(day23::TITLE, input, day23::run),
         Day::Day24 => solve_verbose(day24::TITLE, input, day24::run),
         Day::Day25 => solve_verbose(day25::TITLE, input, day25::run),
-        Day::All => solve_all(input),
+        Day::All => solve_all(input, options),
     }
 }
 
 /// Solve all the problems for this year in a row, timing them all
-pub fn solve_all(dir: PathBuf) -> Result<()> {
-    fn all(dir: PathBuf) -> Result<()> {
+pub fn solve_all(dir: PathBuf, options: &Options) -> Result<()> {
+    fn all(dir: PathBuf, options: &Options) -> Result<()> {
         solve_quiet(1, dir.join("01.txt"), day01::run)?;
         solve_quiet(2, dir.join("02.txt"), day02::run)?;
         solve_quiet(3, dir.join("03.txt"), day03::run)?;
         solve_quiet(4, dir.join("04.txt"), day04::run)?;
-        solve_quiet(5, dir.join("05.txt"), day05::run)?;
+        solve_quiet(5, dir.join("05.txt"), |raw| day05::run(raw, options))?;
         solve_quiet(6, dir.join("06.txt"), day06::run)?;
         solve_quiet(7, dir.join("07.txt"), day07::run)?;
         solve_quiet(8, dir.join("08.txt"), day08::run)?;
         solve_quiet(9, dir.join("09.txt"), day09::run)?;
         solve_quiet(10, dir.join("10.txt"), day10::run)?;
         solve_quiet(11, dir.join("11.txt"), day11::run)?;
-        solve_quiet(12, dir.join("12.txt"), day12::run)?;
+        solve_quiet(12, dir.join("12.txt"), |raw| day12::run(raw, options))?;
         solve_quiet(13, dir.join("13.txt"), day13::run)?;
         solve_quiet(14, dir.join("14.txt"), day14::run)?;
         solve_quiet(15, dir.join("15.txt"), day15::run)?;
@@ -94,7 +97,7 @@ pub fn solve_all(dir: PathBuf) -> Result<()> {
     }
 
     let start = std::time::Instant::now();
-    let result = all(dir);
+    let result = all(dir, options);
     let elapsed = start.elapsed();
     println!("\n\nSolve time: {:}ms", elapsed.as_millis());
 
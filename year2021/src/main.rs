@@ -1,4 +1,5 @@
 fn main() -> commons::error::Result<()> {
-    let args = commons::arguments::parse_arguments("Advent of Code 2021");
-    advent_of_code_2021::solve_problem(args.day, args.input)
+    let args =
+        commons::arguments::parse_arguments("Advent of Code 2021", advent_of_code_2021::OPTIONS);
+    advent_of_code_2021::solve_problem(args.day, args.input, &args.options)
 }
@@ -1,9 +1,12 @@
 use std::str::FromStr;
 
 use commons::error::{Result, WrapErr};
+use commons::ocr;
 use commons::parse::LineSep;
 use commons::{err, Report};
 
+use crate::device::{self, Cpu, Screen, SignalProbe};
+
 pub const TITLE: &str = "Day 10: Cathode-Ray Tube";
 
 pub fn run(raw: String) -> Result<()> {
@@ -12,65 +15,49 @@ pub fn run(raw: String) -> Result<()> {
     println!("1. The signal sum is {first}");
     let second = second_part(&data);
     println!("2. The display after running the program is:\n{second}");
+    println!("It reads {}", ocr::read(second.pixels())?);
 
     Ok(())
 }
 
-fn first_part(inst: &[Instruction]) -> i32 {
-    let mut sums: i32 = 0;
-    execute_cycles(inst, 220, |cycle, register| {
-        if cycle % 40 == 20 {
-            sums += cycle as i32 * register;
-        }
-    });
+/// The size of the screen of the device
+const WIDTH: usize = 40;
+const HEIGHT: usize = 6;
 
-    sums
+fn first_part(inst: &[Instruction]) -> i64 {
+    let mut probe = SignalProbe::new(20, 40);
+    Cpu::new(inst).with_device(&mut probe).execute(220);
+    probe.sum
 }
 
-fn second_part(inst: &[Instruction]) -> String {
-    const HEIGHT: usize = 6;
-    const WIDTH: usize = 40;
-    let mut screen = String::with_capacity((WIDTH + 1) * HEIGHT);
-    execute_cycles(inst, WIDTH * HEIGHT, |cycle, register| {
-        let position = ((cycle - 1) % WIDTH) as i32;
-        if position >= register - 1 && position <= register + 1 {
-            screen.push('#');
-        } else {
-            screen.push('.');
-        }
-
-        if cycle % 40 == 0 && cycle != WIDTH * HEIGHT {
-            screen.push('\n');
-        }
-    });
-
+fn second_part(inst: &[Instruction]) -> Screen {
+    let mut screen = Screen::new(WIDTH, HEIGHT);
+    let cycles = screen.cycles();
+    Cpu::new(inst).with_device(&mut screen).execute(cycles);
     screen
 }
 
-fn execute_cycles(
-    inst: &[Instruction],
-    cycles: usize,
-    mut during_each_cycle: impl FnMut(usize, i32),
-) {
-    let mut register: i32 = 1;
-    let mut pending_add: Option<i32> = None;
-    let mut instructions = inst.iter().copied();
-    for cycle in 0..cycles {
-        during_each_cycle(cycle + 1, register);
-        if let Some(to_add) = pending_add.take() {
-            register += to_add;
-        } else if let Some(Instruction::AddX(to_add)) = instructions.next() {
-            pending_add = Some(to_add as i32);
-        }
-    }
-}
-
 #[derive(Debug, Copy, Clone)]
 enum Instruction {
     Noop,
     AddX(i8),
 }
 
+impl device::Instruction for Instruction {
+    fn cycles(&self) -> usize {
+        match self {
+            Instruction::Noop => 1,
+            Instruction::AddX(_) => 2,
+        }
+    }
+
+    fn execute(&self, x: &mut i64) {
+        if let Instruction::AddX(amount) = self {
+            *x += i64::from(*amount);
+        }
+    }
+}
+
 impl FromStr for Instruction {
     type Err = Report;
 
@@ -109,7 +96,6 @@ mod tests {
 ######......######......######......####
 #######.......#######.......#######.....";
 
-    // PZBGZEJB
     const MAIN_SCREEN: &str = "\
 ###..####.###...##..####.####...##.###..
 #..#....#.#..#.#..#....#.#.......#.#..#.
@@ -133,7 +119,7 @@ mod tests {
     #[test]
     fn second_part_example() {
         assert_eq!(
-            second_part(&parse(EXAMPLE.into()).unwrap()).as_str(),
+            second_part(&parse(EXAMPLE.into()).unwrap()).to_string(),
             EXAMPLE_SCREEN,
             "expected:\n{}",
             EXAMPLE_SCREEN
@@ -142,11 +128,13 @@ mod tests {
 
     #[test]
     fn second_part_main() {
+        let screen = second_part(&parse(MAIN.into()).unwrap());
         assert_eq!(
-            second_part(&parse(MAIN.into()).unwrap()).as_str(),
+            screen.to_string(),
             MAIN_SCREEN,
             "expected:\n{}",
             MAIN_SCREEN
         );
+        assert_eq!(ocr::read(screen.pixels()).unwrap(), "PZBGZEJB");
     }
 }
@@ -1,15 +1,22 @@
+use commons::arguments::Options;
 use commons::error::Result;
+use commons::grid::image::Rgb;
 use commons::grid::{Grid, Point};
 use commons::{err, WrapErr};
 
 pub const TITLE: &str = "Day 14: Regolith Reservoir";
+pub const OPTIONS: &[&str] = &["image"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let mut data = parse(raw.into())?;
     let first = first_part(&mut data);
     println!("1. {first} units of sand were poured before the bottom was reached");
-    let second = second_part(data);
+    let second = second_part(&mut data);
     println!("2. {second} units of sand were poured before the source was blocked");
+    if let Some(path) = options.values("image").first() {
+        data.grid.to_image(2, |tile| tile.color()).save(path)?;
+        println!("The cave was saved in {path}");
+    }
 
     Ok(())
 }
@@ -19,7 +26,7 @@ fn first_part(cave: &mut Cave) -> usize {
     cave.sand_units - 1 // Last unit is on the bottom and not at rest
 }
 
-fn second_part(mut cave: Cave) -> usize {
+fn second_part(cave: &mut Cave) -> usize {
     while let Outcome::AtRest | Outcome::AtBottom = cave.pour_sand() {}
     cave.sand_units
 }
@@ -54,6 +61,15 @@ impl Tile {
             Self::Source => '+',
         }
     }
+
+    fn color(self) -> Rgb {
+        match self {
+            Self::Empty => [20, 20, 30],
+            Self::Wall => [120, 120, 120],
+            Self::Sand => [230, 200, 120],
+            Self::Source => [220, 40, 40],
+        }
+    }
 }
 
 impl Cave {
@@ -165,6 +181,31 @@ mod tests {
         assert_eq!(first_part(&mut data), 24);
     }
 
+    #[test]
+    fn first_part_example_cave() {
+        let mut data = parse(EXAMPLE.into()).unwrap();
+        first_part(&mut data);
+        // The sand at rest, from x = 494 to 503, as drawn in the puzzle
+        let expected = Grid::from_text(
+            "......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.",
+        );
+        let left = data.sand_source.x - 6;
+        let cave = Grid::tabulate(10, 10, |(x, y)| {
+            data.grid[(left + x as isize, y as isize)].to_char()
+        });
+        let diff = cave.diff(&expected, |c| *c);
+        assert!(diff.is_empty(), "{diff}");
+    }
+
     #[test]
     fn first_part_main() {
         let mut data = parse(MAIN.into()).unwrap();
@@ -173,13 +214,18 @@ mod tests {
 
     #[test]
     fn second_part_example() {
-        let data = parse(EXAMPLE.into()).unwrap();
-        assert_eq!(second_part(data), 93);
+        let mut data = parse(EXAMPLE.into()).unwrap();
+        assert_eq!(second_part(&mut data), 93);
+        let image = data.grid.to_image(3, |tile| tile.color());
+        assert_eq!(image.width(), 3 * data.grid.width());
+        assert_eq!(image.height(), 3 * data.grid.height());
+        let (x, y) = (data.sand_source.x as usize, data.sand_source.y as usize);
+        assert_eq!(image.get(3 * x + 2, 3 * y + 2), Some(Tile::Source.color()));
     }
 
     #[test]
     fn second_part_main() {
-        let data = parse(MAIN.into()).unwrap();
-        assert_eq!(second_part(data), 22_499);
+        let mut data = parse(MAIN.into()).unwrap();
+        assert_eq!(second_part(&mut data), 22_499);
     }
 }
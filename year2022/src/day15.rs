@@ -4,17 +4,20 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
+use commons::arguments::Options;
 use commons::error::Result;
 use commons::grid::Point;
 use commons::parse::LineSep;
+use commons::svg::{Style, Svg};
 use commons::{Report, WrapErr};
 
 pub const TITLE: &str = "Day 15: Beacon Exclusion Zone";
+pub const OPTIONS: &[&str] = &["svg"];
 const ROW: i64 = 2_000_000;
 const MIN_COORDINATE: i64 = 0;
 const MAX_COORDINATE: i64 = 4_000_000;
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data, ROW);
     println!("1. There are {first} points at Y={ROW} that are definitely not beacons");
@@ -22,6 +25,11 @@ pub fn run(raw: String) -> Result<()> {
     let second = second.wrap_err("distress beacon not found")?;
     println!("2. The distress beacon frequency is {second}");
 
+    if let Some(path) = options.values("svg").first() {
+        drawing(&data, ROW, MIN_COORDINATE..(MAX_COORDINATE + 1)).save(path)?;
+        println!("The sensors were drawn in {path}");
+    }
+
     Ok(())
 }
 
@@ -54,6 +62,11 @@ fn first_part(sensors: &[Sensor], y: i64) -> u64 {
 }
 
 fn second_part(sensors: &[Sensor], coordinates: Range<i64>) -> Option<i64> {
+    distress_beacon(sensors, coordinates).map(|found| found.x * MAX_COORDINATE + found.y)
+}
+
+/// Find the only position in the coordinates that is not covered by a sensor
+fn distress_beacon(sensors: &[Sensor], coordinates: Range<i64>) -> Option<Point> {
     // Y coordinate intersection at X=0 of lines that border each sensor exclusion zone
     let mut upward_lines_y: BTreeSet<i64> = BTreeSet::new(); // For lines with slope 1,1
     let mut downward_lines_y: BTreeSet<i64> = BTreeSet::new(); // For lines with slope -1,-1
@@ -95,7 +108,55 @@ fn second_part(sensors: &[Sensor], coordinates: Range<i64>) -> Option<i64> {
                 distance > min_distance
             })
         })
-        .map(|found| found.x * MAX_COORDINATE + found.y)
+}
+
+/// Draw the area covered by each sensor, the searched area with the row of the first part, and
+/// the distress beacon if it is found
+fn drawing(sensors: &[Sensor], row: i64, coordinates: Range<i64>) -> Svg {
+    let position = |point: Point| (point.x as f64, point.y as f64);
+    let (min, max) = (coordinates.start as f64, (coordinates.end - 1) as f64);
+
+    let mut svg = Svg::new().with_background([0xff; 3]);
+    let zone = Style::stroke([0x30, 0x60, 0xd0])
+        .with_fill([0x30, 0x60, 0xd0])
+        .with_opacity(0.25);
+    let sensor_dot = Style::fill([0x30, 0x60, 0xd0]).with_width(2.0);
+    let beacon_dot = Style::fill([0x20, 0x20, 0x20]).with_width(2.0);
+    let link = Style::stroke([0x20, 0x20, 0x20]).with_width(0.5);
+    for Sensor { sensor, beacon } in sensors {
+        let distance = (*sensor - beacon).manhattan_distance() as f64;
+        svg.diamond(position(*sensor), distance, &zone);
+        svg.line(position(*sensor), position(*beacon), &link);
+        svg.dot(position(*sensor), &sensor_dot);
+        svg.dot(position(*beacon), &beacon_dot);
+    }
+
+    let searched = Style::stroke([0x20, 0xa0, 0x20]).with_width(2.0);
+    svg.polygon([(min, min), (max, min), (max, max), (min, max)], &searched);
+    let row_style = Style::stroke([0xe0, 0x90, 0x20]);
+    let (left, right) = covered_columns(sensors);
+    svg.line((left, row as f64), (right, row as f64), &row_style);
+    svg.label((left, row as f64), &format!("y={row}"), &row_style);
+
+    if let Some(found) = distress_beacon(sensors, coordinates) {
+        let style = Style::fill([0xd0, 0x20, 0x20]).with_width(5.0);
+        svg.dot(position(found), &style);
+        svg.label(position(found), &format!("{},{}", found.x, found.y), &style);
+    }
+    svg
+}
+
+/// The smallest and the largest X covered by the sensors
+fn covered_columns(sensors: &[Sensor]) -> (f64, f64) {
+    sensors
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(left, right), sensor| {
+            let distance = (sensor.sensor - sensor.beacon).manhattan_distance();
+            (
+                left.min((sensor.sensor.x - distance) as f64),
+                right.max((sensor.sensor.x + distance) as f64),
+            )
+        })
 }
 
 #[derive(Debug)]
@@ -166,6 +227,20 @@ mod tests {
         assert_eq!(result, 56_000_011);
     }
 
+    #[test]
+    fn drawing_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let mut svg = Vec::new();
+        drawing(&data, 10, 0..21).write(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        // A diamond for each sensor and the searched area
+        assert_eq!(svg.matches("<polygon").count(), data.len() + 1);
+        // A line from each sensor to its beacon
+        let link = r##"stroke="#202020" fill="none" stroke-width="0.5""##;
+        assert_eq!(svg.matches(link).count(), data.len());
+        assert!(svg.contains(">14,11</text>"));
+    }
+
     #[test]
     fn second_part_main() {
         let data = parse(MAIN.into()).unwrap();
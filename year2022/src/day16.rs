@@ -4,18 +4,25 @@ use std::str::FromStr;
 
 use itertools::Itertools;
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::error::Result;
 use commons::parse::LineSep;
 use commons::{ensure, Report, WrapErr};
 
 pub const TITLE: &str = "Day 16: Proboscidea Volcanium";
+pub const OPTIONS: &[&str] = &["dot"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data);
     println!("1. The most pressure in 30 minutes is {first}");
     let second = second_part(&data);
     println!("2. The most pressure with two players in 26 minutes is {second}");
+    if let Some(path) = options.values("dot").first() {
+        data.graph().save(path)?;
+        println!("The distances between the valves were described in {path}");
+    }
 
     Ok(())
 }
@@ -100,6 +107,33 @@ impl Distances {
         })
     }
 
+    /// The valves to open (and the start) labelled with their flow rate, linked by their distance
+    fn graph(&self) -> Dot {
+        let mut dot = Dot::undirected("valves").with_attribute("layout", "neato");
+        for (index, valve) in self.to_open.iter().enumerate() {
+            let label = format!("{}\n{}", valve.name, valve.flow_rate);
+            let shape = if index == self.start {
+                "doublecircle"
+            } else {
+                "circle"
+            };
+            dot.node(
+                &valve.name.to_string(),
+                &[("label", &label), ("shape", shape)],
+            );
+        }
+        for (from, distances) in self.distances.iter().enumerate() {
+            // The distances are symmetric, only keep each pair once
+            for &(to, distance) in distances.iter().filter(|(to, _)| *to > from) {
+                let (from, to) = (self.to_open[from].name, self.to_open[to].name);
+                let distance = distance.to_string();
+                let attributes = [("label", distance.as_str()), ("len", distance.as_str())];
+                dot.edge(&from.to_string(), &to.to_string(), &attributes);
+            }
+        }
+        dot
+    }
+
     /// Find the maximum value reached for each combination of valves explored in the time given
     fn compute_max_values(&self, time_available: u32) -> HashMap<ValveSet, u32> {
         let mut max_values = HashMap::new();
@@ -253,6 +287,17 @@ mod tests {
         assert_eq!(second_part(&data), 1707);
     }
 
+    #[test]
+    fn graph_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let dot = data.graph();
+        // AA and the 6 valves with a flow rate, all linked to each other
+        assert_eq!((dot.nodes(), dot.edges()), (7, 21));
+        assert!(dot
+            .to_string()
+            .contains("\"AA\" -- \"JJ\" [label=\"2\", len=\"2\"];"));
+    }
+
     #[test]
     fn second_part_main() {
         let data = parse(MAIN.into()).unwrap();
@@ -1,18 +1,30 @@
+use commons::arguments::Options;
 use commons::err;
 use commons::error::Result;
+use commons::grid::gif::Recorder;
+use commons::grid::image::Rgb;
 use commons::grid::{Direction, Point};
 
 pub const TITLE: &str = "Day 23: Unstable Diffusion";
 
 type Coordinate = i16;
 
-pub fn run(raw: String) -> Result<()> {
-    let mut data = parse(raw.into())?;
+/// The colors of the animation: the ground and the elves
+const PALETTE: [Rgb; 2] = [[0x10, 0x30, 0x10], [0x90, 0xf0, 0x90]];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
+    let mut data = parse(raw.clone().into())?;
     let first = first_part(&mut data);
     println!("1. After 10 rounds there are {first} empty tiles in the bounding rectangle");
     let second = second_part(&mut data);
     println!("2. The first turn elves did not move is {second}");
 
+    if let Some((mut recorder, path)) = Recorder::from_options(options, &PALETTE)? {
+        animate(parse(raw.into())?, &mut recorder);
+        recorder.save(&path)?;
+        println!("The elves spreading out were saved in {path}");
+    }
+
     Ok(())
 }
 
@@ -29,6 +41,18 @@ fn second_part(elves: &mut Simulation) -> usize {
     elves.turns
 }
 
+/// Record the elves until they stop moving, the animation grows with their bounding rectangle
+fn animate(mut elves: Simulation, recorder: &mut Recorder) {
+    let mut capture = |elves: &Simulation| {
+        let (snapshot, corner) = elves.grid.snapshot();
+        recorder.capture_at(&snapshot, corner, |elf| u8::from(*elf));
+    };
+    capture(&elves);
+    while elves.next_turn() {
+        capture(&elves);
+    }
+}
+
 struct Simulation {
     grid: Grid,
     swap: Grid,
@@ -99,6 +123,36 @@ impl Grid {
         width * height.unwrap_or_default() - count
     }
 
+    /// The elves in their bounding rectangle, and the position of its top left corner
+    fn snapshot(&self) -> (commons::grid::Grid<bool>, (i64, i64)) {
+        let rows = self.rows.iter().zip(self.min_y..);
+        let elves: Vec<(Coordinate, Coordinate)> = rows
+            .flat_map(|(row, y)| row.iter().map(move |x| (*x, y)))
+            .collect();
+        if elves.is_empty() {
+            return (commons::grid::Grid::new(0, 0), (0, 0));
+        }
+        let (min_x, max_x, min_y, max_y) = elves.iter().fold(
+            (
+                Coordinate::MAX,
+                Coordinate::MIN,
+                Coordinate::MAX,
+                Coordinate::MIN,
+            ),
+            |(min_x, max_x, min_y, max_y), (x, y)| {
+                (min_x.min(*x), max_x.max(*x), min_y.min(*y), max_y.max(*y))
+            },
+        );
+
+        let width = max_x.abs_diff(min_x) as usize + 1;
+        let height = max_y.abs_diff(min_y) as usize + 1;
+        let mut snapshot = commons::grid::Grid::fill(width, height, false);
+        for (x, y) in elves {
+            snapshot[((x - min_x) as isize, (y - min_y) as isize)] = true;
+        }
+        (snapshot, (min_x.into(), min_y.into()))
+    }
+
     fn get_row_mut(&mut self, y: Coordinate) -> &mut Vec<Coordinate> {
         while y < self.min_y {
             self.rows.insert(0, vec![]);
@@ -257,6 +311,18 @@ mod tests {
         assert_eq!(first_part(&mut data), 3766);
     }
 
+    #[test]
+    fn animation_small() {
+        let data = parse(".....\n..##.\n..#..\n.....\n..##.\n.....".into()).unwrap();
+        let mut recorder = Recorder::new(&PALETTE);
+        animate(data, &mut recorder);
+        assert_eq!(recorder.frames(), 4);
+        let mut gif = Vec::new();
+        recorder.write(&mut gif).unwrap();
+        // The elves end up in a 5 by 6 rectangle, starting from a 2 by 4 one in it
+        assert_eq!(gif[6..10], [5, 0, 6, 0]);
+    }
+
     #[test]
     fn second_part_example() {
         let mut data = parse(EXAMPLE.into()).unwrap();
@@ -0,0 +1,286 @@
+//! The handheld device of the day 10: a cycle-accurate CPU with a single `X` register, and the
+//! peripherals watching it during each cycle.
+//!
+//! Each instruction declares how many cycles it takes, its effect on `X` is only visible after its
+//! last cycle. The peripherals are notified during every cycle with the current value of `X`.
+//! ```
+//! use advent_of_code_2022::device::{Cpu, Instruction, Screen};
+//!
+//! /// Move the sprite to an absolute position in 3 cycles
+//! struct Set(i64);
+//!
+//! impl Instruction for Set {
+//!     fn cycles(&self) -> usize {
+//!         3
+//!     }
+//!
+//!     fn execute(&self, x: &mut i64) {
+//!         *x = self.0;
+//!     }
+//! }
+//!
+//! let mut screen = Screen::new(6, 2);
+//! Cpu::new(&[Set(5), Set(-1), Set(1)]).with_device(&mut screen).execute(12);
+//! assert_eq!(screen.to_string(), "###.##\n#.....");
+//! ```
+
+use std::convert::Infallible;
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use commons::grid::Grid;
+use commons::machine::{Machine, Runner, Step};
+
+/// An instruction of the CPU
+pub trait Instruction {
+    /// The number of cycles needed to execute this instruction, at least 1
+    fn cycles(&self) -> usize;
+
+    /// Update the `X` register at the end of the last cycle of this instruction
+    fn execute(&self, x: &mut i64);
+}
+
+/// A peripheral of the device, watching the CPU
+pub trait Device {
+    /// Called during each cycle
+    /// # Arguments
+    /// * `cycle` - The number of the cycle, starting at 1
+    /// * `x` - The value of the `X` register during this cycle
+    fn tick(&mut self, cycle: usize, x: i64);
+}
+
+/// The CPU of the device, each step is a cycle
+pub struct Cpu<'a, I> {
+    program: &'a [I],
+    devices: Vec<&'a mut dyn Device>,
+    /// The index of the next instruction to start
+    next: usize,
+    /// The cycles left before the end of the current instruction (the one before `next`)
+    remaining: usize,
+    /// The number of cycles since the start
+    cycle: usize,
+    /// The X register
+    x: i64,
+}
+
+/// The CRT screen, drawing one pixel per cycle from left to right and top to bottom. The pixel
+/// is lit if the 3 pixels wide sprite, centered on `X`, covers it.
+#[derive(Debug, Clone)]
+pub struct Screen {
+    pixels: Grid<bool>,
+}
+
+/// Sums the signal strengths (the cycle number times `X`) during some cycles
+#[derive(Debug, Clone)]
+pub struct SignalProbe {
+    /// The first cycle to probe
+    first: usize,
+    /// The number of cycles between two probes
+    period: usize,
+    /// The sum of the signal strengths seen so far
+    pub sum: i64,
+}
+
+impl<'a, I: Instruction> Cpu<'a, I> {
+    /// Create a CPU ready to execute a program, with `X` at 1 and no peripherals
+    pub fn new(program: &'a [I]) -> Self {
+        Self {
+            program,
+            devices: Vec::new(),
+            next: 0,
+            remaining: 0,
+            cycle: 0,
+            x: 1,
+        }
+    }
+
+    /// Plug a peripheral, notified during each cycle
+    pub fn with_device(mut self, device: &'a mut dyn Device) -> Self {
+        self.devices.push(device);
+        self
+    }
+
+    /// The current value of the `X` register
+    pub fn x(&self) -> i64 {
+        self.x
+    }
+
+    /// Execute the program until its end
+    /// # Arguments
+    /// * `limit` - The maximum number of cycles to execute
+    ///
+    /// # Returns
+    /// The number of cycles executed
+    pub fn execute(mut self, limit: usize) -> usize {
+        let Ok(outcome) = Runner::new().with_limit(limit).run(&mut self);
+        outcome.steps
+    }
+}
+
+impl<I: Instruction> Machine for Cpu<'_, I> {
+    /// The next instruction, the cycles left for the current one and the X register
+    type State = (usize, usize, i64);
+    type Output = ();
+    type Error = Infallible;
+
+    fn step(&mut self) -> Result<Step<()>, Infallible> {
+        if self.remaining == 0 {
+            let Some(instruction) = self.program.get(self.next) else {
+                return Ok(Step::Halted);
+            };
+            self.next += 1;
+            self.remaining = instruction.cycles().max(1);
+        }
+
+        self.cycle += 1;
+        for device in &mut self.devices {
+            device.tick(self.cycle, self.x);
+        }
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            self.program[self.next - 1].execute(&mut self.x);
+        }
+        Ok(Step::Running)
+    }
+
+    fn reset(&mut self) {
+        self.next = 0;
+        self.remaining = 0;
+        self.cycle = 0;
+        self.x = 1;
+    }
+
+    fn position(&self) -> usize {
+        self.next
+    }
+
+    fn state(&self) -> Self::State {
+        (self.next, self.remaining, self.x)
+    }
+}
+
+impl Screen {
+    /// Create a screen with all its pixels dark
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            pixels: Grid::fill(width, height, false),
+        }
+    }
+
+    /// The pixels of the screen, true when lit
+    pub fn pixels(&self) -> &Grid<bool> {
+        &self.pixels
+    }
+
+    /// The number of cycles needed to draw the whole screen
+    pub fn cycles(&self) -> usize {
+        self.pixels.width() * self.pixels.height()
+    }
+}
+
+impl Device for Screen {
+    /// Draw the pixel of this cycle, the cycles after the last pixel are ignored
+    fn tick(&mut self, cycle: usize, x: i64) {
+        let width = self.pixels.width();
+        let Some(pixel) = self.pixels.as_mut().get_mut(cycle - 1) else {
+            return;
+        };
+        let column = ((cycle - 1) % width) as i64;
+        *pixel = (column - x).abs() <= 1;
+    }
+}
+
+impl Display for Screen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (y, line) in self.pixels.lines().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for lit in line {
+                write!(f, "{}", if *lit { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SignalProbe {
+    /// Create a probe watching the cycle `first`, then every `period` cycles
+    pub fn new(first: usize, period: usize) -> Self {
+        Self {
+            first,
+            period,
+            sum: 0,
+        }
+    }
+}
+
+impl Device for SignalProbe {
+    fn tick(&mut self, cycle: usize, x: i64) {
+        if cycle >= self.first && (cycle - self.first).is_multiple_of(self.period) {
+            self.sum += cycle as i64 * x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use commons::machine::Detection;
+
+    use super::*;
+
+    /// Add a value to X in a number of cycles
+    struct Add(i64, usize);
+
+    impl Instruction for Add {
+        fn cycles(&self) -> usize {
+            self.1
+        }
+
+        fn execute(&self, x: &mut i64) {
+            *x += self.0;
+        }
+    }
+
+    /// Records the value of X during each cycle
+    struct Recorder(Vec<(usize, i64)>);
+
+    impl Device for Recorder {
+        fn tick(&mut self, cycle: usize, x: i64) {
+            self.0.push((cycle, x));
+        }
+    }
+
+    #[test]
+    fn cycle_timing() {
+        let program = [Add(0, 1), Add(3, 2), Add(-5, 3)];
+        let mut recorder = Recorder(Vec::new());
+        let mut probe = SignalProbe::new(2, 3);
+        let cycles = Cpu::new(&program)
+            .with_device(&mut recorder)
+            .with_device(&mut probe)
+            .execute(100);
+        assert_eq!(cycles, 6);
+        assert_eq!(recorder.0, [(1, 1), (2, 1), (3, 1), (4, 4), (5, 4), (6, 4)]);
+        assert_eq!(probe.sum, 2 + 5 * 4);
+
+        let mut cpu = Cpu::new(&program);
+        // The position stays on an instruction during all its cycles, only its state can loop
+        let outcome = Runner::new().with_detection(Detection::State).run(&mut cpu);
+        assert_eq!(outcome.map(|outcome| outcome.steps), Ok(6));
+        assert_eq!(cpu.x(), -1);
+    }
+
+    #[test]
+    fn screen_sizes() {
+        let program = [Add(2, 4), Add(-3, 1)];
+        let mut screen = Screen::new(4, 2);
+        let cycles = Cpu::new(&program).with_device(&mut screen).execute(3);
+        assert_eq!(cycles, 3);
+        assert_eq!(screen.to_string(), "###.\n....");
+        assert_eq!(screen.cycles(), 8);
+
+        let mut screen = Screen::new(3, 3);
+        Cpu::new(&program).with_device(&mut screen).execute(100);
+        assert_eq!(screen.to_string(), "###\n#..\n...");
+    }
+}
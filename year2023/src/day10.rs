@@ -1,15 +1,22 @@
+use commons::arguments::Options;
 use commons::error::Result;
+use commons::grid::image::Rgb;
 use commons::grid::{Direction, Grid, Point};
 use commons::{err, WrapErr};
 
 pub const TITLE: &str = "Day 10: Pipe Maze";
+pub const OPTIONS: &[&str] = &["image"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data);
     println!("1. The farthest point from the start of the loop is at {first}");
     let second = second_part(&data);
     println!("2. The area enclosed by the loop is {second}");
+    if let Some(path) = options.values("image").first() {
+        areas(&data).to_image(4, |area| area.color()).save(path)?;
+        println!("The areas of the loop were saved in {path}");
+    }
 
     Ok(())
 }
@@ -20,6 +27,24 @@ struct Map {
     start: Point<isize>,
 }
 
+/// The position of a tile relative to the loop
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Area {
+    Outside,
+    Loop,
+    Inside,
+}
+
+impl Area {
+    fn color(self) -> Rgb {
+        match self {
+            Self::Outside => [20, 20, 30],
+            Self::Loop => [230, 230, 230],
+            Self::Inside => [60, 180, 90],
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Tile {
     Empty,
@@ -63,38 +88,43 @@ fn first_part(map: &Map) -> usize {
 }
 
 fn second_part(map: &Map) -> usize {
-    let width = map.tiles.width();
-    let height = map.tiles.height();
-    let mut loop_part = vec![None; width * height];
+    areas(map)
+        .iter()
+        .filter(|area| matches!(area, Area::Inside))
+        .count()
+}
+
+/// Where each tile is relative to the loop
+fn areas(map: &Map) -> Grid<Area> {
+    let mut loop_part = Grid::fill(map.tiles.width(), map.tiles.height(), None);
     map.loop_iter().for_each(|(_, p)| {
-        loop_part[p.x as usize + p.y as usize * width] = map.connections(p);
+        loop_part[p.tupled()] = map.connections(p);
     });
 
-    (0..height)
-        .map(|y| {
-            let mut line = (y * width)..((y + 1) * width);
-            // Skip the parts of the lines that are not inside the perimeter of the loop
-            // And points there are inside if there is an odd number of perpendicular pipes West
-            line.find(|&i| loop_part[i].is_some())
-                .and_then(|_| line.rfind(|&i| loop_part[i].is_some()))
-                .map_or(0, |_| {
-                    // Bent pipes are only partially perpendicular, so count each direction
-                    let (mut north, mut south) = (0, 0);
-                    line.filter(|&i| {
-                        if let Some([a, b]) = loop_part[i - 1] {
-                            if a == Direction::North || b == Direction::North {
-                                north += 1;
-                            }
-                            if a == Direction::South || b == Direction::South {
-                                south += 1;
-                            }
+    let mut areas = Grid::new(loop_part.width(), loop_part.height());
+    for line in loop_part.lines() {
+        // Points are inside if there is an odd number of perpendicular pipes West
+        // Bent pipes are only partially perpendicular, so count each direction
+        let (mut north, mut south) = (0, 0);
+        areas.push_line(
+            line.iter()
+                .map(|part| match part {
+                    Some([a, b]) => {
+                        if *a == Direction::North || *b == Direction::North {
+                            north += 1;
                         }
-                        loop_part[i].is_none() && north.max(south) % 2 == 1
-                    })
-                    .count()
+                        if *a == Direction::South || *b == Direction::South {
+                            south += 1;
+                        }
+                        Area::Loop
+                    }
+                    None if north.max(south) % 2 == 1 => Area::Inside,
+                    None => Area::Outside,
                 })
-        })
-        .sum()
+                .collect(),
+        );
+    }
+    areas
 }
 
 fn parse(s: std::borrow::Cow<'static, str>) -> Result<Map> {
@@ -152,6 +182,13 @@ mod tests {
     fn second_part_example_a() {
         let data = parse(EXAMPLE_2.into()).unwrap();
         assert_eq!(second_part(&data), 4);
+        let image = areas(&data).to_image(2, |area| area.color());
+        assert_eq!(image.width(), 2 * data.tiles.width());
+        let inside = (0..image.height())
+            .flat_map(|y| (0..image.width()).map(move |x| (x, y)))
+            .filter(|(x, y)| image.get(*x, *y) == Some(Area::Inside.color()))
+            .count();
+        assert_eq!(inside, 4 * 4);
     }
 
     #[test]
@@ -1,7 +1,10 @@
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 
+use commons::arguments::Options;
 use commons::error::Result;
+use commons::grid::gif::Recorder;
+use commons::grid::image::Rgb;
 use commons::grid::Grid;
 use commons::{err, WrapErr};
 
@@ -9,13 +12,33 @@ pub const TITLE: &str = "Day 14: Parabolic Reflector Dish";
 
 const CYCLES: usize = 1_000_000_000;
 
-pub fn run(raw: String) -> Result<()> {
+/// The number of cycles recorded in the animation
+const ANIMATED_CYCLES: usize = 10;
+
+/// The tilts of a cycle: north, west, south then east
+const TILTS: [fn(&mut Grid<Tile>); 4] = [
+    tilt_vertical::<true>,
+    tilt_horizontal::<false>,
+    tilt_vertical::<false>,
+    tilt_horizontal::<true>,
+];
+
+/// The colors of the animation: the empty ground, the rounded rocks and the cube rocks
+const PALETTE: [Rgb; 3] = [[0x18, 0x18, 0x20], [0xe0, 0xe0, 0xe0], [0x80, 0x50, 0x30]];
+
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(data.clone());
     println!("1. The load after the north tilt is {first}");
-    let second = second_part(data);
+    let second = second_part(data.clone());
     println!("2. The load after {CYCLES} cycles is {second}");
 
+    if let Some((mut recorder, path)) = Recorder::from_options(options, &PALETTE)? {
+        animate(data, ANIMATED_CYCLES, &mut recorder);
+        recorder.save(&path)?;
+        println!("The first {ANIMATED_CYCLES} cycles were saved in {path}");
+    }
+
     Ok(())
 }
 
@@ -73,10 +96,21 @@ fn compute_load(grid: &Grid<Tile>) -> usize {
 }
 
 fn tilt_cycle(grid: &mut Grid<Tile>) {
-    tilt_vertical::<true>(grid);
-    tilt_horizontal::<false>(grid);
-    tilt_vertical::<false>(grid);
-    tilt_horizontal::<true>(grid);
+    TILTS.iter().for_each(|tilt| tilt(grid));
+}
+
+/// Record the platform initially then after each tilt of some cycles
+fn animate(mut grid: Grid<Tile>, cycles: usize, recorder: &mut Recorder) {
+    let color = |tile: &Tile| match tile {
+        Tile::Empty => 0,
+        Tile::MovingRock => 1,
+        Tile::FixedRock => 2,
+    };
+    recorder.capture(&grid, color);
+    for tilt in TILTS.iter().cycle().take(4 * cycles) {
+        tilt(&mut grid);
+        recorder.capture(&grid, color);
+    }
 }
 
 fn tilt_vertical<const NORTH: bool>(grid: &mut Grid<Tile>) {
@@ -166,12 +200,64 @@ mod tests {
         assert_eq!(first_part(data), 136);
     }
 
+    /// Check the platform against a drawing of the puzzle, showing the differences if any
+    fn assert_platform(grid: &Grid<Tile>, expected: &'static str) {
+        let char = |tile: &Tile| match tile {
+            Tile::Empty => '.',
+            Tile::MovingRock => 'O',
+            Tile::FixedRock => '#',
+        };
+        let diff = grid.diff(&parse(expected.into()).unwrap(), char);
+        assert!(diff.is_empty(), "{diff}");
+    }
+
+    #[test]
+    fn tilts_example() {
+        let mut grid = parse(EXAMPLE.into()).unwrap();
+        let mut north = grid.clone();
+        tilt_vertical::<true>(&mut north);
+        assert_platform(
+            &north,
+            "OOOO.#.O..\nOO..#....#\nOO..O##..O\nO..#.OO...\n........#.\n\
+             ..#....#.#\n..O..#.O.O\n..O.......\n#....###..\n#....#....",
+        );
+
+        let cycles = [
+            "\
+             .....#....\n....#...O#\n...OO##...\n.OO#......\n.....OOO#.\n\
+             .O#...O#.#\n....O#....\n......OOOO\n#...O###..\n#..OO#....",
+            "\
+             .....#....\n....#...O#\n.....##...\n..O#......\n.....OOO#.\n\
+             .O#...O#.#\n....O#...O\n.......OOO\n#..OO###..\n#.OOO#...O",
+            "\
+             .....#....\n....#...O#\n.....##...\n..O#......\n.....OOO#.\n\
+             .O#...O#.#\n....O#...O\n.......OOO\n#...O###.O\n#.OOO#...O",
+        ];
+        for expected in cycles {
+            tilt_cycle(&mut grid);
+            assert_platform(&grid, expected);
+        }
+    }
+
     #[test]
     fn first_part_main() {
         let data = parse(MAIN.into()).unwrap();
         assert_eq!(first_part(data), 109_833);
     }
 
+    #[test]
+    fn animation_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let mut recorder = Recorder::new(&PALETTE).with_skip(4);
+        animate(data, 3, &mut recorder);
+        // The initial platform then after each cycle
+        assert_eq!(recorder.frames(), 4);
+        let mut gif = Vec::new();
+        recorder.write(&mut gif).unwrap();
+        // The canvas is as large as the platform
+        assert_eq!(gif[6..10], [10, 0, 10, 0]);
+    }
+
     #[test]
     fn second_part_example() {
         let data = parse(EXAMPLE.into()).unwrap();
@@ -1,18 +1,26 @@
 use itertools::Itertools;
 
+use commons::arguments::Options;
 use commons::error::Result;
 use commons::grid::{Direction, Point};
+use commons::svg::{Style, Svg};
 use commons::WrapErr;
 
 pub const TITLE: &str = "Day 18: Lavaduct Lagoon";
+pub const OPTIONS: &[&str] = &["svg"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data);
     println!("1. The area of the trench is {first}");
     let second = second_part(&data);
     println!("2. The area of the second trench is {second}");
 
+    if let Some(path) = options.values("svg").first() {
+        drawing(&data).save(path)?;
+        println!("The lagoon was drawn in {path}");
+    }
+
     Ok(())
 }
 
@@ -66,6 +74,29 @@ fn compute_lac_volume(digs: &[Dig], to_step: impl Fn(&Dig) -> (Direction, i64))
     (twice_area + boundary) / 2 + 1
 }
 
+/// Draw the lagoon of the first dig plan, each trench with the color of its edge
+fn drawing(digs: &[Dig]) -> Svg {
+    let position = |point: Point| (point.x as f64, point.y as f64);
+    let corners: Vec<Point> = digs
+        .iter()
+        .scan(Point::new(0, 0), |current, dig| {
+            *current += dig.direction.offset().multiply(dig.count as i64);
+            Some(*current)
+        })
+        .collect();
+
+    let mut svg = Svg::new().with_background([0x20; 3]);
+    let lagoon = Style::fill([0x50, 0x70, 0x90]);
+    svg.polygon(corners.iter().map(|corner| position(*corner)), &lagoon);
+    let starts = std::iter::once(Point::new(0, 0)).chain(corners.iter().copied());
+    for ((from, to), dig) in starts.zip(&corners).zip(digs) {
+        let [_, r, g, b] = dig.color.to_be_bytes();
+        let edge = Style::stroke([r, g, b]).with_width(2.0);
+        svg.line(position(from), position(*to), &edge);
+    }
+    svg
+}
+
 fn parse(s: std::borrow::Cow<'static, str>) -> Result<Vec<Dig>> {
     s.lines()
         .map(|dig| {
@@ -117,6 +148,17 @@ mod tests {
         assert_eq!(first_part(&data), 47_527);
     }
 
+    #[test]
+    fn drawing_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let mut svg = Vec::new();
+        drawing(&data).write(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        assert_eq!(svg.matches("<line").count(), data.len());
+        // The first edge, with the color of the first line
+        assert!(svg.contains(r##"y2="10.00" stroke="#70c710""##));
+    }
+
     #[test]
     fn second_part_example() {
         let data = parse(EXAMPLE.into()).unwrap();
@@ -1,16 +1,23 @@
 use std::collections::{BTreeMap, VecDeque};
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::error::Result;
 use commons::WrapErr;
 
 pub const TITLE: &str = "Day 20: Pulse Propagation";
+pub const OPTIONS: &[&str] = &["dot"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let (low, high, first) = first_part(&data);
     println!("1. After 1000 presses: {low} low pulses * {high} high pulses = {first}");
     let steps = second_part(&data)?;
     println!("2. A low pulse will be sent to 'rx' after {steps} steps");
+    if let Some(path) = options.values("dot").first() {
+        data.graph().save(path)?;
+        println!("The modules were described in {path}");
+    }
 
     Ok(())
 }
@@ -40,6 +47,32 @@ struct Modules {
     start: usize,
     end: Option<usize>,
     modules: Vec<Module>,
+    /// The name of each module, by index
+    names: Vec<String>,
+}
+
+impl Modules {
+    /// The modules linked to their outputs, with the prefix of their kind (flip-flops are boxes
+    /// and conjunctions are diamonds), the modules without outputs are just their name
+    fn graph(&self) -> Dot {
+        let mut dot = Dot::directed("modules").with_attribute("rankdir", "LR");
+        for (module, name) in self.modules.iter().zip(&self.names) {
+            let (prefix, shape) = match module.kind {
+                _ if module.outputs.is_empty() => ("", "plaintext"),
+                Kind::Broadcaster => ("", "doublecircle"),
+                Kind::FlipFlop => ("%", "box"),
+                Kind::Conjunction => ("&", "diamond"),
+            };
+            let label = format!("{prefix}{name}");
+            dot.node(name, &[("label", &label), ("shape", shape)]);
+        }
+        for (module, name) in self.modules.iter().zip(&self.names) {
+            for &output in &module.outputs {
+                dot.edge(name, &self.names[output], &[]);
+            }
+        }
+        dot
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -198,10 +231,15 @@ fn parse(s: std::borrow::Cow<'static, str>) -> Result<Modules> {
         .copied()
         .wrap_err("missing broadcaster module")?;
     let end = names.get("rx").copied();
+    let mut by_index = vec![String::new(); modules.len()];
+    for (name, index) in names {
+        by_index[index] = name.to_owned();
+    }
     Ok(Modules {
         start,
         end,
         modules,
+        names: by_index,
     })
 }
 
@@ -225,6 +263,17 @@ mod tests {
         assert_eq!(first_part(&data), (4_250, 2_750, 11_687_500));
     }
 
+    #[test]
+    fn graph_example_2() {
+        let data = parse(EXAMPLE_2.into()).unwrap();
+        let dot = data.graph();
+        assert_eq!((dot.nodes(), dot.edges()), (6, 6));
+        let dot = dot.to_string();
+        assert!(dot.contains("\"inv\" [label=\"&inv\", shape=\"diamond\"];"));
+        assert!(dot.contains("\"output\" [label=\"output\", shape=\"plaintext\"];"));
+        assert!(dot.contains("\"con\" -> \"output\";"));
+    }
+
     #[test]
     fn first_part_main() {
         let data = parse(MAIN.into()).unwrap();
@@ -1,15 +1,22 @@
 use std::collections::{HashMap, VecDeque};
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::error::Result;
 use commons::grid::{Direction, Grid, Point};
 use commons::{err, WrapErr};
 
 pub const TITLE: &str = "Day 23: A Long Walk";
+pub const OPTIONS: &[&str] = &["dot"];
 
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data);
     println!("1. The longest path will be {first}");
+    if let Some(path) = options.values("dot").first() {
+        data.graph().save(path)?;
+        println!("The crossroads were described in {path}");
+    }
     let second = second_part(data);
     println!("2. The improved longest path will be {second}");
 
@@ -83,6 +90,41 @@ impl Graph {
         max + self.additional_distance
     }
 
+    /// The crossroads linked by the length of the paths between them, with an arrow if the slopes
+    /// only allow walking one way (the first and the last crossroads are doubled)
+    fn graph(&self) -> Dot {
+        let distance = self.additional_distance.to_string();
+        let mut dot = Dot::directed("crossroads")
+            .with_attribute("layout", "neato")
+            .with_attribute(
+                "label",
+                &format!("+{distance} from the start and to the end"),
+            );
+        for node in [0, self.end] {
+            dot.node(&node.to_string(), &[("shape", "doublecircle")]);
+        }
+        for (node, paths) in self.nodes.iter().enumerate() {
+            for path in paths.iter().filter(|p| !p.blocked) {
+                let back = self.nodes[path.destination]
+                    .iter()
+                    .find(|p| p.destination == node);
+                let two_ways = back.is_some_and(|p| !p.blocked);
+                // A path walkable both ways is only kept once
+                if two_ways && path.destination < node {
+                    continue;
+                }
+                let distance = path.distance.to_string();
+                let mut attributes = vec![("label", distance.as_str())];
+                if two_ways {
+                    attributes.push(("dir", "none"));
+                }
+                let (from, to) = (node.to_string(), path.destination.to_string());
+                dot.edge(&from, &to, &attributes);
+            }
+        }
+        dot
+    }
+
     fn new(grid: Grid<u8>, start: Point<isize>, end: Point<isize>) -> Result<Self> {
         fn bit(direction: Direction) -> u8 {
             match direction {
@@ -215,6 +257,15 @@ mod tests {
         assert_eq!(first_part(&data), 94);
     }
 
+    #[test]
+    fn graph_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let dot = data.graph();
+        // All the paths between the 7 crossroads are one way because of the slopes
+        assert_eq!(dot.edges(), 10);
+        assert!(!dot.to_string().contains("dir="));
+    }
+
     #[test]
     fn first_part_main() {
         let data = parse(MAIN.into()).unwrap();
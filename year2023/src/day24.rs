@@ -1,17 +1,24 @@
 use itertools::Itertools;
 use num_bigint::BigInt;
 
+use commons::arguments::Options;
 use commons::error::Result;
 use commons::parse::LineSep;
+use commons::svg::{Style, Svg};
 use commons::{Report, WrapErr};
 
 pub const TITLE: &str = "Day 24: Never Tell Me The Odds";
+pub const OPTIONS: &[&str] = &["svg"];
 
 const FIRST_AREA: (i64, i64) = (200_000_000_000_000, 400_000_000_000_000);
-pub fn run(raw: String) -> Result<()> {
+pub fn run(raw: String, options: &Options) -> Result<()> {
     let data = parse(raw.into())?;
     let first = first_part(&data, FIRST_AREA);
     println!("1. {first} hails are crossing paths in the test area");
+    if let Some(path) = options.values("svg").first() {
+        drawing(&data, FIRST_AREA).save(path)?;
+        println!("The paths of the hails were drawn in {path}");
+    }
     let second = second_part(&data)?;
     println!("2. The rock start coordinates will sum to {second}");
 
@@ -51,6 +58,36 @@ fn first_part(hails: &[Hail<i64>], (min, max): (i64, i64)) -> usize {
         .count()
 }
 
+/// Draw the test area and the future paths of the hails in it (ignoring the Z axis), from their
+/// starting positions
+fn drawing(hails: &[Hail<i64>], (min, max): (i64, i64)) -> Svg {
+    let (min, max) = (min as f64, max as f64);
+    let mut svg = Svg::new().with_background([0xff; 3]);
+    let area = Style::stroke([0x20, 0xa0, 0x20]).with_width(2.0);
+    svg.polygon([(min, min), (max, min), (max, max), (min, max)], &area);
+
+    let path = Style::stroke([0x30, 0x60, 0xd0]).with_opacity(0.5);
+    let start = Style::fill([0xd0, 0x20, 0x20]).with_width(2.0);
+    for hail in hails {
+        let (x, y) = (hail.start.x as f64, hail.start.y as f64);
+        let (dx, dy) = (hail.speed.x as f64, hail.speed.y as f64);
+        // The times when the hail is in the area on each axis, in the future
+        let times = |position: f64, speed: f64| {
+            let (a, b) = ((min - position) / speed, (max - position) / speed);
+            (a.min(b), a.max(b))
+        };
+        let (enter_x, exit_x) = times(x, dx);
+        let (enter_y, exit_y) = times(y, dy);
+        let (enter, exit) = (enter_x.max(enter_y).max(0.0), exit_x.min(exit_y));
+        if enter <= exit {
+            let at = |time: f64| (x + dx * time, y + dy * time);
+            svg.line(at(enter), at(exit), &path);
+        }
+        svg.dot((x, y), &start);
+    }
+    svg
+}
+
 fn second_part(hails: &[Hail<i64>]) -> Result<BigInt> {
     fn find_common_plane(a: &Hail<BigInt>, b: &Hail<BigInt>) -> (Point3D<BigInt>, BigInt) {
         let p = a.start.subtract(&b.start);
@@ -252,6 +289,18 @@ mod tests {
         assert_eq!(first_part(&data, FIRST_AREA), 20_434);
     }
 
+    #[test]
+    fn drawing_example() {
+        let data = parse(EXAMPLE.into()).unwrap();
+        let mut svg = Vec::new();
+        drawing(&data, (7, 27)).write(&mut svg).unwrap();
+        let svg = String::from_utf8(svg).unwrap();
+        // The fourth hail starts out of the area, then crosses it
+        assert_eq!(svg.matches("<line").count(), data.len());
+        assert_eq!(svg.matches("<circle").count(), data.len());
+        assert!(svg.contains(r#"<line x1="110.00" y1="676.67" x2="10.00" y2="476.67""#));
+    }
+
     #[test]
     fn second_part_example() {
         let data = parse(EXAMPLE.into()).unwrap();
@@ -1,23 +1,36 @@
 use std::collections::BTreeMap;
 
+use commons::arguments::Options;
+use commons::dot::Dot;
 use commons::error::Result;
 use commons::WrapErr;
 
 pub const TITLE: &str = "Day 25: Snowverload";
+pub const OPTIONS: &[&str] = &["dot"];
 
-pub fn run(raw: String) -> Result<()> {
-    let data = parse(&raw)?;
-    let first = first_part(&data);
+pub fn run(raw: String, options: &Options) -> Result<()> {
+    let (graph, names) = parse(&raw)?;
+    let first = first_part(&graph);
     println!("1. The product of the two group sizes is {first}");
+    if let Some(path) = options.values("dot").first() {
+        wiring_graph(&graph, &names).save(path)?;
+        println!("The wiring was described in {path}");
+    }
     Ok(())
 }
 
+fn first_part(g: &[Vec<usize>]) -> usize {
+    let s_len = split(g).iter().filter(|&&o| o).count();
+    s_len * (g.len() - s_len)
+}
+
 /// Start with all nodes on one side
 /// Repeatedly find the node most connected to the other side and move it other
 /// When there are exactly 3 connections to the other side, we found a minimal cut
-fn first_part(g: &[Vec<usize>]) -> usize {
+/// Returns true for the nodes staying on the first side
+fn split(g: &[Vec<usize>]) -> Vec<bool> {
     let mut s = vec![true; g.len()];
-    let s_len = loop {
+    loop {
         let mut max_node = None;
         let mut max_neighbours = None;
         let total_neighbours: usize = (0..s.len())
@@ -35,17 +48,40 @@ fn first_part(g: &[Vec<usize>]) -> usize {
             })
             .sum();
 
-        if total_neighbours == 3 {
-            break s.iter().filter(|&&o| o).count();
-        } else if let Some(max_node) = max_node {
-            s[max_node] = false;
+        match max_node {
+            Some(max_node) if total_neighbours != 3 => s[max_node] = false,
+            _ => return s,
         }
-    };
+    }
+}
 
-    s_len * (g.len() - s_len)
+/// The components colored by group, with the 3 wires to disconnect in red
+fn wiring_graph(g: &[Vec<usize>], names: &[&str]) -> Dot {
+    let sides = split(g);
+    let mut dot = Dot::undirected("wiring").with_attribute("layout", "sfdp");
+    for (name, side) in names.iter().zip(&sides) {
+        let color = if *side { "lightblue" } else { "lightgreen" };
+        dot.node(name, &[("style", "filled"), ("fillcolor", color)]);
+    }
+    for (from, to) in g.iter().enumerate() {
+        // Each wire is in both components, only keep it once
+        for &to in to.iter().filter(|&&to| to > from) {
+            if sides[from] == sides[to] {
+                dot.edge(names[from], names[to], &[]);
+            } else {
+                dot.edge(
+                    names[from],
+                    names[to],
+                    &[("color", "red"), ("penwidth", "3")],
+                );
+            }
+        }
+    }
+    dot
 }
 
-fn parse(s: &str) -> Result<Vec<Vec<usize>>> {
+/// The components connected to each component, and the name of each component
+fn parse(s: &str) -> Result<(Vec<Vec<usize>>, Vec<&str>)> {
     let mut name_to_id: BTreeMap<&str, usize> = BTreeMap::new();
     let mut vertices: Vec<Vec<usize>> = vec![];
     s.lines().try_for_each(|line| {
@@ -78,7 +114,11 @@ fn parse(s: &str) -> Result<Vec<Vec<usize>>> {
             .wrap_err_with(|| format!("invalid line {line:?}"))
     })?;
 
-    Ok(vertices)
+    let mut names = vec![""; vertices.len()];
+    for (name, id) in name_to_id {
+        names[id] = name;
+    }
+    Ok((vertices, names))
 }
 
 #[cfg(test)]
@@ -90,13 +130,26 @@ mod tests {
 
     #[test]
     fn first_part_example() {
-        let data = parse(EXAMPLE).unwrap();
+        let (data, _) = parse(EXAMPLE).unwrap();
         assert_eq!(first_part(&data), 54);
     }
 
     #[test]
     fn first_part_main() {
-        let data = parse(MAIN).unwrap();
+        let (data, _) = parse(MAIN).unwrap();
         assert_eq!(first_part(&data), 495_607);
     }
+
+    #[test]
+    fn wiring_graph_example() {
+        let (data, names) = parse(EXAMPLE).unwrap();
+        let dot = wiring_graph(&data, &names);
+        assert_eq!((dot.nodes(), dot.edges()), (15, 33));
+        let dot = dot.to_string();
+        assert_eq!(dot.matches("color=\"red\"").count(), 3);
+        assert!(
+            dot.contains("\"hfx\" -- \"pzl\" [color=\"red\"")
+                || dot.contains("\"pzl\" -- \"hfx\" [color=\"red\"")
+        );
+    }
 }